@@ -1,15 +1,45 @@
 //! 清理操作处理器模块
 use core::{
-    cleaner::FileCleaner,
-    progress::Progress,
+    cleaner::{CleaningPreview, CleaningResult, FileCleaner, Outcome},
+    errors::Error,
+    filesystem::{DryRunFs, Fs, RealFs},
+    history::ScanHistory,
     display::*,
 };
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
 use crate::{
     AppResult,
     operations::CliOperations,
+    output::{self, Status},
 };
 
+/// `clean --json` 输出的数据
+#[derive(Serialize)]
+struct CleanOutput<'a> {
+    scan_id: &'a str,
+    preview: Option<&'a CleaningPreview>,
+    result: Option<&'a CleaningResult>,
+    /// 清理记录文件，模拟运行时不保存
+    log: Option<&'a Path>,
+}
+
+/// 覆盖配置文件的清理选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CleanOptions {
+    /// 清理后删除空目录
+    pub prune_empty_dirs: bool,
+    /// 清理孤立缩略图
+    pub clean_orphaned_thumbnails: bool,
+    /// 只保留原始文件，清理全部缩略图
+    pub keep_only_originals: bool,
+    /// 清理残留文件
+    pub clean_leftovers: bool,
+    /// 模拟运行：完整执行清理流程但不修改文件系统，也不需要确认
+    pub dry_run: bool,
+}
+
 /// 清理操作处理器
 pub struct CleanerHandler<'a> {
     ops: &'a CliOperations,
@@ -22,29 +52,142 @@ impl<'a> CleanerHandler<'a> {
     }
 
     /// 执行
-    pub fn execute(&self, mode: &str, force: bool) -> AppResult<()> {
-        let file_cleaner = FileCleaner::new(&self.ops.settings().scanner)?;
+    ///
+    /// `scan` 指定使用哪一次扫描的结果，未指定时使用最新的一次。
+    pub fn execute(&self, mode: &str, force: bool, target_free: Option<u64>, scan: Option<&str>, options: CleanOptions) -> AppResult<Status> {
+        let mut history = ScanHistory::open(&self.ops.settings().scanner)?;
+        let entry = history.resolve(scan)?.clone();
+        if entry.cleaned {
+            return Err(format!("扫描结果 {} 已用于清理，请重新扫描", entry.id).into());
+        }
+        let file_cleaner = FileCleaner::open(&history.path_of(&entry))?;
+
+        let mut settings = self.ops.settings().cleaner.clone();
+        settings.mode = self.ops.parse_cleaning_mode(mode);
+        settings.prune_empty_dirs |= options.prune_empty_dirs;
+        settings.clean_orphaned_thumbnails |= options.clean_orphaned_thumbnails;
+        settings.keep_only_originals |= options.keep_only_originals;
+        settings.clean_leftovers |= options.clean_leftovers;
+
+        // 按空间预算清理时只选择重复文件，配置中开启的其他类别不生效
+        if target_free.is_some()
+            && (settings.clean_leftovers || settings.clean_orphaned_thumbnails || settings.keep_only_originals)
+        {
+            let note = "按空间预算清理时不清理残留文件和缩略图，忽略配置中的相应设置";
+            // JSON 模式下 stdout 只输出 JSON 文档
+            if self.ops.json() { eprintln!("⚠️  {}", note) } else { println!("⚠️  {}", note) }
+        }
+
+        let preview = match target_free {
+            Some(target) => file_cleaner.preview_with_budget(target),
+            None => file_cleaner.preview(&settings),
+        };
+        let preview = match preview {
+            Ok(preview) => preview,
+            Err(Error::NothingToClean) => {
+                return self.finish(&entry.id, None, None, None, Status::NothingToDo, Some("找到扫描结果，但无可清理文件"));
+            }
+            Err(e) => return Err(e.into()),
+        };
 
-        let preview = file_cleaner.preview()?;
-        println!("{}", preview.display_details());
+        // JSON 模式下预览只在需要确认时写入 stderr，结果统一包含在 JSON 文档中
+        if !self.ops.json() {
+            println!("{}", preview.display_details());
+            if let Some(target) = preview.target_freed_space {
+                println!(
+                    "目标释放空间: {}，已选文件累计: {}",
+                    format_size(target),
+                    format_size(preview.estimated_freed_disk_space)
+                );
+                if preview.estimated_freed_disk_space < target {
+                    println!("可清理文件不足以达到目标，将清理全部候选文件");
+                }
+            }
+        } else if !force && !options.dry_run {
+            eprintln!("{}", preview.display_details());
+        }
         
-        let should_clean = if force {
+        let should_clean = if force || options.dry_run {
             true
         } else {
             self.ops.get_user_confirmation("确认执行清理操作？")?
         };
 
-        if should_clean {
-            let mode = self.ops.parse_cleaning_mode(mode);
-            let progress = Progress::Bar(self.ops.create_progress_bar()?);
-            let clean_result = preview.clean_with_progress(mode, &progress).ok_or("没能清理任何文件")?;
+        if !should_clean {
+            return self.finish(&entry.id, Some(&preview), None, None, Status::NothingToDo, Some("清理已取消"));
+        }
+
+        let cancel = self.ops.interruptible()?;
+        let progress = self.ops.create_progress()?;
+        let fs: Box<dyn Fs> = if options.dry_run { Box::new(DryRunFs::new()) } else { Box::new(RealFs) };
+        let clean_result = preview.clean_with_fs(fs.as_ref(), &settings, &progress, &cancel);
+        progress.finish(if cancel.is_cancelled() { "清理已中断" } else { "清理完成" });
+        let clean_result = clean_result.ok_or("没能清理任何文件")?;
 
-            println!("{}", clean_result.display_summary());
-            file_cleaner.delete_scan_result()?;
+        let mut log = None;
+        if !clean_result.simulated {
+            log = Some(history.save_cleaning(&entry.id, &clean_result)?);
+            // 中断时只要删除过文件，扫描结果就不再反映磁盘状态
+            let deleted_any = clean_result.outcomes.iter().any(|o| o.outcome == Outcome::Deleted);
+            if !clean_result.cancelled || deleted_any {
+                history.mark_cleaned(&entry.id)?;
+            }
+        }
+
+        let (status, message) = if clean_result.cancelled {
+            (Status::Cancelled, Some("清理已中断，以下为已完成的操作"))
+        } else if clean_result.is_partial() {
+            (Status::Partial, None)
         } else {
-            println!("清理已取消");
+            (Status::Ok, None)
+        };
+        self.finish(&entry.id, Some(&preview), Some(&clean_result), log, status, message)
+    }
+
+    /// 输出清理结果
+    fn finish(
+        &self,
+        scan_id: &str,
+        preview: Option<&CleaningPreview>,
+        result: Option<&CleaningResult>,
+        log: Option<PathBuf>,
+        status: Status,
+        message: Option<&str>,
+    ) -> AppResult<Status> {
+        if self.ops.json() {
+            let data = CleanOutput { scan_id, preview, result, log: log.as_deref() };
+            output::print_json("clean", status, message, Some(&data))?;
+            return Ok(status);
         }
 
-        Ok(())
+        if let Some(message) = message {
+            println!("{}", message);
+        }
+        if let Some(result) = result {
+            if result.simulated {
+                println!("模拟运行，未修改任何文件:");
+            }
+            println!("{}", result.display_summary());
+            for space in &result.free_space {
+                println!(
+                    "可用空间 ({}): {} → {} ({})",
+                    space.root.display(),
+                    format_size(space.before),
+                    format_size(space.after),
+                    space.gained().format_display()
+                );
+            }
+            for outcome in result.outcomes.iter() {
+                match &outcome.outcome {
+                    Outcome::Failed(reason) => println!("⚠️  删除失败: {} - {}", outcome.path.display(), reason),
+                    Outcome::Skipped(reason) => println!("⏭️  已跳过: {} - {}", outcome.path.display(), reason),
+                    Outcome::Deleted => {}
+                }
+            }
+        }
+        if let Some(log) = log {
+            println!("清理记录已保存到: {}", log.display());
+        }
+        Ok(status)
     }
 }
@@ -1,6 +1,6 @@
 //! 配置操作处理器模块
 
-use crate::{AppResult, operations::CliOperations};
+use crate::{AppResult, operations::CliOperations, output::{self, Status}};
 
 /// 配置操作处理器
 pub struct ConfigHandler<'a> {
@@ -14,11 +14,42 @@ impl<'a> ConfigHandler<'a> {
     }
 
     /// 显示当前配置
-    pub fn execute(&self) -> AppResult<()> {
+    pub fn execute(&self) -> AppResult<Status> {
+        if self.ops.json() {
+            output::print_json("config", Status::Ok, None, Some(self.ops.settings()))?;
+            return Ok(Status::Ok);
+        }
+
         println!("当前配置:");
         println!("  微信缓存路径: {:?}", self.ops.settings().wechat.cache_path);
+        println!("  扫描结果存储格式: {:?}", self.ops.settings().scanner.storage);
+        let scanner = &self.ops.settings().scanner;
+        match scanner.threads {
+            Some(threads) => println!("  扫描线程数: {}", threads),
+            None => println!("  扫描线程数: 全部核心"),
+        }
+        match scanner.read_limit_mbps() {
+            Some(mbps) => println!("  最大读取带宽: {} MB/s", mbps),
+            None => println!("  最大读取带宽: 不限速"),
+        }
+        println!("  机械硬盘模式: {}", scanner.hdd_mode);
+        println!("  符号链接处理: {}", scanner.symlinks.as_str());
+        println!("  单一文件系统: {}", scanner.one_file_system);
         println!("  默认清理模式: {:?}", self.ops.settings().cleaner.mode);
-        println!("  缓存文件模式: {:?}", self.ops.settings().wechat.cache_patterns);
-        Ok(())
+        println!("  清理空目录: {}", self.ops.settings().cleaner.prune_empty_dirs);
+        println!("  清理孤立缩略图: {}", self.ops.settings().cleaner.clean_orphaned_thumbnails);
+        println!("  只保留原始文件: {}", self.ops.settings().cleaner.keep_only_originals);
+        println!("  清理残留文件: {}", self.ops.settings().cleaner.clean_leftovers);
+        println!("  残留文件最短存在时间: {} 小时", self.ops.settings().cleaner.leftover_min_age_hours);
+        let wechat = &self.ops.settings().wechat;
+        if !wechat.cache_patterns.is_empty() {
+            println!("  缓存文件模式: {:?}", wechat.cache_patterns);
+        }
+        println!("  模式规则:");
+        for rule in &wechat.pattern_rules {
+            let state = if wechat.disabled_patterns.contains(&rule.name) { "（已禁用）" } else { "" };
+            println!("    {}{}: {} (捕获组 {})", rule.name, state, rule.regex, rule.group);
+        }
+        Ok(Status::Ok)
     }
 }
@@ -1,12 +1,16 @@
 //! 命令处理器模块
 //!
-//! 提供不同命令的处理器实现，包括扫描、清理和配置操作。
+//! 提供不同命令的处理器实现，包括扫描、扫描历史、清理、配置和模式规则测试操作。
 
 pub mod scan;
+pub mod scans;
 pub mod cleaner;
 pub mod config;
+pub mod pattern;
 
-pub use scan::ScanHandler;
-pub use cleaner::CleanerHandler;
+pub use scan::{ScanHandler, ScanOptions};
+pub use scans::ScansHandler;
+pub use cleaner::{CleanerHandler, CleanOptions};
 pub use config::ConfigHandler;
+pub use pattern::{PatternHandler, PatternTestOptions};
 
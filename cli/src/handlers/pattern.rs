@@ -0,0 +1,162 @@
+//! 模式规则测试处理器模块
+
+use core::cancel::CancellationToken;
+use core::errors::Error;
+use core::file_utils::{FileInfo, PatternPreviewFile, PatternPreviewGroup, PatternRule, PatternSet};
+use core::filesystem::{RealFs, WalkOptions};
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::{AppResult, operations::CliOperations, output::{self, Status}};
+
+/// 被测试规则的名称
+const TESTED_RULE: &str = "测试规则";
+
+/// 每个示例分组最多列出的文件数
+const SAMPLE_FILES: usize = 5;
+
+/// 模式规则测试选项
+#[derive(Debug, Clone, Copy)]
+pub struct PatternTestOptions {
+    /// 原始名称所在的捕获组
+    pub group: usize,
+    /// 按旧版 `cache_patterns` 的方式解释正则表达式
+    pub legacy: bool,
+    /// 只使用被测试的规则，不附加配置中启用的规则
+    pub alone: bool,
+    /// 对配置的缓存目录中的全部文件测试
+    pub cache: bool,
+    /// 显示的示例分组数
+    pub samples: usize,
+}
+
+/// `pattern test --json` 输出的数据
+#[derive(Serialize)]
+struct PatternTestOutput<'a> {
+    /// 按顺序尝试的规则，第一条为被测试的规则
+    rules: Vec<&'a PatternRule>,
+    /// 测试的文件数
+    files_tested: usize,
+    /// 被测试规则匹配的文件数
+    files_matched: usize,
+    /// 逐个文件的结果（测试缓存目录时只包含被测试规则匹配的文件）
+    files: Vec<&'a PatternPreviewFile>,
+    /// 示例分组
+    groups: Vec<&'a PatternPreviewGroup>,
+}
+
+/// 模式规则处理器
+pub struct PatternHandler<'a> {
+    ops: &'a CliOperations,
+}
+
+impl<'a> PatternHandler<'a> {
+    /// 创建新的模式规则处理器
+    pub fn new(ops: &'a CliOperations) -> Self {
+        Self { ops }
+    }
+
+    /// 测试正则表达式对给定文件或缓存目录的分组效果
+    ///
+    /// 被测试的规则排在配置中启用的规则之前，因此结果与加入该规则后的扫描一致（不比对文件内容）。
+    pub fn test(&self, regex: &str, paths: &[PathBuf], options: PatternTestOptions) -> AppResult<Status> {
+        let tested = if options.legacy {
+            PatternRule::legacy(TESTED_RULE, regex)
+        } else {
+            PatternRule::new(TESTED_RULE, regex, options.group)
+        };
+        let mut rules = vec![tested];
+        if !options.alone {
+            rules.extend(self.ops.settings().wechat.active_pattern_rules());
+        }
+        let patterns = PatternSet::new(&rules)?;
+
+        let files = if options.cache {
+            self.cache_files()?
+        } else {
+            paths.iter().map(|path| FileInfo::new(path, 0, 0)).collect()
+        };
+        let preview = patterns.preview(&files);
+
+        let is_tested = |rule: &Option<String>| rule.as_deref() == Some(TESTED_RULE);
+        let files_matched = preview.files.iter().filter(|file| is_tested(&file.rule)).count();
+        let listed: Vec<&PatternPreviewFile> = preview
+            .files
+            .iter()
+            .filter(|file| !options.cache || is_tested(&file.rule))
+            .collect();
+        let samples: Vec<&PatternPreviewGroup> = preview
+            .groups
+            .iter()
+            .filter(|group| !options.cache || group.rules.iter().any(|rule| rule == TESTED_RULE))
+            .take(options.samples)
+            .collect();
+
+        let (status, message) = if files_matched == 0 {
+            (Status::NothingToDo, Some("测试规则没有匹配任何文件"))
+        } else {
+            (Status::Ok, None)
+        };
+
+        if self.ops.json() {
+            let data = PatternTestOutput {
+                rules: patterns.rules().collect(),
+                files_tested: files.len(),
+                files_matched,
+                files: listed,
+                groups: samples,
+            };
+            output::print_json("pattern test", status, message, Some(&data))?;
+            return Ok(status);
+        }
+
+        println!("{}: {} (捕获组 {})", TESTED_RULE, rules[0].regex, rules[0].group);
+        if rules.len() > 1 {
+            println!("之后依次尝试配置中的 {} 条规则", rules.len() - 1);
+        }
+        println!("测试规则匹配了 {} / {} 个文件", files_matched, files.len());
+        println!();
+
+        for file in &listed {
+            let name = file.name.as_deref().unwrap_or("（无法解码的文件名）");
+            let rule = file.rule.as_deref().unwrap_or("无规则，去掉扩展名");
+            println!("{}", file.path.display());
+            println!("  → {}  [{}]  组内 {} 个文件", name, rule, file.group_size);
+        }
+
+        if !samples.is_empty() {
+            println!();
+            println!("示例分组:");
+            for group in &samples {
+                let rules = if group.rules.is_empty() { "无".to_string() } else { group.rules.join("、") };
+                println!("  {} ({} 个文件，规则: {})", group.name, group.files.len(), rules);
+                for path in group.files.iter().take(SAMPLE_FILES) {
+                    println!("    {}", path.display());
+                }
+                if group.files.len() > SAMPLE_FILES {
+                    println!("    … 另有 {} 个文件", group.files.len() - SAMPLE_FILES);
+                }
+            }
+        }
+        if let Some(message) = message {
+            println!();
+            println!("{}", message);
+        }
+        Ok(status)
+    }
+
+    /// 按扫描设置收集配置的缓存目录中的文件
+    fn cache_files(&self) -> AppResult<Vec<FileInfo>> {
+        let settings = self.ops.settings();
+        let root = settings.wechat.cache_path.as_ref().ok_or(Error::CacheNotFound)?;
+        let options = WalkOptions {
+            symlinks: settings.scanner.symlinks,
+            one_file_system: settings.scanner.one_file_system,
+        };
+
+        let progress = self.ops.create_progress()?;
+        let collected = FileInfo::collect_with(&RealFs, root, &options, &progress, &CancellationToken::new());
+        progress.finish("读取完成");
+        Ok(collected.ok_or(Error::CacheNotFound)?.files)
+    }
+}
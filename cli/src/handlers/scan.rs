@@ -1,10 +1,45 @@
 //! 扫描操作处理器模块
 
-use core::scanner::{FileScanner};
-use core::progress::Progress;
+use core::history::ScanHistory;
+use core::scanner::{ScanResult, ScannerBuilder};
 use core::display::*;
+use core::filesystem::SymlinkPolicy;
+use core::report::ReportFormat;
+use serde::Serialize;
+use std::path::Path;
 
-use crate::{AppResult, operations::CliOperations};
+use crate::{AppResult, operations::CliOperations, output::{self, Status}};
+
+/// 导出的扫描报告
+#[derive(Serialize)]
+struct ExportedReport<'a> {
+    path: &'a Path,
+    format: ReportFormat,
+}
+
+/// `scan --json` 输出的数据
+#[derive(Serialize)]
+struct ScanOutput<'a> {
+    scan: &'a ScanResult,
+    report: Option<ExportedReport<'a>>,
+}
+
+/// 覆盖配置文件的扫描选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanOptions {
+    /// 从上次中断的检查点继续
+    pub resume: bool,
+    /// 扫描线程数
+    pub threads: Option<usize>,
+    /// 最大读取带宽（MB/s）
+    pub max_read_mbps: Option<u64>,
+    /// 机械硬盘模式
+    pub hdd: bool,
+    /// 符号链接的处理方式
+    pub symlinks: Option<SymlinkPolicy>,
+    /// 允许跨越文件系统边界
+    pub cross_file_systems: bool,
+}
 
 /// 扫描操作处理器
 pub struct ScanHandler<'a> {
@@ -17,22 +52,74 @@ impl<'a> ScanHandler<'a> {
         Self { ops }
     }
 
-    pub fn execute(&self, verbose: bool) -> AppResult<()> {
-        let scanner = FileScanner::new(&self.ops.settings().wechat)?;
+    pub fn execute(&self, verbose: bool, output: Option<&Path>, format: Option<ReportFormat>, options: ScanOptions) -> AppResult<Status> {
+        let mut settings = self.ops.settings().scanner.clone();
+        settings.threads = options.threads.or(settings.threads);
+        settings.max_read_mbps = options.max_read_mbps.or(settings.max_read_mbps);
+        settings.hdd_mode |= options.hdd;
+        settings.symlinks = options.symlinks.unwrap_or(settings.symlinks);
+        settings.one_file_system &= !options.cross_file_systems;
+        let settings = &settings;
+
+        let scanner = ScannerBuilder::from_settings(&self.ops.settings().wechat)
+            .cancellation(self.ops.interruptible()?)
+            .checkpoint(settings.checkpoint_path())
+            .resume(options.resume)
+            .symlinks(settings.symlinks)
+            .one_file_system(settings.one_file_system)
+            .build()?;
         
-        let progress = Progress::Bar(self.ops.create_progress_bar()?);
-        let settings = &self.ops.settings().scanner;
+        let progress = self.ops.create_progress()?;
         
-        if let Some(result) = scanner.scan_with_progress(settings, &progress) {
-            if verbose {
-                println!("{}", result.display_details());
+        let result = scanner.scan_with_progress(settings, &progress);
+        progress.finish(match &result {
+            Some(result) if result.interrupted => "扫描已中断",
+            Some(_) => "扫描完成",
+            None => "无重复文件",
+        });
+        let Some(mut result) = result else {
+            if self.ops.json() {
+                output::print_json::<()>("scan", Status::NothingToDo, Some("未扫描到任何文件"), None)?;
             } else {
-                println!("{}", result.display_summary());
+                println!("未扫描到任何文件");
+            }
+            return Ok(Status::NothingToDo);
+        };
+
+        // 中断的扫描同样保存到历史，其中已确认的重复组仍可用于清理
+        ScanHistory::open(settings)?.record(&mut result)?;
+        let status = if result.interrupted { Status::Cancelled } else { Status::Ok };
+        let message = result.interrupted.then_some("扫描已中断，已保存部分结果");
+
+        let report = match output {
+            Some(output) => {
+                let format = format
+                    .or_else(|| ReportFormat::from_path(output))
+                    .unwrap_or(ReportFormat::Json);
+                result.export(output, format)?;
+                Some(ExportedReport { path: output, format })
             }
-            
-            result.save()?;
+            None => None,
+        };
+
+        if self.ops.json() {
+            let data = ScanOutput { scan: &result, report };
+            output::print_json("scan", status, message, Some(&data))?;
+            return Ok(status);
         }
-        
-        Ok(())
+
+        if let Some(message) = message {
+            println!("⚠️  {}", message);
+        }
+        if verbose {
+            println!("{}", result.display_details());
+        } else {
+            println!("{}", result.display_summary());
+        }
+        if let Some(report) = report {
+            println!("扫描报告已导出到: {} ({})", report.path.display(), report.format);
+        }
+
+        Ok(status)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,115 @@
+//! 扫描历史处理器模块
+
+use core::diff::ScanDiff;
+use core::display::*;
+use core::history::{self, ScanHistory};
+
+use crate::{AppResult, operations::CliOperations, output::{self, Status}};
+
+/// 扫描历史处理器
+pub struct ScansHandler<'a> {
+    ops: &'a CliOperations,
+}
+
+impl<'a> ScansHandler<'a> {
+    /// 创建新的扫描历史处理器
+    pub fn new(ops: &'a CliOperations) -> Self {
+        Self { ops }
+    }
+
+    /// 列出所有扫描，最新的在最后
+    pub fn list(&self) -> AppResult<Status> {
+        let history = ScanHistory::open(&self.ops.settings().scanner)?;
+        let status = if history.entries().is_empty() { Status::NothingToDo } else { Status::Ok };
+
+        if self.ops.json() {
+            output::print_json("scans list", status, None, Some(&history.entries()))?;
+            return Ok(status);
+        }
+
+        if history.entries().is_empty() {
+            println!("暂无扫描记录，请先执行扫描命令");
+            return Ok(status);
+        }
+
+        println!("{:<20} {:<22} {:>8} {:>10}  状态", "编号", "扫描时间", "文件数", "重复文件");
+        for entry in history.entries() {
+            let status = match (entry.cleaned, entry.interrupted) {
+                (true, _) => "已清理",
+                (false, true) => "已中断",
+                (false, false) => "",
+            };
+            println!(
+                "{:<20} {:<22} {:>8} {:>10}  {}",
+                entry.id,
+                history::format_timestamp(entry.created_at),
+                entry.total_files_count,
+                entry.duplicate_count,
+                status
+            );
+        }
+        Ok(status)
+    }
+
+    /// 显示一次扫描的结果，未指定编号时显示最新的一次
+    pub fn show(&self, id: Option<&str>, verbose: bool) -> AppResult<Status> {
+        let history = ScanHistory::open(&self.ops.settings().scanner)?;
+        let entry = history.resolve(id)?;
+        let result = history.load(&entry.id)?;
+
+        if self.ops.json() {
+            output::print_json("scans show", Status::Ok, None, Some(&result))?;
+            return Ok(Status::Ok);
+        }
+
+        println!("扫描时间: {}", history::format_timestamp(entry.created_at));
+        if verbose {
+            println!("{}", result.display_details());
+        } else {
+            println!("{}", result.display_summary());
+        }
+        Ok(Status::Ok)
+    }
+
+    /// 比较两次扫描，未指定对比扫描时与最新的一次比较
+    pub fn diff(&self, from: &str, to: Option<&str>, verbose: bool) -> AppResult<Status> {
+        let history = ScanHistory::open(&self.ops.settings().scanner)?;
+        let from = history.load(&history.find(from)?.id)?;
+        let to = history.load(&history.resolve(to)?.id)?;
+
+        let diff = ScanDiff::between(&from, &to);
+        let message = (!diff.has_usage()).then_some("扫描结果来自不记录占用统计的旧版本，无法计算缓存占用变化");
+        if self.ops.json() {
+            output::print_json("scans diff", Status::Ok, message, Some(&diff))?;
+            return Ok(Status::Ok);
+        }
+
+        if let Some(message) = message {
+            println!("⚠️  {}", message);
+        }
+        if verbose {
+            println!("{}", diff.display_details());
+        } else {
+            println!("{}", diff.display_summary());
+        }
+        Ok(Status::Ok)
+    }
+
+    /// 删除扫描记录
+    pub fn remove(&self, ids: &[String]) -> AppResult<Status> {
+        let mut history = ScanHistory::open(&self.ops.settings().scanner)?;
+        let mut removed = Vec::with_capacity(ids.len());
+        for id in ids {
+            removed.push(history.remove(id)?);
+        }
+
+        if self.ops.json() {
+            output::print_json("scans rm", Status::Ok, None, Some(&removed))?;
+        } else {
+            for entry in &removed {
+                println!("已删除扫描: {}", entry.id);
+            }
+        }
+        Ok(Status::Ok)
+    }
+}
@@ -13,13 +13,28 @@
 //! - 实时进度显示
 //! - 交互式确认机制
 //! - 详细的统计信息
+//!
+//! # 退出码
+//! - `0`: 成功
+//! - `1`: 出错
+//! - `2`: 没有需要处理的内容
+//! - `3`: 部分失败
+//! - `64`: 命令行参数错误
+//! - `130`: 被 Ctrl-C 中断
 
 mod operations;
 mod handlers;
+mod output;
+mod progress;
 
 use clap::{Parser, Subcommand};
+use core::filesystem::SymlinkPolicy;
+use core::report::ReportFormat;
+use std::path::PathBuf;
+use std::str::FromStr;
 use operations::CliOperations;
-use handlers::{ScanHandler, CleanerHandler, ConfigHandler};
+use handlers::{ScanHandler, ScanOptions, ScansHandler, CleanerHandler, CleanOptions, ConfigHandler, PatternHandler, PatternTestOptions};
+use output::Status;
 
 /// 应用错误类型
 type AppResult<T> = Result<T, Box<dyn std::error::Error>>;
@@ -28,6 +43,10 @@ type AppResult<T> = Result<T, Box<dyn std::error::Error>>;
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    /// 以 JSON 格式输出结果（stdout 上只有一个 JSON 文档，进度写入 stderr）
+    #[arg(long, global = true)]
+    json: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -39,6 +58,43 @@ enum Commands {
         /// 显示详细信息
         #[arg(short, long)]
         verbose: bool,
+
+        /// 将扫描报告导出到指定文件
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// 报告格式: json、csv 或 html（默认根据文件扩展名推断）
+        #[arg(long, requires = "output", value_parser = ReportFormat::from_str)]
+        format: Option<ReportFormat>,
+
+        /// 从上次中断的扫描检查点继续
+        #[arg(long)]
+        resume: bool,
+
+        /// 扫描使用的线程数（默认使用全部 CPU 核心）
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        threads: Option<u16>,
+
+        /// 计算哈希时的最大读取带宽（MB/s）
+        #[arg(long, value_name = "MB", value_parser = clap::value_parser!(u64).range(1..))]
+        max_read_mbps: Option<u64>,
+
+        /// 机械硬盘模式：按磁盘顺序逐个计算哈希
+        #[arg(long)]
+        hdd: bool,
+
+        /// 符号链接的处理方式: skip、follow 或 report
+        #[arg(long, value_name = "POLICY", value_parser = SymlinkPolicy::from_str)]
+        symlinks: Option<SymlinkPolicy>,
+
+        /// 允许进入挂载在缓存目录下的其他文件系统
+        #[arg(long)]
+        cross_file_systems: bool,
+    },
+    /// 管理扫描历史
+    Scans {
+        #[command(subcommand)]
+        action: ScansCommand,
     },
     /// 清理重复文件（总是显示预览并要求确认）
     Clean {
@@ -49,47 +105,247 @@ enum Commands {
         /// 跳过确认，直接清理
         #[arg(short, long)]
         force: bool,
+
+        /// 使用指定编号的扫描结果（默认使用最新的一次扫描）
+        #[arg(long, value_name = "ID")]
+        scan: Option<String>,
+
+        /// 只释放指定大小的空间（如 20GB），优先删除价值最低的文件
+        #[arg(long, visible_alias = "budget", value_name = "SIZE", value_parser = parse_size)]
+        target_free: Option<u64>,
+
+        /// 清理后删除变为空的目录（不会删除缓存根目录）
+        #[arg(long)]
+        prune_empty_dirs: bool,
+
+        /// 清理同一目录中找不到原始文件的孤立缩略图
+        #[arg(long, conflicts_with = "target_free")]
+        clean_orphaned_thumbnails: bool,
+
+        /// 只保留原始文件：清理全部缩略图，包括原始文件仍在的缩略图
+        #[arg(long, conflicts_with = "target_free")]
+        keep_only_originals: bool,
+
+        /// 清理零字节文件和未完成下载的临时文件（只清理超过配置的最短存在时间的文件）
+        #[arg(long, conflicts_with = "target_free")]
+        clean_leftovers: bool,
+
+        /// 模拟运行：完整执行清理流程并报告每个文件的结果，但不修改任何文件
+        #[arg(long)]
+        dry_run: bool,
     },
     /// 显示配置信息
     Config,
+    /// 测试副本文件名规则
+    Pattern {
+        #[command(subcommand)]
+        action: PatternCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum PatternCommand {
+    /// 显示正则表达式对文件名的提取结果和形成的分组，不修改任何文件
+    Test {
+        /// 要测试的正则表达式，匹配完整文件名
+        regex: String,
+
+        /// 要测试的文件路径或文件名（文件不需要存在）
+        #[arg(required_unless_present = "cache")]
+        paths: Vec<PathBuf>,
+
+        /// 原始名称所在的捕获组
+        #[arg(long, value_name = "N", default_value_t = 1)]
+        group: usize,
+
+        /// 按旧版 cache_patterns 的方式解释：取第一个匹配位置之前的部分
+        #[arg(long, conflicts_with = "group")]
+        legacy: bool,
+
+        /// 只使用被测试的规则，不附加配置中启用的规则
+        #[arg(long)]
+        alone: bool,
+
+        /// 对配置的微信缓存目录中的全部文件测试
+        #[arg(long, conflicts_with = "paths")]
+        cache: bool,
+
+        /// 显示的示例分组数
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        samples: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScansCommand {
+    /// 列出所有扫描
+    List,
+    /// 显示一次扫描的结果
+    Show {
+        /// 扫描编号（支持唯一前缀，默认为最新的一次）
+        id: Option<String>,
+
+        /// 显示详细信息
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// 比较两次扫描，显示缓存增长和新出现的重复文件
+    Diff {
+        /// 基准扫描编号（较早的一次）
+        from: String,
+
+        /// 对比扫描编号（默认为最新的一次）
+        to: Option<String>,
+
+        /// 显示详细信息
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// 删除扫描记录
+    Rm {
+        /// 扫描编号（支持唯一前缀）
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+}
+
+impl Cli {
+    /// 命令名称，用于 JSON 输出
+    fn command_name(&self) -> &'static str {
+        match &self.command {
+            Some(Commands::Scan { .. }) => "scan",
+            Some(Commands::Scans { action }) => match action {
+                ScansCommand::List => "scans list",
+                ScansCommand::Show { .. } => "scans show",
+                ScansCommand::Diff { .. } => "scans diff",
+                ScansCommand::Rm { .. } => "scans rm",
+            },
+            Some(Commands::Clean { .. }) => "clean",
+            Some(Commands::Config) => "config",
+            Some(Commands::Pattern { action }) => match action {
+                PatternCommand::Test { .. } => "pattern test",
+            },
+            None => "help",
+        }
+    }
+}
+
+/// 解析空间大小参数，支持 `20GB`、`500 MiB` 或纯字节数
+/// 解析空间大小，必须大于 0
+fn parse_size(value: &str) -> Result<u64, String> {
+    match value.parse::<bytesize::ByteSize>()?.as_u64() {
+        0 => Err("空间大小必须大于 0".to_string()),
+        size => Ok(size),
+    }
 }
 
 fn main() {
-    if let Err(err) = run() {
-        eprintln!("❌ {}", err);
-    
-        // 显示错误链
-        let mut source = err.source();
-        while let Some(err) = source {
-            eprintln!("   └─ 原因: {}", err);
-            source = err.source();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => std::process::exit(parse_error(err)),
+    };
+
+    let status = match run(&cli) {
+        Ok(status) => status,
+        Err(err) if cli.json => {
+            output::print_json_error(cli.command_name(), Status::Error, err.as_ref());
+            Status::Error
         }
+        Err(err) => {
+            eprintln!("❌ {}", err);
+        
+            // 显示错误链
+            let mut source = err.source();
+            while let Some(err) = source {
+                eprintln!("   └─ 原因: {}", err);
+                source = err.source();
+            }
+            Status::Error
+        }
+    };
+
+    std::process::exit(status.exit_code());
+}
 
-        std::process::exit(1);
+/// 处理命令行解析失败，返回退出码
+///
+/// 帮助和版本信息照常输出并以 `0` 退出；参数错误使用单独的退出码，不与“没有需要处理的内容”混淆。
+/// 命令行中有 `--json` 时以 JSON 文档报告参数错误，此时无法确定命令名称，`command` 为 `unknown`。
+fn parse_error(err: clap::Error) -> i32 {
+    if !err.use_stderr() {
+        err.exit();
+    }
+    if std::env::args_os().any(|arg| arg == "--json") {
+        output::print_json_error("unknown", Status::UsageError, &err);
+    } else {
+        let _ = err.print();
     }
+    Status::UsageError.exit_code()
 }
 
-fn run() -> AppResult<()> {
-    let cli = Cli::parse();
-    let ops = CliOperations::new()?;
+fn run(cli: &Cli) -> AppResult<Status> {
+    let ops = CliOperations::new(cli.json)?;
 
     match &cli.command {
-        Some(Commands::Scan { verbose }) => {
+        Some(Commands::Scan { verbose, output, format, resume, threads, max_read_mbps, hdd, symlinks, cross_file_systems }) => {
             let handler = ScanHandler::new(&ops);
-            handler.execute(*verbose)
+            let options = ScanOptions {
+                resume: *resume,
+                threads: threads.map(usize::from),
+                max_read_mbps: *max_read_mbps,
+                hdd: *hdd,
+                symlinks: *symlinks,
+                cross_file_systems: *cross_file_systems,
+            };
+            handler.execute(*verbose, output.as_deref(), *format, options)
         }
-        Some(Commands::Clean { mode, force }) => {
+        Some(Commands::Scans { action }) => {
+            let handler = ScansHandler::new(&ops);
+            match action {
+                ScansCommand::List => handler.list(),
+                ScansCommand::Show { id, verbose } => handler.show(id.as_deref(), *verbose),
+                ScansCommand::Diff { from, to, verbose } => handler.diff(from, to.as_deref(), *verbose),
+                ScansCommand::Rm { ids } => handler.remove(ids),
+            }
+        }
+        Some(Commands::Clean { mode, force, scan, target_free, prune_empty_dirs, clean_orphaned_thumbnails, keep_only_originals, clean_leftovers, dry_run }) => {
             let handler = CleanerHandler::new(&ops);
-            handler.execute(mode, *force)
+            let options = CleanOptions {
+                prune_empty_dirs: *prune_empty_dirs,
+                clean_orphaned_thumbnails: *clean_orphaned_thumbnails,
+                keep_only_originals: *keep_only_originals,
+                clean_leftovers: *clean_leftovers,
+                dry_run: *dry_run,
+            };
+            handler.execute(mode, *force, *target_free, scan.as_deref(), options)
         }
         Some(Commands::Config) => {
             let handler = ConfigHandler::new(&ops);
             handler.execute()
         }
+        Some(Commands::Pattern { action }) => {
+            let handler = PatternHandler::new(&ops);
+            match action {
+                PatternCommand::Test { regex, paths, group, legacy, alone, cache, samples } => {
+                    let options = PatternTestOptions {
+                        group: *group,
+                        legacy: *legacy,
+                        alone: *alone,
+                        cache: *cache,
+                        samples: *samples,
+                    };
+                    handler.test(regex, paths, options)
+                }
+            }
+        }
+        None if cli.json => {
+            output::print_json::<()>(cli.command_name(), Status::NothingToDo, Some("未指定命令"), None)?;
+            Ok(Status::NothingToDo)
+        }
         None => {
             println!("微信缓存清理工具");
             println!("使用 'wechat-cleaner --help' 查看可用命令");
-            Ok(())
+            Ok(Status::Ok)
         }
     }
 }
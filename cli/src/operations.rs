@@ -2,16 +2,26 @@
 //!
 //! 提供 CLI 操作的基础设施，包括进度条配置和用户交互功能。
 
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressState, ProgressStyle};
+use std::fmt;
+use core::cancel::CancellationToken;
 use core::config::ConfigManager;
 use core::config::settings::{CleaningMode, Settings};
 use std::io::{self, Write};
 
 use crate::AppResult;
+use crate::output::Status;
+use crate::progress::{BarProgress, ProgressStyles};
 
 /// 进度条配置
 pub struct ProgressConfig {
+    /// 总量未知的阶段（如遍历目录）使用的旋转指示器模板
     pub template: &'static str,
+    /// 按文件数计量的阶段模板
+    pub count_template: &'static str,
+    /// 按字节数计量的阶段（哈希）模板
+    pub bytes_template: &'static str,
+    pub progress_chars: &'static str,
     pub tick_strings: &'static [&'static str],
     pub tick_interval: std::time::Duration,
 }
@@ -20,6 +30,9 @@ impl Default for ProgressConfig {
     fn default() -> Self {
         Self {
             template: "{spinner:.green} {msg}",
+            count_template: "{spinner:.green} {msg:<14} [{bar:30.cyan/blue}] {pos}/{len} ({rate}, 剩余 {eta})",
+            bytes_template: "{spinner:.green} {msg:<14} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({binary_bytes_per_sec}, 剩余 {eta})",
+            progress_chars: "=> ",
             tick_strings: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
             tick_interval: std::time::Duration::from_millis(100),
         }
@@ -29,13 +42,38 @@ impl Default for ProgressConfig {
 /// CLI 操作核心结构
 pub struct CliOperations {
     config_manager: ConfigManager,
+    /// 是否以 JSON 格式输出
+    json: bool,
+    /// Ctrl-C 时取消扫描或清理
+    cancel: CancellationToken,
 }
 
 impl CliOperations {
     /// 创建新的 CLI 操作实例
-    pub fn new() -> AppResult<Self> {
+    pub fn new(json: bool) -> AppResult<Self> {
         let config_manager = ConfigManager::new()?;
-        Ok(Self { config_manager })
+        Ok(Self { config_manager, json, cancel: CancellationToken::new() })
+    }
+
+    /// 安装 Ctrl-C 处理函数并返回取消令牌
+    ///
+    /// 第一次 Ctrl-C 请求取消，扫描或清理在完成当前文件后停止并输出已完成的部分；
+    /// 第二次 Ctrl-C 立即退出。应在用户确认之后调用，以免影响确认提示的中断。
+    pub fn interruptible(&self) -> AppResult<CancellationToken> {
+        let cancel = self.cancel.clone();
+        ctrlc::set_handler(move || {
+            if cancel.is_cancelled() {
+                std::process::exit(Status::Cancelled.exit_code());
+            }
+            eprintln!("\n正在停止，完成当前文件后退出（再次按 Ctrl-C 立即退出）...");
+            cancel.cancel();
+        })?;
+        Ok(self.cancel.clone())
+    }
+
+    /// 是否以 JSON 格式输出
+    pub fn json(&self) -> bool {
+        self.json
     }
 
     /// 获取应用设置
@@ -54,26 +92,41 @@ impl CliOperations {
         }
     }
 
-    /// 创建配置好的进度条
-    pub fn create_progress_bar(&self) -> AppResult<ProgressBar> {
+    /// 创建多阶段进度显示
+    ///
+    /// 每个阶段开始时新增一个进度条：总量未知的阶段显示旋转指示器，
+    /// 其余阶段显示进度、速率和剩余时间。
+    pub fn create_progress(&self) -> AppResult<BarProgress> {
         let config = ProgressConfig::default();
-        let pb = ProgressBar::new_spinner();
-        
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template(config.template)?
-                .tick_strings(config.tick_strings),
-        );
+        let spinner = ProgressStyle::default_spinner()
+            .template(config.template)?
+            .tick_strings(config.tick_strings);
+        let count = ProgressStyle::default_bar()
+            .template(config.count_template)?
+            .with_key("rate", |state: &ProgressState, w: &mut dyn fmt::Write| {
+                let _ = write!(w, "{:.0} 个/秒", state.per_sec());
+            })
+            .progress_chars(config.progress_chars)
+            .tick_strings(config.tick_strings);
+        let bytes = ProgressStyle::default_bar()
+            .template(config.bytes_template)?
+            .progress_chars(config.progress_chars)
+            .tick_strings(config.tick_strings);
 
-        pb.enable_steady_tick(config.tick_interval);
-        
-        Ok(pb)
+        Ok(BarProgress::new(ProgressStyles { spinner, count, bytes }, config.tick_interval))
     }
 
     /// 获取用户确认
+    ///
+    /// JSON 模式下提示写入 stderr，避免混入 stdout 上的 JSON 文档。
     pub fn get_user_confirmation(&self, prompt: &str) -> AppResult<bool> {
-        println!("{}\n是否继续? (y/n): ", prompt);
-        io::stdout().flush()?;
+        if self.json {
+            eprintln!("{}\n是否继续? (y/n): ", prompt);
+            io::stderr().flush()?;
+        } else {
+            println!("{}\n是否继续? (y/n): ", prompt);
+            io::stdout().flush()?;
+        }
         
         let mut input = String::new();
         io::stdin().read_line(&mut input)?;
@@ -0,0 +1,88 @@
+//! 命令输出模块
+//!
+//! 使用全局 `--json` 参数时，每个命令在 stdout 上输出且只输出一个 JSON 文档，
+//! 进度、提示和日志一律写入 stderr。文档结构见 `docs/json-output.md`。
+
+use serde::Serialize;
+
+use crate::AppResult;
+
+/// 命令执行状态，决定 JSON 文档中的 `status` 和进程退出码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// 执行成功
+    Ok,
+    /// 没有需要处理的内容（如没有扫描到文件、没有可清理的文件、用户取消）
+    NothingToDo,
+    /// 部分失败（如部分文件删除失败）
+    Partial,
+    /// 被 Ctrl-C 中断，只完成了部分工作
+    Cancelled,
+    /// 执行出错
+    Error,
+    /// 命令行参数错误
+    UsageError,
+}
+
+impl Status {
+    /// 进程退出码
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Status::Ok => 0,
+            Status::Error => 1,
+            Status::NothingToDo => 2,
+            Status::Partial => 3,
+            Status::UsageError => 64,
+            Status::Cancelled => 130,
+        }
+    }
+}
+
+/// 命令执行成功时输出的 JSON 文档
+#[derive(Serialize)]
+struct Document<'a, T: Serialize> {
+    command: &'a str,
+    status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+    data: Option<&'a T>,
+}
+
+/// 命令出错时输出的 JSON 文档
+#[derive(Serialize)]
+struct ErrorDocument<'a> {
+    command: &'a str,
+    status: Status,
+    error: String,
+    /// 错误链，从直接原因到根本原因
+    causes: Vec<String>,
+}
+
+/// 输出命令结果文档
+pub fn print_json<T: Serialize>(command: &str, status: Status, message: Option<&str>, data: Option<&T>) -> AppResult<()> {
+    let document = Document { command, status, message, data };
+    println!("{}", serde_json::to_string_pretty(&document)?);
+    Ok(())
+}
+
+/// 输出错误文档，`status` 为 `Error` 或 `UsageError`
+pub fn print_json_error(command: &str, status: Status, err: &(dyn std::error::Error + 'static)) {
+    let mut causes = Vec::new();
+    let mut source = err.source();
+    while let Some(err) = source {
+        causes.push(err.to_string());
+        source = err.source();
+    }
+
+    let document = ErrorDocument {
+        command,
+        status,
+        error: err.to_string().trim_end().to_string(),
+        causes,
+    };
+    match serde_json::to_string_pretty(&document) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("❌ {}", e),
+    }
+}
@@ -0,0 +1,128 @@
+//! 进度条适配器
+//!
+//! 把核心库发出的进度事件显示在 indicatif 多行进度条上：每个阶段
+//! （遍历目录、读取元数据、分组、哈希、清理）各占一行，显示进度、速率和剩余时间。
+
+use core::progress::{Phase, ProgressEvent, ProgressSink};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// 各类阶段使用的进度条样式
+pub struct ProgressStyles {
+    /// 总量未知的阶段
+    pub spinner: ProgressStyle,
+    /// 按文件数计量的阶段
+    pub count: ProgressStyle,
+    /// 按字节数计量的阶段
+    pub bytes: ProgressStyle,
+}
+
+/// 基于 indicatif 的多阶段进度显示
+pub struct BarProgress {
+    multi: MultiProgress,
+    styles: ProgressStyles,
+    tick_interval: Duration,
+    state: Mutex<BarState>,
+}
+
+/// 已开始的阶段及其进度条
+#[derive(Default)]
+struct BarState {
+    bars: HashMap<Phase, ProgressBar>,
+    current: Option<Phase>,
+}
+
+impl BarProgress {
+    /// 创建多阶段进度显示
+    pub fn new(styles: ProgressStyles, tick_interval: Duration) -> Self {
+        Self {
+            multi: MultiProgress::new(),
+            styles,
+            tick_interval,
+            state: Mutex::new(BarState::default()),
+        }
+    }
+
+    /// 结束所有进度条并显示最终消息
+    pub fn finish(&self, message: &str) {
+        for bar in self.state().bars.values() {
+            if !bar.is_finished() {
+                bar.finish();
+            }
+        }
+        let _ = self.multi.println(message);
+    }
+
+    fn state(&self) -> MutexGuard<'_, BarState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn start(&self, phase: Phase, total: Option<u64>) {
+        let bar = match total {
+            Some(total) => {
+                let style = if phase == Phase::Hash { &self.styles.bytes } else { &self.styles.count };
+                ProgressBar::new(total).with_style(style.clone())
+            }
+            None => ProgressBar::new_spinner().with_style(self.styles.spinner.clone()),
+        };
+        let bar = self.multi.add(bar.with_message(phase.to_string()));
+        bar.enable_steady_tick(self.tick_interval);
+
+        let mut state = self.state();
+        state.current = Some(phase);
+        if let Some(previous) = state.bars.insert(phase, bar) {
+            previous.finish_and_clear();
+        }
+    }
+
+    /// 推进指定阶段的进度条
+    fn advance(&self, phase: Phase, delta: u64) {
+        if let Some(bar) = self.state().bars.get(&phase) {
+            bar.inc(delta);
+        }
+    }
+
+    /// 推进当前阶段的进度条（用于失败的文件）
+    ///
+    /// 哈希阶段按字节计量，失败的文件由 `HashFailed` 事件按大小推进，这里不处理。
+    fn advance_current(&self) {
+        let state = self.state();
+        if let Some(bar) = state.current.filter(|phase| *phase != Phase::Hash).and_then(|phase| state.bars.get(&phase)) {
+            bar.inc(1);
+        }
+    }
+}
+
+impl ProgressSink for BarProgress {
+    fn on_event(&self, event: &ProgressEvent) {
+        match *event {
+            ProgressEvent::PhaseStarted { phase, total } => self.start(phase, total),
+            ProgressEvent::PhaseFinished { phase } => {
+                if let Some(bar) = self.state().bars.get(&phase) {
+                    bar.finish();
+                }
+            }
+            ProgressEvent::FilesDiscovered { count } => {
+                if let Some(bar) = self.state().bars.get(&Phase::Walk) {
+                    bar.set_message(format!("{}: 发现 {count} 个文件", Phase::Walk));
+                }
+            }
+            ProgressEvent::MetadataRead { .. } => self.advance(Phase::Metadata, 1),
+            ProgressEvent::FilesGrouped { count } => self.advance(Phase::Group, count as u64),
+            ProgressEvent::BytesHashed { bytes, .. } | ProgressEvent::HashFailed { bytes, .. } => {
+                self.advance(Phase::Hash, bytes)
+            }
+            ProgressEvent::GroupFound { .. } => {}
+            ProgressEvent::FileDeleted { .. } | ProgressEvent::FileSkipped { .. } => self.advance(Phase::Clean, 1),
+            ProgressEvent::Error { path: Some(_), .. } => self.advance_current(),
+            ProgressEvent::Error { path: None, message } => {
+                let _ = self.multi.println(format!("⚠️  {message}"));
+            }
+            ProgressEvent::Message(message) => {
+                let _ = self.multi.println(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! 基准测试共用的合成微信缓存生成器
+//!
+//! 按微信的目录结构生成可重复的合成缓存：
+//!
+//! ```text
+//! <root>/wxid_<账号>/FileStorage/
+//! ├── File/2024-03/report_12.pdf, report_12(1).pdf ...   文档及其 `(1)` 副本
+//! ├── Image/2024-03/3f9a...c1.dat ...                    加密图片，部分内容重复
+//! ├── Image/2024-03/3f9a...c1_thumb.jpg ...              缩略图
+//! └── Video/2024-03/5b02...7e.mp4 ...                    大视频，部分有 `(1)` 副本
+//! ```
+//!
+//! 同一份 [`CacheSpec`] 总是生成相同的文件树和内容，可以写入临时目录测试真实 I/O，
+//! 也可以填充到 `MemoryFs` 只测试计算开销。
+
+// 各个基准测试只用到其中一部分
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use wechat_cleaner::MemoryFs;
+
+/// 合成缓存的规模和组成
+#[derive(Debug, Clone, Copy)]
+pub struct CacheSpec {
+    /// 账号目录数
+    pub accounts: usize,
+    /// 每个账号的月份目录数
+    pub months: usize,
+    /// 每个月份目录中的文档数
+    pub documents: usize,
+    /// 每个月份目录中的 `.dat` 图片数
+    pub images: usize,
+    /// 每个账号的视频数
+    pub videos: usize,
+    /// 视频大小（字节）
+    pub video_size: usize,
+    /// 带有 `(1)` 副本的文档比例（百分比）
+    pub copy_percent: usize,
+    /// 内容与其他图片重复的图片比例（百分比）
+    pub duplicate_percent: usize,
+    /// 随机数种子
+    pub seed: u64,
+}
+
+impl Default for CacheSpec {
+    fn default() -> Self {
+        CacheSpec {
+            accounts: 2,
+            months: 6,
+            documents: 40,
+            images: 120,
+            videos: 4,
+            video_size: 8 * 1024 * 1024,
+            copy_percent: 20,
+            duplicate_percent: 10,
+            seed: 0x5eed,
+        }
+    }
+}
+
+/// 合成文件
+#[derive(Debug, Clone)]
+pub struct SyntheticFile {
+    /// 相对于缓存根目录的路径
+    pub path: PathBuf,
+    /// 文件大小
+    pub size: usize,
+    /// 内容编号，编号相同的文件内容相同
+    pub content: u64,
+    /// 修改时间（Unix 时间戳，秒）
+    pub modified: u64,
+}
+
+/// 生成的合成缓存
+#[derive(Debug, Clone, Default)]
+pub struct SyntheticCache {
+    pub files: Vec<SyntheticFile>,
+}
+
+impl SyntheticCache {
+    /// 按规格生成文件列表
+    pub fn generate(spec: &CacheSpec) -> Self {
+        let mut rng = XorShift::new(spec.seed);
+        let mut cache = SyntheticCache::default();
+        let mut next_content = 0u64;
+        let base_time = 1_704_067_200; // 2024-01-01
+
+        for account in 0..spec.accounts {
+            let storage = PathBuf::from(format!("wxid_{:08x}", rng.next() as u32)).join("FileStorage");
+
+            for month in 0..spec.months {
+                let month_dir = format!("2024-{:02}", month % 12 + 1);
+                let modified = base_time + (month as u64) * 30 * 86_400;
+
+                for doc in 0..spec.documents {
+                    let ext = ["pdf", "docx", "xlsx", "zip"][doc % 4];
+                    let name = format!("report_{account}_{month}_{doc}");
+                    let size = rng.range(4 * 1024, 512 * 1024);
+                    next_content += 1;
+                    let dir = storage.join("File").join(&month_dir);
+                    cache.push(dir.join(format!("{name}.{ext}")), size, next_content, modified);
+                    if rng.percent(spec.copy_percent) {
+                        cache.push(dir.join(format!("{name}(1).{ext}")), size, next_content, modified + 60);
+                    }
+                }
+
+                let mut previous_image: Option<(usize, u64)> = None;
+                for _ in 0..spec.images {
+                    let hash = format!("{:016x}{:016x}", rng.next(), rng.next());
+                    let dir = storage.join("Image").join(&month_dir);
+                    let (size, content) = match previous_image {
+                        // 重复发送的图片：文件名不同，内容相同
+                        Some(previous) if rng.percent(spec.duplicate_percent) => previous,
+                        _ => {
+                            next_content += 1;
+                            (rng.range(8 * 1024, 200 * 1024), next_content)
+                        }
+                    };
+                    cache.push(dir.join(format!("{hash}.dat")), size, content, modified);
+                    if rng.percent(30) {
+                        next_content += 1;
+                        cache.push(dir.join(format!("{hash}_thumb.jpg")), rng.range(2 * 1024, 16 * 1024), next_content, modified);
+                    }
+                    previous_image = Some((size, content));
+                }
+            }
+
+            for video in 0..spec.videos {
+                let month_dir = format!("2024-{:02}", video % 12 + 1);
+                let dir = storage.join("Video").join(month_dir);
+                let name = format!("{:016x}", rng.next());
+                next_content += 1;
+                cache.push(dir.join(format!("{name}.mp4")), spec.video_size, next_content, base_time);
+                if video % 2 == 0 {
+                    cache.push(dir.join(format!("{name}(1).mp4")), spec.video_size, next_content, base_time + 60);
+                }
+            }
+        }
+        cache
+    }
+
+    fn push(&mut self, path: PathBuf, size: usize, content: u64, modified: u64) {
+        self.files.push(SyntheticFile { path, size, content, modified });
+    }
+
+    /// 文件总大小
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|file| file.size as u64).sum()
+    }
+
+    /// 把文件树写入 `root`
+    pub fn write_to(&self, root: &Path) {
+        for file in &self.files {
+            let path = root.join(&file.path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content(file.content, file.size)).unwrap();
+        }
+    }
+
+    /// 把文件树填充到以 `root` 为根目录的内存文件系统
+    pub fn fill(&self, fs: &MemoryFs, root: &Path) {
+        for file in &self.files {
+            fs.add_file(root.join(&file.path), content(file.content, file.size), file.modified);
+        }
+    }
+}
+
+/// 由内容编号生成指定大小的伪随机内容
+pub fn content(id: u64, size: usize) -> Vec<u8> {
+    let mut rng = XorShift::new(id.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1);
+    let mut bytes = Vec::with_capacity(size + 8);
+    while bytes.len() < size {
+        bytes.extend_from_slice(&rng.next().to_le_bytes());
+    }
+    bytes.truncate(size);
+    bytes
+}
+
+/// 简单的 xorshift 伪随机数生成器，保证不同平台结果一致
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        XorShift(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// `[low, high)` 范围内的随机数
+    fn range(&mut self, low: usize, high: usize) -> usize {
+        low + (self.next() % (high - low) as u64) as usize
+    }
+
+    /// 以 `percent`% 的概率返回 `true`
+    fn percent(&mut self, percent: usize) -> bool {
+        (self.next() % 100) < percent as u64
+    }
+}
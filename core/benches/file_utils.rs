@@ -0,0 +1,130 @@
+//! 重复文件检测基准测试
+//!
+//! 在合成的微信缓存上测量扫描流程的各个步骤：
+//! - `collect_from`：遍历目录并读取元数据（真实文件系统）
+//! - `duplicates_by_pattern`：按模式和内容检测重复文件（真实文件系统）
+//! - `group_by_hash`：对同样大小的候选文件计算哈希（内存文件系统，只测计算开销）
+//! - `CleaningPreview::from`：由扫描结果生成清理预览
+//!
+//! ```bash
+//! cargo bench -p core --bench file_utils
+//! ```
+
+mod common;
+
+use common::{CacheSpec, SyntheticCache};
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use wechat_cleaner::cleaner::CleaningPreview;
+use wechat_cleaner::config::settings::ScannerSettings;
+use wechat_cleaner::file_utils::{FileFilter, FileGrouper, HasSize, PatternRule, PatternSet};
+use wechat_cleaner::progress::NoProgress;
+use wechat_cleaner::{CancellationToken, FileInfo, MemoryFs, Scanner};
+
+const PATTERN: &str = r"\(\d+\)\.[a-zA-Z0-9]+$";
+
+/// 写入磁盘的合成缓存，视频较小以控制运行时间
+fn disk_spec() -> CacheSpec {
+    CacheSpec {
+        video_size: 2 * 1024 * 1024,
+        ..CacheSpec::default()
+    }
+}
+
+fn write_cache(spec: &CacheSpec) -> (TempDir, SyntheticCache) {
+    let dir = TempDir::new().unwrap();
+    let cache = SyntheticCache::generate(spec);
+    cache.write_to(dir.path());
+    (dir, cache)
+}
+
+fn bench_collect_from(c: &mut Criterion) {
+    let (dir, cache) = write_cache(&disk_spec());
+    let mut group = c.benchmark_group("collect_from");
+    group.throughput(Throughput::Elements(cache.files.len() as u64));
+    group.bench_function("synthetic", |b| {
+        b.iter(|| black_box(FileInfo::collect_from(dir.path()).unwrap()))
+    });
+    group.finish();
+}
+
+fn bench_duplicates_by_pattern(c: &mut Criterion) {
+    let (dir, _cache) = write_cache(&disk_spec());
+    let files = FileInfo::collect_from(dir.path()).unwrap();
+    let patterns = PatternSet::new(&[PatternRule::legacy("pattern", PATTERN)]).unwrap();
+
+    let mut group = c.benchmark_group("duplicates_by_pattern");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(files.len() as u64));
+    group.bench_function("synthetic", |b| {
+        b.iter_batched(|| files.clone(), |files| black_box(files.duplicates_by_pattern(&patterns)), BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+fn bench_group_by_hash(c: &mut Criterion) {
+    let spec = CacheSpec::default();
+    let root = Path::new("/cache");
+    let fs = Arc::new(MemoryFs::new());
+    SyntheticCache::generate(&spec).fill(&fs, root);
+
+    // 与扫描相同，只对大小相同的文件计算哈希
+    let files = FileInfo::collect_from_fs(fs.as_ref(), root, &NoProgress, &CancellationToken::new()).unwrap();
+    let candidates: Vec<FileInfo> = files
+        .group_by_size()
+        .into_values()
+        .filter(|files| files.len() > 1)
+        .flatten()
+        .collect();
+    let bytes = candidates.iter().map(HasSize::size).sum();
+
+    let mut group = c.benchmark_group("group_by_hash");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(bytes));
+    group.bench_function("memory_fs", |b| {
+        b.iter_batched(
+            || candidates.clone(),
+            |candidates| black_box(candidates.group_by_hash_with(fs.as_ref())),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_cleaning_preview(c: &mut Criterion) {
+    let spec = CacheSpec {
+        accounts: 4,
+        video_size: 64 * 1024,
+        ..CacheSpec::default()
+    };
+    let fs = Arc::new(MemoryFs::new());
+    SyntheticCache::generate(&spec).fill(&fs, Path::new("/cache"));
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        ..Default::default()
+    };
+    let result = Scanner::builder()
+        .root("/cache")
+        .fs(fs)
+        .build()
+        .unwrap()
+        .scan(&settings)
+        .unwrap();
+
+    let mut group = c.benchmark_group("cleaning_preview");
+    group.throughput(Throughput::Elements(result.duplicate_count as u64));
+    group.bench_function("from", |b| b.iter(|| black_box(CleaningPreview::from(&result))));
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_collect_from,
+    bench_duplicates_by_pattern,
+    bench_group_by_hash,
+    bench_cleaning_preview
+);
+criterion_main!(benches);
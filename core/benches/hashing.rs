@@ -0,0 +1,73 @@
+//! 文件哈希读取方式基准测试
+//!
+//! 比较固定 64 KiB `BufReader`（旧的读取方式）、1 MiB 页对齐缓冲区和内存映射，
+//! 以及扫描时按文件大小选择的读取方式（不使用内存映射）。
+//!
+//! ```bash
+//! cargo bench -p core --bench hashing
+//! ```
+//!
+//! 基准测试使用 `bench` 配置（继承 release 并保留调试信息）。
+//! 前三组的测试文件在迭代之间一直位于页缓存中，结果反映的是读取路径本身的开销，
+//! 而不是磁盘速度。`adaptive` 组与扫描时相同，每次读取后释放页缓存，
+//! 测试目录位于磁盘上时它包含了重新从磁盘读取的时间（tmpfs 上释放提示不生效）。
+
+mod common;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::fs::File;
+use std::hint::black_box;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use wechat_cleaner::hasher::{ContentHasher, Md5Hasher};
+use wechat_cleaner::reader::{self, ReadStrategy, LARGE_BUFFER};
+
+/// 测试文件大小：小文件、中等文件和视频大小的大文件
+const SIZES: [(&str, usize); 3] = [
+    ("256KiB", 256 * 1024),
+    ("8MiB", 8 * 1024 * 1024),
+    ("128MiB", 128 * 1024 * 1024),
+];
+
+/// 写入指定大小的伪随机内容
+fn create_file(dir: &Path, name: &str, size: usize) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, common::content(size as u64, size)).unwrap();
+    path
+}
+
+/// 旧的读取方式：固定 64 KiB `BufReader`
+fn hash_buf_reader(path: &Path) -> String {
+    let file = File::open(path).unwrap();
+    Md5Hasher.hash_reader(&mut BufReader::with_capacity(64 * 1024, file)).unwrap()
+}
+
+fn bench_hashing(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+    let mut group = c.benchmark_group("hash_file");
+    group.sample_size(10);
+
+    // SAFETY（下面的 hash_file_with）: 基准文件只由本进程创建，测量期间不会被修改
+    for (name, size) in SIZES {
+        let path = create_file(dir.path(), name, size);
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("buf_reader_64k", name), &path, |b, path| {
+            b.iter(|| black_box(hash_buf_reader(path)))
+        });
+        group.bench_with_input(BenchmarkId::new("aligned_1m", name), &path, |b, path| {
+            b.iter(|| black_box(unsafe { reader::hash_file_with(path, ReadStrategy::Buffered(LARGE_BUFFER), &Md5Hasher) }.unwrap()))
+        });
+        group.bench_with_input(BenchmarkId::new("mmap", name), &path, |b, path| {
+            b.iter(|| black_box(unsafe { reader::hash_file_with(path, ReadStrategy::Mapped, &Md5Hasher) }.unwrap()))
+        });
+        group.bench_with_input(BenchmarkId::new("adaptive", name), &path, |b, path| {
+            b.iter(|| black_box(reader::hash_file(path, size as u64, &Md5Hasher).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_hashing);
+criterion_main!(benches);
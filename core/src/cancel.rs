@@ -0,0 +1,30 @@
+//! 协作式取消
+//!
+//! 扫描（读取元数据、计算哈希）和清理在处理每个文件之前检查 [`CancellationToken`]，
+//! 取消后完成当前文件即停止，并返回标记为已中断的部分结果。
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 取消令牌
+///
+/// 克隆的令牌共享同一状态，可以在信号处理函数等其他线程中调用 [`CancellationToken::cancel`]。
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// 创建未取消的令牌
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求取消
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// 是否已请求取消
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
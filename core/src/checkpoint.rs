@@ -0,0 +1,160 @@
+//! 扫描检查点模块
+//!
+//! 扫描过程中把已收集的文件列表和已计算的文件摘要定期写入检查点文件，
+//! 扫描被中断后可以从检查点继续（`scan --resume`），不必重新计算已完成的哈希。
+//!
+//! 检查点只有在缓存根目录和扫描设置指纹都与当前扫描一致时才会被复用。
+//! 恢复时直接使用检查点中的文件列表和摘要，之后发生变化的文件由清理前的重新校验兜底。
+
+use crate::errors::Result;
+use crate::file_utils::{raw_path, FileInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// 检查点文件格式版本，结构变化时提升，旧检查点直接忽略
+const CHECKPOINT_VERSION: u32 = 2;
+
+/// 计算哈希期间写入检查点的最短间隔
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 检查点文件内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CheckpointState {
+    version: u32,
+    settings_fingerprint: String,
+    #[serde(with = "raw_path::many")]
+    roots: Vec<PathBuf>,
+    /// 完整的文件列表，读取元数据阶段完成前为 `None`
+    files: Option<Vec<FileInfo>>,
+    /// 未作为文件扫描的符号链接
+    #[serde(with = "raw_path::many")]
+    symlinks: Vec<PathBuf>,
+    /// 已计算的文件摘要
+    #[serde(with = "raw_path::keys")]
+    digests: HashMap<PathBuf, String>,
+}
+
+/// 扫描检查点
+///
+/// 摘要在并行哈希中记录，因此内部状态由互斥锁保护。
+pub struct ScanCheckpoint {
+    path: PathBuf,
+    state: Mutex<CheckpointState>,
+    last_saved: Mutex<Instant>,
+}
+
+impl ScanCheckpoint {
+    /// 创建新的空检查点（尚未写入磁盘）
+    pub fn new(path: impl Into<PathBuf>, settings_fingerprint: &str, roots: &[PathBuf]) -> Self {
+        Self::with_state(path.into(), CheckpointState {
+            version: CHECKPOINT_VERSION,
+            settings_fingerprint: settings_fingerprint.to_string(),
+            roots: roots.to_vec(),
+            ..Default::default()
+        })
+    }
+
+    /// 读取与当前扫描匹配的检查点
+    ///
+    /// 文件不存在、无法解析、版本不同或者根目录与设置指纹不一致时返回 `None`。
+    pub fn load(path: &Path, settings_fingerprint: &str, roots: &[PathBuf]) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        let state: CheckpointState = serde_json::from_str(&json).ok()?;
+        let matches = state.version == CHECKPOINT_VERSION
+            && state.settings_fingerprint == settings_fingerprint
+            && state.roots == roots;
+        matches.then(|| Self::with_state(path.to_path_buf(), state))
+    }
+
+    fn with_state(path: PathBuf, state: CheckpointState) -> Self {
+        ScanCheckpoint {
+            path,
+            state: Mutex::new(state),
+            last_saved: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CheckpointState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 检查点文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 检查点中完整的文件列表
+    pub fn files(&self) -> Option<Vec<FileInfo>> {
+        self.state().files.clone()
+    }
+
+    /// 检查点中未作为文件扫描的符号链接
+    pub fn symlinks(&self) -> Vec<PathBuf> {
+        self.state().symlinks.clone()
+    }
+
+    /// 已记录的摘要数量
+    pub fn digests_count(&self) -> usize {
+        self.state().digests.len()
+    }
+
+    /// 记录完整的文件列表和符号链接并立即写入磁盘
+    pub fn record_files(&self, files: &[FileInfo], symlinks: &[PathBuf]) -> Result<()> {
+        {
+            let mut state = self.state();
+            state.files = Some(files.to_vec());
+            state.symlinks = symlinks.to_vec();
+        }
+        self.save()
+    }
+
+    /// 查找已计算的摘要
+    pub fn digest(&self, path: &Path) -> Option<String> {
+        self.state().digests.get(path).cloned()
+    }
+
+    /// 记录一个摘要，距上次写入超过间隔时写入磁盘
+    ///
+    /// 写入失败只记录日志，不影响扫描。
+    pub fn record_digest(&self, path: &Path, digest: &str) {
+        self.state().digests.insert(path.to_path_buf(), digest.to_string());
+
+        let due = {
+            let mut last_saved = self.last_saved.lock().unwrap_or_else(|e| e.into_inner());
+            let due = last_saved.elapsed() >= SAVE_INTERVAL;
+            if due {
+                *last_saved = Instant::now();
+            }
+            due
+        };
+        if due && let Err(e) = self.save() {
+            log::warn!("写入扫描检查点失败: {} - {}", self.path.display(), e);
+        }
+    }
+
+    /// 写入磁盘
+    ///
+    /// 先写入临时文件再重命名，中途被终止也不会留下损坏的检查点。
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec(&*self.state())?;
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, json)?;
+        fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+
+    /// 扫描完成后删除检查点文件
+    pub fn remove(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
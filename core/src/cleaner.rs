@@ -1,48 +1,165 @@
 //! 文件清理模块
 //!
 //! 提供重复文件的清理功能，支持自动清理模式和安全的删除操作。
-use crate::config::settings::{CleaningMode, ScannerSettings};
+use crate::cancel::CancellationToken;
+use crate::config::settings::{CleanerSettings, CleaningMode, ScannerSettings};
 use crate::errors::{Error, Result};
-use crate::file_utils::{FileGrouper, FileInfo, FileProcessor, HasSize};
-use crate::progress::Progress;
+use crate::file_utils::{prune_empty_dirs_with, raw_path, FileGrouper, FileInfo, HasPath, HasSize};
+use crate::filesystem::{DryRunFs, FileMeta, Fs, RealFs};
+use crate::history::{self, ScanHistory};
+use crate::progress::{NoProgress, Phase, ProgressEvent, ProgressSink};
 use crate::scanner::ScanResult;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use crate::storage::{self, DuplicateGroup, StorageFormat};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 #[cfg(feature = "display")]
 use crate::Display;
 
 /// 清理结果数据结构（用于序列化/反序列化）
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 #[cfg_attr(feature = "display", derive(Display))]
 pub struct CleaningResult {
     #[cfg_attr(feature = "display", display(summary, name = "删除文件数"))]
     pub files_deleted: usize,
 
+    #[cfg_attr(feature = "display", display(summary, name = "删除残留文件数"))]
+    pub leftover_files_deleted: usize,
+
+    #[cfg_attr(feature = "display", display(summary, name = "删除缩略图数"))]
+    pub derivative_files_deleted: usize,
+
+    /// 删除文件的逻辑大小之和
     #[cfg_attr(feature = "display", display(summary, name = "释放空间"))]
     pub freed_space: u64,
 
+    /// 删除文件实际占用的磁盘空间之和
+    #[cfg_attr(feature = "display", display(summary, name = "释放磁盘空间"))]
+    pub freed_disk_space: u64,
+
+    /// 清理前后各缓存根目录所在文件系统的可用空间，无法测量的根目录不在其中
+    pub free_space: Vec<FreeSpace>,
+
+    #[cfg_attr(feature = "display", display(summary, name = "删除空目录数"))]
+    pub empty_dirs_removed: usize,
+
+    /// 每个待删除文件的处理结果，单个文件失败不会中断其余文件的清理
+    #[cfg_attr(feature = "display", display(details, name = "文件处理结果"))]
+    pub outcomes: Vec<FileOutcome>,
+
+    /// 是否为模拟运行（`--dry-run`），模拟运行不会修改文件系统
+    pub simulated: bool,
+
+    /// 清理是否被取消而提前结束；此时 `outcomes` 只包含已处理的文件
+    pub cancelled: bool,
+
+    #[serde(rename = "clean_time_ms", with = "crate::scanner::duration_ms")]
     #[cfg_attr(feature = "display", display(summary, name = "清理耗时"))]
     pub clean_time: Duration,
 }
 
+/// 缓存根目录所在文件系统在清理前后的可用空间
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FreeSpace {
+    #[serde(with = "raw_path")]
+    pub root: PathBuf,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl FreeSpace {
+    /// 可用空间的变化量，其他程序同时写入磁盘时可能为负数
+    pub fn gained(&self) -> i64 {
+        self.after as i64 - self.before as i64
+    }
+}
+
+/// 单个文件的处理结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FileOutcome {
+    #[serde(with = "raw_path")]
+    pub path: PathBuf,
+    pub size: u64,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+/// 文件处理结果类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", content = "reason", rename_all = "snake_case")]
+pub enum Outcome {
+    /// 已删除（模拟运行时表示将被删除）
+    Deleted,
+    /// 扫描后文件已变化，为安全起见跳过
+    Skipped(String),
+    /// 删除失败
+    Failed(String),
+}
+
+impl CleaningResult {
+    /// 删除失败的文件
+    pub fn failures(&self) -> impl Iterator<Item = &FileOutcome> {
+        self.outcomes.iter().filter(|o| matches!(o.outcome, Outcome::Failed(_)))
+    }
+
+    /// 因扫描后发生变化而跳过的文件
+    pub fn skipped(&self) -> impl Iterator<Item = &FileOutcome> {
+        self.outcomes.iter().filter(|o| matches!(o.outcome, Outcome::Skipped(_)))
+    }
+
+    /// 是否有文件删除失败
+    pub fn is_partial(&self) -> bool {
+        self.failures().next().is_some()
+    }
+}
+
 /// 清理预览信息
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 #[cfg_attr(feature = "display", derive(Display))]
 pub struct CleaningPreview {
     #[cfg_attr(feature = "display", display(summary, name = "预计删除文件数"))]
     pub estimated_files_count: usize,
 
+    /// 待删除文件的逻辑大小之和
     #[cfg_attr(feature = "display", display(summary, name = "预计释放空间"))]
     pub estimated_freed_space: u64,
 
+    /// 待删除文件实际占用的磁盘空间之和
+    #[cfg_attr(feature = "display", display(summary, name = "预计释放磁盘空间"))]
+    pub estimated_freed_disk_space: u64,
+
+    /// 空间预算模式下要求释放的空间，`None` 表示清理全部重复文件
+    pub target_freed_space: Option<u64>,
+
+    /// 按保留文件路径索引的预览组
     #[cfg_attr(feature = "display", display(details, name = "文件分组详情"))]
+    #[serde(serialize_with = "raw_path::keys::serialize")]
     pub file_groups: HashMap<PathBuf, PreviewGroup>,
+
+    /// 将被清理的零字节或未完成下载的残留文件（见 [`CleanerSettings::clean_leftovers`]）
+    #[cfg_attr(feature = "display", display(summary, details, name = "残留文件"))]
+    pub leftover_files: Vec<FileInfo>,
+
+    /// 将被清理的缩略图：开启时的孤立缩略图，以及只保留原始文件时的全部缩略图
+    #[cfg_attr(feature = "display", display(summary, details, name = "缩略图"))]
+    pub derivative_files: Vec<FileInfo>,
+
+    /// 重复组、残留文件或缩略图中不在允许设备上的文件，既不保留也不删除
+    #[cfg_attr(feature = "display", display(summary, details, name = "其他设备上的文件"))]
+    pub off_device_files: Vec<FileInfo>,
+
+    /// 扫描时的缓存根目录，清理空目录时不会越过这些目录
+    #[serde(serialize_with = "raw_path::many::serialize")]
+    pub roots: Vec<PathBuf>,
+
+    /// 允许删除文件的设备（见 [`ScanResult::devices`]），为空时不检查设备
+    pub devices: BTreeSet<u64>,
 }
 
-/// 预览组，表示一个文件夹中的文件清理情况
-#[derive(Debug, Clone)]
+/// 预览组，表示一个文件夹中同一重复组的文件清理情况
+#[derive(Debug, Clone, Serialize)]
 #[cfg_attr(feature = "display", derive(Display))]
 pub struct PreviewGroup {
     #[cfg_attr(feature = "display", display(details, name = "保留文件"))]
@@ -52,113 +169,528 @@ pub struct PreviewGroup {
     pub files_to_delete: Vec<FileInfo>,
 }
 
+/// 重复组之外的可清理文件类别
+#[derive(Default)]
+struct Extras {
+    leftover_files: Vec<FileInfo>,
+    derivative_files: Vec<FileInfo>,
+    off_device_files: Vec<FileInfo>,
+}
+
+/// 可清理的预览组（标注是否经过内容验证）及不在允许设备上的文件
+type Candidates = (Vec<(bool, PreviewGroup)>, Vec<FileInfo>);
+
+/// 文件是否位于允许的设备上，设备未知时视为允许
+fn on_devices(file: &FileInfo, devices: &BTreeSet<u64>) -> bool {
+    devices.is_empty() || file.id().is_none_or(|id| devices.contains(&id.device))
+}
+
 impl CleaningPreview {
     /// 从ScanResult加载清理预览
     pub fn from(scan_result: &ScanResult) -> Option<Self> {
+        Self::with_settings(scan_result, &CleanerSettings::default())
+    }
 
-        let mut groups = HashMap::new();
-        let mut total_count = 0;
-        let mut total_size = 0;
+    /// 按清理设置从ScanResult生成清理预览
+    ///
+    /// 孤立缩略图只在设置了 `clean_orphaned_thumbnails` 时清理；设置了 `keep_only_originals` 时，
+    /// 孤立缩略图和原始文件仍存在的缩略图都会被清理。
+    ///
+    /// 残留文件只在设置了 `clean_leftovers` 时清理，且修改时间距今至少
+    /// `leftover_min_age_hours` 小时。
+    pub fn with_settings(scan_result: &ScanResult, settings: &CleanerSettings) -> Option<Self> {
+        Self::from_groups(scan_result, scan_result.duplicate_groups().map(Ok), settings).ok().flatten()
+    }
 
-        for files in scan_result.duplicate_files.values().cloned() {
-            if files.is_empty() {
-                continue;
+    /// 从重复组流生成清理预览
+    ///
+    /// `scan_result` 只需提供头部信息（根目录、残留文件等），重复组逐个从 `groups` 读取，
+    /// 因此可以配合 [`storage::open_binary`] 在不加载完整扫描结果的情况下生成预览。
+    ///
+    /// 不在扫描结果允许的设备上的文件不参与清理，列入 `off_device_files`。
+    pub fn from_groups<I>(scan_result: &ScanResult, groups: I, settings: &CleanerSettings) -> Result<Option<Self>>
+    where
+        I: IntoIterator<Item = Result<DuplicateGroup>>,
+    {
+        let devices = &scan_result.devices;
+        let (groups, mut off_device_files) = Self::candidate_groups(groups, devices)?;
+        let groups = groups
+            .into_iter()
+            .map(|(_, group)| (group.file_to_keep.path().clone(), group))
+            .collect();
+
+        let mut derivative_files = Vec::new();
+        if settings.clean_orphaned_thumbnails || settings.keep_only_originals {
+            derivative_files.extend(scan_result.orphaned_derivatives.iter().cloned());
+        }
+        if settings.keep_only_originals {
+            derivative_files.extend(scan_result.attached_derivatives.iter().cloned());
+        }
+
+        let min_age = settings.leftover_min_age_hours.saturating_mul(3600);
+        let now = history::now();
+        let (leftover_files, off_device): (Vec<_>, Vec<_>) = scan_result
+            .leftover_files
+            .iter()
+            .filter(|file| settings.clean_leftovers && now.saturating_sub(file.modified) >= min_age)
+            .cloned()
+            .partition(|file| on_devices(file, devices));
+        off_device_files.extend(off_device);
+        let (derivative_files, off_device): (Vec<_>, Vec<_>) =
+            derivative_files.into_iter().partition(|file| on_devices(file, devices));
+        off_device_files.extend(off_device);
+
+        let extras = Extras { leftover_files, derivative_files, off_device_files };
+        Ok(Self::build(groups, extras, scan_result, None))
+    }
+
+    /// 按空间预算生成清理预览
+    ///
+    /// 将所有可删除文件排序后依次选入，累计释放的磁盘空间一旦达到 `target` 即停止：
+    /// 1. 内容哈希验证过的重复文件优先
+    /// 2. 其余文件按修改时间从旧到新
+    /// 3. 修改时间相同时优先选择较大的文件
+    ///
+    /// 残留文件和缩略图不计入预算；扫描范围之外还有硬链接的文件删除后不释放空间，不会被选入。
+    pub fn with_budget(scan_result: &ScanResult, target: u64) -> Option<Self> {
+        Self::budget_from_groups(scan_result, scan_result.duplicate_groups().map(Ok), target).ok().flatten()
+    }
+
+    /// 从重复组流按空间预算生成清理预览，排序规则同 [`CleaningPreview::with_budget`]
+    pub fn budget_from_groups<I>(scan_result: &ScanResult, groups: I, target: u64) -> Result<Option<Self>>
+    where
+        I: IntoIterator<Item = Result<DuplicateGroup>>,
+    {
+        let mut candidates: Vec<(bool, &FileInfo, FileInfo)> = Vec::new();
+        let (groups, off_device_files) = Self::candidate_groups(groups, &scan_result.devices)?;
+        for (verified, group) in &groups {
+            for file in group.files_to_delete.iter().filter(|file| file.freed_disk_size() > 0) {
+                candidates.push((*verified, &group.file_to_keep, file.clone()));
+            }
+        }
+
+        candidates.sort_by(|(a_verified, _, a), (b_verified, _, b)| {
+            b_verified
+                .cmp(a_verified)
+                .then(a.modified.cmp(&b.modified))
+                .then(b.size().cmp(&a.size()))
+        });
+
+        let mut selected: HashMap<PathBuf, PreviewGroup> = HashMap::new();
+        let mut freed = 0;
+        for (_, keeper, file) in candidates {
+            if freed >= target {
+                break;
             }
-            for (parent, mut group) in files.group_by_parent() {
+            freed += file.freed_disk_size();
+            selected
+                .entry(keeper.path().clone())
+                .or_insert_with(|| PreviewGroup {
+                    file_to_keep: keeper.clone(),
+                    files_to_delete: Vec::new(),
+                })
+                .files_to_delete
+                .push(file);
+        }
+
+        let extras = Extras { off_device_files, ..Extras::default() };
+        Ok(Self::build(selected, extras, scan_result, Some(target)))
+    }
+
+    /// 收集所有可清理的预览组，并标注其是否经过内容验证
+    ///
+    /// 同一重复组内的文件按所在文件夹和设备拆分，每个文件夹保留最早的文件，
+    /// 因此保留的文件与删除的文件总在同一设备上。不在 `devices` 上的文件单独返回。
+    fn candidate_groups<I>(duplicate_groups: I, devices: &BTreeSet<u64>) -> Result<Candidates>
+    where
+        I: IntoIterator<Item = Result<DuplicateGroup>>,
+    {
+        let mut groups = Vec::new();
+        let mut off_device_files = Vec::new();
+
+        for duplicate_group in duplicate_groups {
+            let DuplicateGroup { verified, files, .. } = duplicate_group?;
+            let (files, off_device): (Vec<_>, Vec<_>) = files.into_iter().partition(|file| on_devices(file, devices));
+            off_device_files.extend(off_device);
+
+            let by_location = files.group_by(|file| {
+                let parent = file.path().parent().unwrap_or("/".as_ref()).to_path_buf();
+                Some((parent, file.id().map(|id| id.device)))
+            });
+            for (_, mut group) in by_location {
                 if group.len() > 1 {
                     group.sort_by_key(|f| f.modified);
-                    let to_delete = group.iter().skip(1).cloned().collect::<Vec<_>>();
-
-                    if !to_delete.is_empty() {
-                        total_count += to_delete.len();
-                        total_size += to_delete.iter().map(|f| f.size()).sum::<u64>();
-
-                        groups.insert(
-                            parent.to_path_buf(),
-                            PreviewGroup {
-                                file_to_keep: group[0].clone(),
-                                files_to_delete: to_delete,
-                            },
-                        );
-                    }
+                    let file_to_keep = group.remove(0);
+                    groups.push((verified, PreviewGroup {
+                        file_to_keep,
+                        files_to_delete: group,
+                    }));
                 }
             }
         }
+        Ok((groups, off_device_files))
+    }
+
+    fn build(
+        file_groups: HashMap<PathBuf, PreviewGroup>,
+        extras: Extras,
+        scan_result: &ScanResult,
+        target_freed_space: Option<u64>,
+    ) -> Option<Self> {
+        let Extras { leftover_files, derivative_files, off_device_files } = extras;
+        let to_delete = file_groups
+            .values()
+            .flat_map(|g| g.files_to_delete.iter())
+            .chain(leftover_files.iter())
+            .chain(derivative_files.iter());
+        let (total_count, total_size, total_disk_size) = to_delete.fold((0, 0, 0), |(count, size, disk_size), f| {
+            (count + f.path_count(), size + f.freed_size(), disk_size + f.freed_disk_size())
+        });
+
         if total_count == 0 { None } else {
             Some(CleaningPreview {
                 estimated_files_count: total_count,
                 estimated_freed_space: total_size,
-                file_groups: groups,
+                estimated_freed_disk_space: total_disk_size,
+                target_freed_space,
+                file_groups,
+                leftover_files,
+                derivative_files,
+                off_device_files,
+                roots: scan_result.roots.clone(),
+                devices: scan_result.devices.clone(),
             })
         }
     }
 
     /// 执行文件清理（支持预览模式）
-    pub fn clean(&self, mode: CleaningMode) -> Option<CleaningResult> {
-        self.clean_with_progress(mode, &Progress::none())
+    pub fn clean(&self, settings: &CleanerSettings) -> Option<CleaningResult> {
+        self.clean_with_progress(settings, &NoProgress)
+    }
+
+    /// 带进度报告的文件清理
+    pub fn clean_with_progress(&self, settings: &CleanerSettings, progress: &dyn ProgressSink) -> Option<CleaningResult> {
+        self.clean_with_fs(&RealFs, settings, progress, &CancellationToken::new())
     }
 
-    /// 带进度显示的文件清理
-    pub fn clean_with_progress(&self, mode: CleaningMode, progress: &Progress) -> Option<CleaningResult> {
+    /// 模拟清理：完整执行清理流程（包括重新校验和空目录清理），但不修改文件系统
+    ///
+    /// 返回的 `CleaningResult` 中 `simulated` 为 `true`。
+    pub fn simulate(&self, settings: &CleanerSettings, progress: &dyn ProgressSink) -> Option<CleaningResult> {
+        self.clean_with_fs(&DryRunFs::new(), settings, progress, &CancellationToken::new())
+    }
+
+    /// 通过指定的文件系统后端执行清理
+    ///
+    /// 每删除一个文件前检查 `cancel`：取消后完成当前文件即停止，不再清理空目录，
+    /// 返回 `cancelled` 为 `true` 的部分结果。
+    ///
+    /// 清理前后分别测量各缓存根目录所在文件系统的可用空间（见 [`Fs::available_space`]）。
+    pub fn clean_with_fs(
+        &self,
+        fs: &dyn Fs,
+        settings: &CleanerSettings,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Option<CleaningResult> {
         if self.estimated_files_count == 0 {
-            progress.set_message("没有需要清理的文件");
+            progress.on_event(&ProgressEvent::Message("没有需要清理的文件"));
             return None;
         }
 
-        match mode {
-            CleaningMode::Auto => self.execute_deletion(progress).ok(),
+        match settings.mode {
+            CleaningMode::Auto => self.execute_deletion(fs, settings, progress, cancel).ok(),
             CleaningMode::Interactive => {
-                progress.set_message("交互模式需要用户界面支持");
-                return None;
+                progress.on_event(&ProgressEvent::Error { path: None, message: "交互模式需要用户界面支持" });
+                None
             }
         }
     }
 
-    fn execute_deletion(&self, progress: &Progress) -> Result<CleaningResult> {
+    fn execute_deletion(
+        &self,
+        fs: &dyn Fs,
+        settings: &CleanerSettings,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Result<CleaningResult> {
         let start_time = Instant::now();
-        let total = self.file_groups.len();
+        let free_before: Vec<(&PathBuf, u64)> = self
+            .roots
+            .iter()
+            .filter_map(|root| Some((root, fs.available_space(root)?)))
+            .collect();
 
-        progress.set_message("执行清理中...");
+        progress.on_event(&ProgressEvent::PhaseStarted {
+            phase: Phase::Clean,
+            total: Some(self.estimated_files_count as u64),
+        });
+        let mut outcomes = Vec::new();
         let mut deleted_files = HashMap::new();
-        for (idx, (parent, group)) in self.file_groups.iter().enumerate() {
-            let deleted = group.files_to_delete.delete()?;
+        for (key, group) in self.file_groups.iter() {
+            if let Some(reason) = recheck_keeper(fs, &self.devices, &group.file_to_keep) {
+                skip_each(&group.files_to_delete, &reason, &mut outcomes, progress, cancel);
+                continue;
+            }
+            let deleted = delete_each(fs, &self.devices, &group.files_to_delete, &mut outcomes, progress, cancel);
             if !deleted.is_empty() {
-                deleted_files.insert(parent.clone(), deleted);
+                deleted_files.insert(key.clone(), deleted);
             }
-            progress.update(idx + 1, total, &format!("清理进度: {}/{}", idx + 1, total));
         }
-        progress.finish("清理完成");
-        
-        Ok(CleaningResult {
-            files_deleted: deleted_files.values().map(Vec::len).sum(),
-            freed_space: deleted_files
+
+        let deleted_leftovers = delete_each(fs, &self.devices, &self.leftover_files, &mut outcomes, progress, cancel);
+        let deleted_derivatives = delete_each(fs, &self.devices, &self.derivative_files, &mut outcomes, progress, cancel);
+        progress.on_event(&ProgressEvent::PhaseFinished { phase: Phase::Clean });
+
+        let cancelled = cancel.is_cancelled();
+        if cancelled {
+            progress.on_event(&ProgressEvent::Message("清理已取消，结果只包含已处理的文件"));
+        }
+
+        let mut empty_dirs_removed = 0;
+        if settings.prune_empty_dirs && !cancelled {
+            if self.roots.is_empty() {
+                progress.on_event(&ProgressEvent::Error {
+                    path: None,
+                    message: "扫描结果中没有缓存根目录信息，跳过空目录清理",
+                });
+            } else {
+                progress.on_event(&ProgressEvent::PhaseStarted { phase: Phase::Prune, total: None });
+                let touched_dirs = deleted_files
+                    .values()
+                    .flatten()
+                    .chain(deleted_leftovers.iter())
+                    .chain(deleted_derivatives.iter())
+                    .filter_map(|f| f.path().parent().map(PathBuf::from));
+                empty_dirs_removed = prune_empty_dirs_with(fs, touched_dirs, &self.roots).len();
+                progress.on_event(&ProgressEvent::PhaseFinished { phase: Phase::Prune });
+            }
+        }
+
+        let free_space = free_before
+            .into_iter()
+            .filter_map(|(root, before)| {
+                let after = fs.available_space(root)?;
+                Some(FreeSpace { root: root.clone(), before, after })
+            })
+            .collect();
+
+        let deleted = || {
+            deleted_files
                 .values()
                 .flat_map(|files| files.iter())
-                .map(|f| f.size())
-                .sum(),
+                .chain(deleted_leftovers.iter())
+                .chain(deleted_derivatives.iter())
+        };
+        Ok(CleaningResult {
+            files_deleted: deleted_files.values().flatten().map(FileInfo::path_count).sum(),
+            leftover_files_deleted: deleted_leftovers.iter().map(FileInfo::path_count).sum(),
+            derivative_files_deleted: deleted_derivatives.iter().map(FileInfo::path_count).sum(),
+            freed_space: deleted().map(FileInfo::freed_size).sum(),
+            freed_disk_space: deleted().map(FileInfo::freed_disk_size).sum(),
+            free_space,
+            empty_dirs_removed,
+            outcomes,
+            simulated: fs.is_simulated(),
+            cancelled,
             clean_time: start_time.elapsed(),
         })
     }
 }
 
+/// 文件与扫描时相比是否发生了变化，变化时返回原因
+///
+/// 大小或修改时间不同、或已不在 `devices` 上的文件视为已变化。
+fn recheck(meta: &FileMeta, file: &FileInfo, devices: &BTreeSet<u64>) -> Option<&'static str> {
+    if meta.size != file.size() || meta.modified != file.modified {
+        Some("扫描后文件已变化")
+    } else if !devices.is_empty() && meta.id.is_some_and(|id| !devices.contains(&id.device)) {
+        Some("文件不在扫描时的设备上")
+    } else {
+        None
+    }
+}
+
+/// 删除一组副本前重新校验保留的文件，返回不能删除这组副本的原因
+///
+/// 保留的文件在扫描后被移动、删除或改写时，删除副本可能删掉最后一份数据。
+fn recheck_keeper(fs: &dyn Fs, devices: &BTreeSet<u64>, keeper: &FileInfo) -> Option<String> {
+    match fs.metadata(keeper.path()) {
+        Err(e) => Some(format!("保留的文件 {} 无法访问: {}", keeper.path().display(), e)),
+        Ok(meta) => recheck(&meta, keeper, devices)
+            .map(|reason| format!("保留的文件 {} {}", keeper.path().display(), reason)),
+    }
+}
+
+/// 把一组文件（包括硬链接路径）全部记为跳过，不做任何修改
+fn skip_each(
+    files: &[FileInfo],
+    reason: &str,
+    outcomes: &mut Vec<FileOutcome>,
+    progress: &dyn ProgressSink,
+    cancel: &CancellationToken,
+) {
+    for file in files {
+        if cancel.is_cancelled() {
+            break;
+        }
+        record(file, Outcome::Skipped(reason.to_string()), outcomes, progress);
+        skip_hard_links(file, reason, outcomes, progress);
+    }
+}
+
+/// 记录一个文件的处理结果，并作为事件发送到 `progress`
+fn record(file: &FileInfo, outcome: Outcome, outcomes: &mut Vec<FileOutcome>, progress: &dyn ProgressSink) {
+    record_path(file.path(), file.size(), outcome, outcomes, progress);
+}
+
+/// 记录一个路径的处理结果，并作为事件发送到 `progress`
+fn record_path(
+    path: &Path,
+    size: u64,
+    outcome: Outcome,
+    outcomes: &mut Vec<FileOutcome>,
+    progress: &dyn ProgressSink,
+) {
+    progress.on_event(&match &outcome {
+        Outcome::Deleted => ProgressEvent::FileDeleted { path, size },
+        Outcome::Skipped(reason) => ProgressEvent::FileSkipped { path, reason },
+        Outcome::Failed(message) => ProgressEvent::Error { path: Some(path), message },
+    });
+    outcomes.push(FileOutcome {
+        path: path.to_path_buf(),
+        size,
+        outcome,
+    });
+}
+
+/// 逐个删除文件，并把每个文件的处理结果记录到 `outcomes`
+///
+/// 删除前重新校验文件（见 [`recheck`]），已变化的文件会被跳过；
+/// 单个文件失败不会中断清理。每个文件的结果同时作为事件发送到 `progress`。
+/// 取消后不再处理剩余文件。
+///
+/// 文件在扫描范围内的其他硬链接（预览中逐个列出）随文件一起删除，否则数据块不会被释放；
+/// 每个硬链接路径单独记录结果。文件本身未删除时，它的硬链接记为跳过。
+/// 返回的文件信息只保留实际删除的硬链接，释放空间据此计算。
+fn delete_each(
+    fs: &dyn Fs,
+    devices: &BTreeSet<u64>,
+    files: &[FileInfo],
+    outcomes: &mut Vec<FileOutcome>,
+    progress: &dyn ProgressSink,
+    cancel: &CancellationToken,
+) -> Vec<FileInfo> {
+    let mut deleted = Vec::with_capacity(files.len());
+    for file in files {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let outcome = match fs.metadata(file.path()) {
+            Err(e) => Outcome::Failed(e.to_string()),
+            Ok(meta) => match recheck(&meta, file, devices) {
+                Some(reason) => Outcome::Skipped(reason.to_string()),
+                None => match fs.remove_file(file.path()) {
+                    Ok(()) => Outcome::Deleted,
+                    Err(e) => Outcome::Failed(e.to_string()),
+                },
+            },
+        };
+        let removed = matches!(outcome, Outcome::Deleted);
+        record(file, outcome, outcomes, progress);
+        if removed {
+            deleted.push(remove_hard_links(fs, file, outcomes, progress));
+        } else {
+            skip_hard_links(file, "同一文件的其他路径未删除", outcomes, progress);
+        }
+    }
+    deleted
+}
+
+/// 删除文件在扫描范围内的其他硬链接，返回只保留已删除链接的文件信息
+///
+/// 已不存在的链接视为已删除；删除失败的链接记为失败。
+fn remove_hard_links(
+    fs: &dyn Fs,
+    file: &FileInfo,
+    outcomes: &mut Vec<FileOutcome>,
+    progress: &dyn ProgressSink,
+) -> FileInfo {
+    let mut removed = Vec::with_capacity(file.hard_links().len());
+    for link in file.hard_links() {
+        let outcome = match fs.remove_file(link) {
+            Ok(()) => Outcome::Deleted,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Outcome::Deleted,
+            Err(e) => Outcome::Failed(format!("删除硬链接失败: {}", e)),
+        };
+        if matches!(outcome, Outcome::Deleted) {
+            removed.push(link.clone());
+        }
+        record_path(link, file.size(), outcome, outcomes, progress);
+    }
+    file.with_hard_links(removed)
+}
+
+/// 把文件的硬链接路径全部记为跳过
+fn skip_hard_links(file: &FileInfo, reason: &str, outcomes: &mut Vec<FileOutcome>, progress: &dyn ProgressSink) {
+    for link in file.hard_links() {
+        record_path(link, file.size(), Outcome::Skipped(reason.to_string()), outcomes, progress);
+    }
+}
+
 /// 文件清理器
 /// 
 /// 实现了清晰的链式调用方式
 pub struct FileCleaner {
     scan_result: ScanResult,
+    /// 扫描结果为二进制格式时只加载头部，重复组在生成预览时流式读取
+    streamed: bool,
 }
 
 impl FileCleaner {
-    /// 创建新的文件清理器
+    /// 从最新的一次扫描创建文件清理器
     pub fn new(settings: &ScannerSettings) -> Result<Self> {
-        let result_path = settings.save_path.join("wechat-cleaner/scan-result.json");
-        let scan_result = ScanResult::load(&result_path)?;
+        let history = ScanHistory::open(settings)?;
+        let entry = history.resolve(None)?;
+        Self::open(&history.path_of(entry))
+    }
+
+    /// 从指定的扫描结果文件创建文件清理器
+    pub fn open(result_path: &Path) -> Result<Self> {
+        if !result_path.exists() {
+            return Err(Error::FileProcessing("未找到扫描结果文件，请先执行扫描命令".to_string()));
+        }
+
+        if StorageFormat::detect(result_path)? == StorageFormat::Binary {
+            let (mut scan_result, _) = storage::open_binary(result_path)?;
+            scan_result.path = result_path.to_path_buf();
+            return Ok(FileCleaner { scan_result, streamed: true });
+        }
+
+        let mut scan_result = ScanResult::load(&result_path.to_path_buf())?;
+        scan_result.path = result_path.to_path_buf();
+        Ok(FileCleaner { scan_result, streamed: false })
+    }
 
-        Ok(FileCleaner { scan_result })
+    pub fn preview(&self, settings: &CleanerSettings) -> Result<CleaningPreview> {
+        let preview = if self.streamed {
+            let (_, groups) = storage::open_binary(&self.scan_result.path)?;
+            CleaningPreview::from_groups(&self.scan_result, groups, settings)?
+        } else {
+            CleaningPreview::with_settings(&self.scan_result, settings)
+        };
+        preview.ok_or(Error::NothingToClean)
     }
 
-    pub fn preview(&self) -> Result<CleaningPreview> {
-        CleaningPreview::from(&self.scan_result).ok_or(Error::FileProcessing("找到扫描结果，但无可清理文件".to_string()))
+    /// 按空间预算生成清理预览，优先选择价值最低的文件
+    pub fn preview_with_budget(&self, target: u64) -> Result<CleaningPreview> {
+        let preview = if self.streamed {
+            let (_, groups) = storage::open_binary(&self.scan_result.path)?;
+            CleaningPreview::budget_from_groups(&self.scan_result, groups, target)?
+        } else {
+            CleaningPreview::with_budget(&self.scan_result, target)
+        };
+        preview.ok_or(Error::NothingToClean)
     }
 
     pub fn delete_scan_result(&self) -> Result<()> {
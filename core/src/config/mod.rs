@@ -33,10 +33,10 @@ impl ConfigManager {
         self.settings = Settings::default();
 
         // 2. 加载用户级配置文件
-        if let Some(user_path) = Self::user_config_path() {
-            if user_path.exists() {
-                self.merge_from_file(&user_path)?;
-            }
+        if let Some(user_path) = Self::user_config_path()
+            && user_path.exists()
+        {
+            self.merge_from_file(&user_path)?;
         }
 
         Ok(())
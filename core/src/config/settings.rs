@@ -2,7 +2,9 @@
 use serde::{Deserialize, Serialize};
 use std::path::{PathBuf};
 
-use crate::file_utils::WechatCacheResolver;
+use crate::file_utils::{PatternRule, WechatCacheResolver};
+use crate::filesystem::SymlinkPolicy;
+use crate::storage::StorageFormat;
 
 /// 配置合并策略
 pub trait Merge {
@@ -11,7 +13,7 @@ pub trait Merge {
 }
 
 /// 应用程序设置
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct Settings {
     pub wechat: WechatSettings,
     pub scanner: ScannerSettings,
@@ -25,9 +27,32 @@ pub struct WechatSettings {
     #[serde(default = "default_wechat_cache_path")]
     pub cache_path: Option<PathBuf>,
 
-    /// 用于识别微信自动生成副本的文件名模式
-    #[serde(default = "default_cache_patterns")]
+    /// 旧版的单个副本文件名模式，取第一个匹配位置之前的部分作为原始名称
+    ///
+    /// 非空时作为名为 `cache_patterns` 的规则排在 `pattern_rules` 之前；新配置请使用 `pattern_rules`。
+    /// 配置文件只设置了该项而没有配置 `pattern_rules` 时，不启用内置规则，与升级前的行为一致。
+    #[serde(default)]
     pub cache_patterns: String,
+
+    /// 识别副本文件名的模式规则，按顺序尝试；配置文件中未配置时为空，合并后使用内置规则
+    #[serde(default)]
+    pub pattern_rules: Vec<PatternRule>,
+
+    /// 禁用的模式规则名称，默认只启用 `序号副本`，其余内置规则需要从列表中移除才会启用
+    #[serde(default = "default_disabled_patterns")]
+    pub disabled_patterns: Vec<String>,
+}
+
+impl WechatSettings {
+    /// 扫描时使用的模式规则：旧版模式（如果设置了）加上未被禁用的规则
+    pub fn active_pattern_rules(&self) -> Vec<PatternRule> {
+        let legacy = (!self.cache_patterns.is_empty()).then(|| PatternRule::legacy("cache_patterns", &self.cache_patterns));
+        legacy
+            .into_iter()
+            .chain(self.pattern_rules.iter().cloned())
+            .filter(|rule| !self.disabled_patterns.contains(&rule.name))
+            .collect()
+    }
 }
 
 /// 扫描设置
@@ -36,6 +61,52 @@ pub struct ScannerSettings {
     /// 扫描结果保存位置
     #[serde(default = "default_scan_result_save_path")]
     pub save_path: PathBuf,
+
+    /// 扫描结果存储格式：json 或 binary
+    #[serde(default)]
+    pub storage: StorageFormat,
+
+    /// 扫描使用的线程数，未设置时使用全部 CPU 核心
+    #[serde(default)]
+    pub threads: Option<usize>,
+
+    /// 计算哈希时读取文件内容的最大带宽（MB/s），未设置或为 0 时不限速
+    #[serde(default)]
+    pub max_read_mbps: Option<u64>,
+
+    /// 机械硬盘模式：按文件在磁盘上的顺序逐个计算哈希，避免来回寻道
+    #[serde(default)]
+    pub hdd_mode: bool,
+
+    /// 符号链接的处理方式：skip（跳过）、follow（跟随）或 report（不跟随，只列出）
+    #[serde(default)]
+    pub symlinks: SymlinkPolicy,
+
+    /// 只扫描与缓存根目录在同一文件系统上的文件，不进入挂载点
+    #[serde(default = "default_one_file_system")]
+    pub one_file_system: bool,
+}
+
+impl ScannerSettings {
+    /// 实际生效的读取带宽上限，`0` 视为不限速
+    pub fn read_limit_mbps(&self) -> Option<u64> {
+        self.max_read_mbps.filter(|&mbps| mbps > 0)
+    }
+
+    /// 扫描历史目录
+    pub fn history_dir(&self) -> PathBuf {
+        self.save_path.join("wechat-cleaner").join("scans")
+    }
+
+    /// 指定编号的扫描结果文件路径
+    pub fn scan_path(&self, id: &str) -> PathBuf {
+        self.history_dir().join(format!("{}.{}", id, self.storage.extension()))
+    }
+
+    /// 扫描检查点文件路径（不在扫描历史目录中，以免被当作扫描结果）
+    pub fn checkpoint_path(&self) -> PathBuf {
+        self.save_path.join("wechat-cleaner").join("checkpoint.json")
+    }
 }
 
 /// 清理设置
@@ -44,6 +115,28 @@ pub struct CleanerSettings {
     /// 默认清理模式
     #[serde(default = "default_cleaning_mode")]
     pub mode: CleaningMode,
+
+    /// 清理后删除新产生的空目录（缓存根目录除外）
+    #[serde(default)]
+    pub prune_empty_dirs: bool,
+
+    /// 清理孤立缩略图（同一目录中找不到原始文件的缩略图）
+    ///
+    /// 缩略图只按文件名识别，`cat_t.jpg` 这样的普通文件也可能被当作孤立缩略图，因此默认关闭。
+    #[serde(default)]
+    pub clean_orphaned_thumbnails: bool,
+
+    /// 只保留原始文件：清理全部缩略图，包括孤立缩略图和原始文件仍在的缩略图
+    #[serde(default)]
+    pub keep_only_originals: bool,
+
+    /// 清理零字节文件和未完成下载的临时文件
+    #[serde(default)]
+    pub clean_leftovers: bool,
+
+    /// 残留文件的最短存在时间（小时），修改时间距今不足此时长的残留文件可能仍在下载，不会被清理
+    #[serde(default = "default_leftover_min_age_hours")]
+    pub leftover_min_age_hours: u64,
 }
 
 /// 清理模式
@@ -61,31 +154,80 @@ fn default_wechat_cache_path() -> Option<PathBuf> {
     WechatCacheResolver::find_wechat_dirs()
 }
 
-fn default_cache_patterns() -> String {
-    r"\(\d+\)\.[a-zA-Z0-9]+$".to_string()
+/// 内置的副本文件名模式规则
+///
+/// 按顺序尝试，带序号的 `副本`、`copy` 副本先于通用的序号规则匹配，以便提取到同一个原始名称。
+/// 模式组未经内容验证也会被清理，因此默认只启用与旧版默认模式相同的 `序号副本`（见 `disabled_patterns`）。
+pub fn default_pattern_rules() -> Vec<PatternRule> {
+    vec![
+        PatternRule::new("下划线副本", r"^(.+?)_副本\d*\.[a-zA-Z0-9]+$", 1),
+        PatternRule::new("横线副本", r"^(.+?) - 副本(?: ?\(\d+\))?\.[a-zA-Z0-9]+$", 1),
+        PatternRule::new("copy 副本", r"^(.+?) copy(?: \d+)?\.[a-zA-Z0-9]+$", 1),
+        PatternRule::new("序号副本", r"^(.+?) ?\(\d+\)\.[a-zA-Z0-9]+$", 1),
+        PatternRule::new("时间戳后缀", r"^(.+?)[_-](?:\d{8}[_-]?\d{6}|\d{13}|\d{10})\.[a-zA-Z0-9]+$", 1),
+    ]
+}
+
+fn default_disabled_patterns() -> Vec<String> {
+    ["下划线副本", "横线副本", "copy 副本", "时间戳后缀"].into_iter().map(String::from).collect()
+}
+
+/// 未配置时扫描使用的模式规则：内置规则中默认启用的部分
+pub(crate) fn default_active_pattern_rules() -> Vec<PatternRule> {
+    let disabled = default_disabled_patterns();
+    default_pattern_rules().into_iter().filter(|rule| !disabled.contains(&rule.name)).collect()
 }
 
 fn default_cleaning_mode() -> CleaningMode {
     CleaningMode::Auto
 }
 
+fn default_leftover_min_age_hours() -> u64 {
+    24
+}
+
+fn default_one_file_system() -> bool {
+    true
+}
+
 fn default_scan_result_save_path() -> PathBuf {
     dirs::cache_dir().unwrap_or(PathBuf::from("."))
 }
 
-impl Default for Settings {
+impl Default for WechatSettings {
     fn default() -> Self {
-        Settings {
-            wechat: WechatSettings {
-                cache_path: default_wechat_cache_path(),
-                cache_patterns: default_cache_patterns(),
-            },
-            scanner: ScannerSettings {
-                save_path: default_scan_result_save_path(),
-            },
-            cleaner: CleanerSettings {
-                mode: default_cleaning_mode(),
-            },
+        WechatSettings {
+            cache_path: default_wechat_cache_path(),
+            cache_patterns: String::new(),
+            pattern_rules: default_pattern_rules(),
+            disabled_patterns: default_disabled_patterns(),
+        }
+    }
+}
+
+impl Default for ScannerSettings {
+    fn default() -> Self {
+        ScannerSettings {
+            save_path: default_scan_result_save_path(),
+            storage: StorageFormat::default(),
+            threads: None,
+            max_read_mbps: None,
+            hdd_mode: false,
+            symlinks: SymlinkPolicy::default(),
+            one_file_system: default_one_file_system(),
+        }
+    }
+}
+
+impl Default for CleanerSettings {
+    fn default() -> Self {
+        CleanerSettings {
+            mode: default_cleaning_mode(),
+            prune_empty_dirs: false,
+            clean_orphaned_thumbnails: false,
+            keep_only_originals: false,
+            clean_leftovers: false,
+            leftover_min_age_hours: default_leftover_min_age_hours(),
         }
     }
 }
@@ -104,16 +246,24 @@ impl Merge for WechatSettings {
         // 如果 other 中有新的路径，则更新
         if let Some(path) = other.cache_path {
             if !path.exists() {
-                println!("配置的微信缓存路径不存在，将使用默认路径")
+                eprintln!("配置的微信缓存路径不存在，将使用默认路径")
             } else {
                 self.cache_path = Some(path);
             }
         }
         
-        // 如果 other 中有非空的模式列表，则更新
+        // 如果 other 中有非空的模式或规则列表，则更新
+        // 旧版配置只有 cache_patterns，不能在升级后悄悄加上内置规则
+        if !other.pattern_rules.is_empty() {
+            self.pattern_rules = other.pattern_rules;
+        } else if !other.cache_patterns.is_empty() {
+            self.pattern_rules.clear();
+        }
         if !other.cache_patterns.is_empty() {
             self.cache_patterns = other.cache_patterns;
         }
+        // 未配置时为默认值，配置为空列表表示启用全部规则
+        self.disabled_patterns = other.disabled_patterns;
     }
 }
 
@@ -121,10 +271,20 @@ impl Merge for ScannerSettings {
     fn merge(&mut self, other: Self) {
         // 清理模式直接更新（枚举类型没有“空”状态）
         if !other.save_path.exists() {
-            println!("配置的扫描数据保存路径不存在, 将使用默认路径")
+            eprintln!("配置的扫描数据保存路径不存在, 将使用默认路径")
         } else {
             self.save_path = other.save_path;
         }
+        self.storage = other.storage;
+        if other.threads.is_some() {
+            self.threads = other.threads;
+        }
+        if let Some(mbps) = other.max_read_mbps {
+            self.max_read_mbps = (mbps > 0).then_some(mbps);
+        }
+        self.hdd_mode = other.hdd_mode;
+        self.symlinks = other.symlinks;
+        self.one_file_system = other.one_file_system;
     }
 }
 
@@ -132,5 +292,10 @@ impl Merge for CleanerSettings {
     fn merge(&mut self, other: Self) {
         // 清理模式直接更新（枚举类型没有“空”状态）
         self.mode = other.mode;
+        self.prune_empty_dirs = other.prune_empty_dirs;
+        self.clean_orphaned_thumbnails = other.clean_orphaned_thumbnails;
+        self.keep_only_originals = other.keep_only_originals;
+        self.clean_leftovers = other.clean_leftovers;
+        self.leftover_min_age_hours = other.leftover_min_age_hours;
     }
 }
\ No newline at end of file
@@ -0,0 +1,181 @@
+//! 扫描结果对比模块
+//!
+//! 比较同一缓存目录的两次扫描，统计缓存增长和新出现的重复文件。
+//!
+//! 扫描结果只保存重复文件、残留文件和缩略图的明细，因此新增/消失的文件列表
+//! 只覆盖这些类别；总体增长通过文件总数和按账号、类型的占用统计反映。
+//! 从没有占用统计的旧格式迁移来的扫描无法计算占用变化，相应字段为 `None`。
+
+use crate::file_utils::{FileCategory, FileInfo, HasPath, HasSize};
+use crate::scanner::{ScanResult, UsageStat};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+#[cfg(feature = "display")]
+use crate::Display;
+
+/// 两次扫描之间的差异
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "display", derive(Display))]
+pub struct ScanDiff {
+    #[cfg_attr(feature = "display", display(summary, name="基准扫描"))]
+    pub from: String,
+
+    #[cfg_attr(feature = "display", display(summary, name="对比扫描"))]
+    pub to: String,
+
+    #[cfg_attr(feature = "display", display(summary, name="文件总数变化"))]
+    pub total_files_delta: isize,
+
+    /// 缓存总占用的变化，任一扫描缺少占用统计时为 `None`
+    #[cfg_attr(feature = "display", display(summary, name="缓存占用变化"))]
+    pub total_size_delta: Option<i64>,
+
+    #[cfg_attr(feature = "display", display(summary, name="重复文件占用变化"))]
+    pub duplicate_size_delta: i64,
+
+    /// 对比扫描中新出现的已跟踪文件（重复文件、残留文件、缩略图）
+    #[cfg_attr(feature = "display", display(summary, name="新增文件"))]
+    pub added_files: Vec<FileInfo>,
+
+    /// 基准扫描中存在、对比扫描中已不存在的已跟踪文件
+    #[cfg_attr(feature = "display", display(summary, name="消失文件"))]
+    pub removed_files: Vec<FileInfo>,
+
+    #[cfg_attr(feature = "display", display(details, name="新增重复组"))]
+    pub new_groups: Vec<GroupSummary>,
+
+    #[cfg_attr(feature = "display", display(details, name="消失的重复组"))]
+    pub removed_groups: Vec<GroupSummary>,
+
+    /// 按账号和类型的占用变化，只包含有变化的项；任一扫描缺少占用统计时为 `None`
+    #[cfg_attr(feature = "display", display(details, name="按账号和类型"))]
+    pub usage_deltas: Option<Vec<UsageDelta>>,
+}
+
+/// 重复组摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupSummary {
+    pub key: String,
+    pub files_count: usize,
+    pub total_size: u64,
+}
+
+/// 一个账号下一种文件类型的占用变化
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageDelta {
+    pub account: Option<String>,
+    pub category: FileCategory,
+    pub files_delta: isize,
+    pub bytes_delta: i64,
+}
+
+impl ScanDiff {
+    /// 比较两次扫描，`from` 为较早的基准扫描
+    pub fn between(from: &ScanResult, to: &ScanResult) -> Self {
+        let from_files = tracked_files(from);
+        let to_files = tracked_files(to);
+
+        let added_files = sorted_difference(&to_files, &from_files);
+        let removed_files = sorted_difference(&from_files, &to_files);
+
+        let new_groups = group_difference(to, from);
+        let removed_groups = group_difference(from, to);
+
+        let usage_deltas = (has_usage(from) && has_usage(to)).then(|| usage_deltas(&from.usage, &to.usage));
+        let total_size_delta = usage_deltas.as_ref().map(|deltas| deltas.iter().map(|d| d.bytes_delta).sum());
+
+        ScanDiff {
+            from: from.id.clone(),
+            to: to.id.clone(),
+            total_files_delta: to.total_files_count as isize - from.total_files_count as isize,
+            total_size_delta,
+            duplicate_size_delta: duplicate_size(to) as i64 - duplicate_size(from) as i64,
+            added_files,
+            removed_files,
+            new_groups,
+            removed_groups,
+            usage_deltas,
+        }
+    }
+
+    /// 是否能计算占用变化
+    pub fn has_usage(&self) -> bool {
+        self.usage_deltas.is_some()
+    }
+}
+
+/// 扫描结果是否带有占用统计
+///
+/// 格式版本 4 之前的扫描结果迁移后 `usage` 为空；没有文件的扫描统计本来就为空。
+fn has_usage(result: &ScanResult) -> bool {
+    !result.usage.is_empty() || result.total_files_count == 0
+}
+
+/// 扫描结果中保存了明细的所有文件
+fn tracked_files(result: &ScanResult) -> HashMap<&PathBuf, &FileInfo> {
+    result
+        .duplicate_files
+        .values()
+        .flatten()
+        .chain(&result.leftover_files)
+        .chain(&result.orphaned_derivatives)
+        .chain(&result.attached_derivatives)
+        .map(|file| (file.path(), file))
+        .collect()
+}
+
+/// `left` 中有而 `right` 中没有的文件，按路径排序
+fn sorted_difference(left: &HashMap<&PathBuf, &FileInfo>, right: &HashMap<&PathBuf, &FileInfo>) -> Vec<FileInfo> {
+    let mut files: Vec<FileInfo> = left
+        .iter()
+        .filter(|(path, _)| !right.contains_key(*path))
+        .map(|(_, file)| (*file).clone())
+        .collect();
+    files.sort_by(|a, b| a.path().cmp(b.path()));
+    files
+}
+
+/// `left` 中有而 `right` 中没有的重复组，按组标识排序
+fn group_difference(left: &ScanResult, right: &ScanResult) -> Vec<GroupSummary> {
+    let mut groups: Vec<GroupSummary> = left
+        .duplicate_files
+        .iter()
+        .filter(|(key, _)| !right.duplicate_files.contains_key(*key))
+        .map(|(key, files)| GroupSummary {
+            key: key.clone(),
+            files_count: files.len(),
+            total_size: files.iter().map(HasSize::size).sum(),
+        })
+        .collect();
+    groups.sort_by(|a, b| a.key.cmp(&b.key));
+    groups
+}
+
+fn duplicate_size(result: &ScanResult) -> u64 {
+    result.duplicate_files.values().flatten().map(HasSize::size).sum()
+}
+
+/// 合并两次扫描的占用统计，计算每个账号和类型的变化
+fn usage_deltas(from: &[UsageStat], to: &[UsageStat]) -> Vec<UsageDelta> {
+    let mut deltas: BTreeMap<(Option<String>, FileCategory), (isize, i64)> = BTreeMap::new();
+    for (stats, sign) in [(from, -1), (to, 1)] {
+        for stat in stats {
+            let delta = deltas.entry((stat.account.clone(), stat.category)).or_default();
+            delta.0 += sign as isize * stat.files_count as isize;
+            delta.1 += sign * stat.total_size as i64;
+        }
+    }
+
+    deltas
+        .into_iter()
+        .filter(|(_, (files, bytes))| *files != 0 || *bytes != 0)
+        .map(|((account, category), (files_delta, bytes_delta))| UsageDelta {
+            account,
+            category,
+            files_delta,
+            bytes_delta,
+        })
+        .collect()
+}
@@ -26,8 +26,20 @@ pub enum Error {
     #[error("JSON serialization error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("Binary serialization error: {0}")]
+    Bincode(#[from] bincode::Error),
+
     #[error("Regex error: {0}")]
-    Regex(#[from] regex::Error)
+    Regex(#[from] regex::Error),
+
+    #[error("Nothing to clean in the scan result")]
+    NothingToClean,
+
+    #[error("Scan not found: {0}")]
+    ScanNotFound(String),
+
+    #[error("Unsupported scan result format version {found} (this build supports up to {supported}), please upgrade wechat-cleaner")]
+    UnsupportedFormatVersion { found: u32, supported: u32 },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
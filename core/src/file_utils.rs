@@ -7,20 +7,29 @@
 //! - 跨平台文件权限设置
 //! - 文件元数据收集与处理
 //! - 基于模式和哈希的重复文件检测
+//! - 缩略图等派生文件与原始文件的配对
 //! - 微信缓存目录自动发现
 //!
 //! # 性能优化
 //! - 使用 rayon 进行并行计算
 //! - 按大小预分组避免不必要的哈希计算
-//! - 8KB-32KB 动态缓冲区优化文件读取
+//! - 按文件大小选择读取方式（缓冲区、页对齐大缓冲区、内存映射），见 [`crate::reader`]
 //! - 分层处理逻辑：大小 → 模式 → 哈希
 
+use crate::cancel::CancellationToken;
+use crate::checkpoint::ScanCheckpoint;
 use crate::errors::{Error, Result};
+use crate::filesystem::{FileId, Fs, RealFs, SymlinkPolicy, WalkOptions};
+use crate::hasher::{ContentHasher, Md5Hasher};
+use crate::progress::{NoProgress, Phase, ProgressEvent, ProgressSink};
+use crate::throttle::{Throttle, ThrottledHasher};
 use rayon::prelude::*;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::hash::Hash;
 // 无用导入：文件权限相关的import没有被使用
@@ -29,23 +38,22 @@ use std::hash::Hash;
 // #[cfg(windows)]
 // use std::os::windows::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
-use walkdir::{DirEntry, WalkDir};
 
-/// 设置文件权限（跨平台）
-///
-/// 根据不同操作系统平台设置文件权限，确保跨平台兼容性。
-///
-/// # 参数
-/// * `path` - 要设置权限的文件路径
-/// * `mode` - Unix 权限模式（Windows 下被忽略）
-///
-/// # 返回值
-/// * `Result<()>` - 设置成功返回 Ok(())，失败返回相应错误
-///
-/// # 平台支持
-/// - **Unix/Linux/macOS**: 使用标准的文件权限模式
-/// - **Windows**: 尝试移除只读属性，忽略权限模式参数
-/// - **其他平台**: 记录警告日志，不执行实际操作
+// 设置文件权限（跨平台）
+//
+// 根据不同操作系统平台设置文件权限，确保跨平台兼容性。
+//
+// # 参数
+// * `path` - 要设置权限的文件路径
+// * `mode` - Unix 权限模式（Windows 下被忽略）
+//
+// # 返回值
+// * `Result<()>` - 设置成功返回 Ok(())，失败返回相应错误
+//
+// # 平台支持
+// - **Unix/Linux/macOS**: 使用标准的文件权限模式
+// - **Windows**: 尝试移除只读属性，忽略权限模式参数
+// - **其他平台**: 记录警告日志，不执行实际操作
 // 无用代码：设置文件权限函数没有被使用
 // fn set_file_permissions(path: &Path, mode: u32) -> Result<()> {
 //     #[cfg(unix)]
@@ -77,23 +85,8 @@ use walkdir::{DirEntry, WalkDir};
 //     Ok(())
 // }
 
-/// 检测文件或目录是否为隐藏文件
-///
-/// 根据 Unix 约定，以点号 "." 开头的文件名被认为是隐藏文件。
-/// 在文件扫描过程中用于过滤不需要处理的隐藏文件。
-///
-/// # 参数
-/// * `entry` - walkdir 的目录项
-///
-/// # 返回值
-/// * `bool` - 如果是隐藏文件返回 true，否则返回 false
-fn is_hidden(entry: &DirEntry) -> bool {
-    entry
-        .file_name()
-        .to_str()
-        .map(|s| s.starts_with("."))
-        .unwrap_or(false)
-}
+/// 未完成下载或临时文件的扩展名
+pub const LEFTOVER_EXTENSIONS: &[&str] = &["tmp", "part", "crdownload", "download"];
 
 /// 文件信息结构体
 ///
@@ -104,42 +97,320 @@ fn is_hidden(entry: &DirEntry) -> bool {
 /// * `path` - 文件的绝对路径
 /// * `size` - 文件大小（字节）
 /// * `modified` - 文件最后修改时间（Unix 时间戳）
+/// * `id` - 设备号和 inode，平台不支持时为 `None`
+/// * `links` - 硬链接数，加上并入的符号链接数
+/// * `hard_links` - 扫描范围内指向同一文件的其他路径（跟随符号链接时也包括链接路径）
+/// * `allocated` - 实际分配的磁盘空间（字节），未知时为 `None`
+///
+/// 指向同一 inode 的多个路径在收集时被合并为一个逻辑文件，
+/// 由路径最小的一个作为 `path`，其余记录在 `hard_links` 中。
+///
+/// 路径按原始字节保存（见 [`raw_path`]），无法解码为 UTF-8 的路径同样可以保存和删除。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
+    #[serde(with = "raw_path")]
     path: PathBuf,
     size: u64,
     pub modified: u64,
+    #[serde(default)]
+    id: Option<FileId>,
+    #[serde(default)]
+    links: u64,
+    #[serde(default, with = "raw_path::many")]
+    hard_links: Vec<PathBuf>,
+    #[serde(default)]
+    allocated: Option<u64>,
 }
 
-impl FileInfo {
-    /// 从文件路径创建 FileInfo 实例
+/// 按原始字节序列化路径
+///
+/// JSON 等文本格式中，能解码为 UTF-8 的路径保存为字符串，其余保存为 `{ "raw": [...] }`
+/// （Unix 上为字节，Windows 上为 UTF-16 码元）；二进制格式中总是带标签保存。
+/// 与 serde 默认的 `PathBuf` 序列化不同，不会因为文件名无法解码而失败。
+pub(crate) mod raw_path {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::path::{Path, PathBuf};
+
+    #[cfg(unix)]
+    type Unit = u8;
+    #[cfg(windows)]
+    type Unit = u16;
+    #[cfg(not(any(unix, windows)))]
+    type Unit = u8;
+
+    #[cfg(unix)]
+    fn to_units(path: &Path) -> Vec<Unit> {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_os_str().as_bytes().to_vec()
+    }
+
+    #[cfg(unix)]
+    fn from_units(units: Vec<Unit>) -> PathBuf {
+        use std::os::unix::ffi::OsStringExt;
+        std::ffi::OsString::from_vec(units).into()
+    }
+
+    #[cfg(windows)]
+    fn to_units(path: &Path) -> Vec<Unit> {
+        use std::os::windows::ffi::OsStrExt;
+        path.as_os_str().encode_wide().collect()
+    }
+
+    #[cfg(windows)]
+    fn from_units(units: Vec<Unit>) -> PathBuf {
+        use std::os::windows::ffi::OsStringExt;
+        std::ffi::OsString::from_wide(&units).into()
+    }
+
+    // 其他平台的路径总是 UTF-8，不会走到原始字节分支
+    #[cfg(not(any(unix, windows)))]
+    fn to_units(path: &Path) -> Vec<Unit> {
+        path.to_string_lossy().into_owned().into_bytes()
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn from_units(units: Vec<Unit>) -> PathBuf {
+        String::from_utf8_lossy(&units).into_owned().into()
+    }
+
+    /// 文本格式中的表示
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Readable {
+        Utf8(String),
+        Raw { raw: Vec<Unit> },
+    }
+
+    /// 二进制格式中的表示（不支持无标签枚举）
+    #[derive(Serialize, Deserialize)]
+    enum Binary {
+        Utf8(String),
+        Raw(Vec<Unit>),
+    }
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        let utf8 = path.to_str().map(str::to_owned);
+        if serializer.is_human_readable() {
+            match utf8 {
+                Some(s) => Readable::Utf8(s),
+                None => Readable::Raw { raw: to_units(path) },
+            }
+            .serialize(serializer)
+        } else {
+            match utf8 {
+                Some(s) => Binary::Utf8(s),
+                None => Binary::Raw(to_units(path)),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        if deserializer.is_human_readable() {
+            Ok(match Readable::deserialize(deserializer)? {
+                Readable::Utf8(s) => s.into(),
+                Readable::Raw { raw } => from_units(raw),
+            })
+        } else {
+            Ok(match Binary::deserialize(deserializer)? {
+                Binary::Utf8(s) => s.into(),
+                Binary::Raw(raw) => from_units(raw),
+            })
+        }
+    }
+
+    /// 以路径为键的映射
     ///
-    /// 读取文件的元数据信息并创建相应的 FileInfo 结构体。
-    /// 会自动获取文件大小和最后修改时间。
+    /// 文本格式的映射键只能是字符串，无法解码的路径保存为其 `{ "raw": [...] }` 形式的 JSON 文本；
+    /// 读取时只有内容确实无法解码的该形式才还原为原始路径。二进制格式中同 [`raw_path`](self)。
+    pub mod keys {
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use std::collections::BTreeMap;
+        use std::path::PathBuf;
+
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Key(PathBuf);
+
+        impl Serialize for Key {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if !serializer.is_human_readable() {
+                    return super::serialize(&self.0, serializer);
+                }
+                match self.0.to_str() {
+                    Some(s) => serializer.serialize_str(s),
+                    None => {
+                        let raw = super::Readable::Raw { raw: super::to_units(&self.0) };
+                        serializer.serialize_str(&serde_json::to_string(&raw).map_err(serde::ser::Error::custom)?)
+                    }
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for Key {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if !deserializer.is_human_readable() {
+                    return super::deserialize(deserializer).map(Key);
+                }
+                let s = String::deserialize(deserializer)?;
+                if let Ok(super::Readable::Raw { raw }) = serde_json::from_str(&s) {
+                    let path = super::from_units(raw);
+                    if path.to_str().is_none() {
+                        return Ok(Key(path));
+                    }
+                }
+                Ok(Key(s.into()))
+            }
+        }
+
+        pub fn serialize<'a, M, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            &'a M: IntoIterator<Item = (&'a PathBuf, &'a V)>,
+            V: Serialize + 'a,
+            S: Serializer,
+        {
+            serializer.collect_map(map.into_iter().map(|(path, value)| (Key(path.clone()), value)))
+        }
+
+        pub fn deserialize<'de, M, V, D>(deserializer: D) -> Result<M, D::Error>
+        where
+            M: FromIterator<(PathBuf, V)>,
+            V: Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            Ok(BTreeMap::<Key, V>::deserialize(deserializer)?.into_iter().map(|(Key(path), value)| (path, value)).collect())
+        }
+    }
+
+    /// 路径列表，每个路径按 [`raw_path`](self) 序列化
+    pub mod many {
+        use serde::ser::SerializeSeq;
+        use serde::{Deserialize, Deserializer, Serializer};
+        use std::path::PathBuf;
+
+        #[derive(serde::Serialize, Deserialize)]
+        struct Item(#[serde(with = "super")] PathBuf);
+
+        pub fn serialize<S: Serializer>(paths: &[PathBuf], serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(paths.len()))?;
+            for path in paths {
+                seq.serialize_element(&Item(path.clone()))?;
+            }
+            seq.end()
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<PathBuf>, D::Error> {
+            Ok(Vec::<Item>::deserialize(deserializer)?.into_iter().map(|Item(path)| path).collect())
+        }
+    }
+}
+
+/// 收集目录得到的文件信息
+#[derive(Debug, Default)]
+pub struct CollectedFiles {
+    /// 合并硬链接后的逻辑文件
+    pub files: Vec<FileInfo>,
+    /// 未作为文件扫描的符号链接，见 [`FileInfo::collect_with`]
+    pub symlinks: Vec<PathBuf>,
+}
+
+impl FileInfo {
+    /// 使用已知的元数据创建文件信息，不访问文件系统
     ///
     /// # 参数
-    /// * `file` - 文件路径引用
+    /// * `path` - 文件路径
+    /// * `size` - 文件大小（字节）
+    /// * `modified` - 最后修改时间（Unix 时间戳，秒）
+    pub fn new(path: impl Into<PathBuf>, size: u64, modified: u64) -> Self {
+        FileInfo {
+            path: path.into(),
+            size,
+            modified,
+            id: None,
+            links: 1,
+            hard_links: Vec::new(),
+            allocated: None,
+        }
+    }
+
+    /// 文件名是否无法解码为 UTF-8
     ///
-    /// # 返回值
-    /// * `Result<Self>` - 成功返回 FileInfo 实例，失败返回错误
+    /// 这类文件没有可用于模式匹配的名称，只按内容参与重复检测。
+    pub fn has_undecodable_name(&self) -> bool {
+        self.path.file_name().is_some_and(|name| name.to_str().is_none())
+    }
+
+    /// 设备号和 inode，平台不支持或来自旧的扫描结果时为 `None`
+    pub fn id(&self) -> Option<FileId> {
+        self.id
+    }
+
+    /// 文件的硬链接数（包括扫描范围之外的链接），跟随符号链接时加上并入的链接数
+    pub fn links(&self) -> u64 {
+        self.links
+    }
+
+    /// 扫描范围内指向同一文件的其他路径，删除该文件时一并删除
+    pub fn hard_links(&self) -> &[PathBuf] {
+        &self.hard_links
+    }
+
+    /// 删除该文件要删除的路径数：`path` 加上 `hard_links`
+    pub fn path_count(&self) -> usize {
+        1 + self.hard_links.len()
+    }
+
+    /// 替换扫描范围内的其他硬链接路径
+    pub fn with_hard_links(&self, hard_links: Vec<PathBuf>) -> Self {
+        FileInfo { hard_links, ..self.clone() }
+    }
+
+    /// 文件占用的磁盘空间，平台不支持或来自旧的扫描结果时使用文件大小
+    pub fn allocated_size(&self) -> u64 {
+        self.allocated.unwrap_or(self.size)
+    }
+
+    /// 删除该文件（包括 `hard_links` 中的路径）释放的逻辑大小
+    ///
+    /// 扫描范围之外还有硬链接时，删除后数据块仍被占用，释放空间为 0。
+    pub fn freed_size(&self) -> u64 {
+        if self.has_outside_links() { 0 } else { self.size }
+    }
+
+    /// 删除该文件（包括 `hard_links` 中的路径）释放的磁盘空间，规则同 [`FileInfo::freed_size`]
+    pub fn freed_disk_size(&self) -> u64 {
+        if self.has_outside_links() { 0 } else { self.allocated_size() }
+    }
+
+    /// 扫描范围之外是否还有指向该文件的硬链接
+    fn has_outside_links(&self) -> bool {
+        self.links > 1 + self.hard_links.len() as u64
+    }
+
+    /// 读取真实文件系统中的文件信息
+    ///
+    /// # 错误
+    /// 路径不是文件或无法访问时返回错误。
+    pub fn from_path(file: &Path) -> Result<Self> {
+        Self::from_fs(&RealFs, file)
+    }
+
+    /// 通过指定的文件系统后端读取文件信息
+    ///
+    /// 读取文件的元数据信息并创建相应的 FileInfo 结构体，
+    /// 包括文件大小和最后修改时间。
     ///
     /// # 错误
-    /// 当文件不存在或无法访问时返回 IO 错误。
-    fn new(file: &Path) -> Result<Self> {
-        if !file.is_file() {
-            return Err(Error::FileProcessing("FileInfo: 请传入一个文件".to_string()))
-        }
-        let metadata = fs::metadata(file)?;
-        let size = metadata.len();
-        let modified = metadata
-            .modified()?
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs();
+    /// 路径不是文件或无法访问时返回错误。
+    pub fn from_fs(fs: &dyn Fs, file: &Path) -> Result<Self> {
+        let meta = fs
+            .metadata(file)
+            .map_err(|e| Error::FileProcessing(format!("FileInfo: {} - {}", file.display(), e)))?;
 
         Ok(FileInfo {
-            path: file.to_path_buf(),
-            size,
-            modified,
+            id: meta.id,
+            links: meta.links,
+            allocated: meta.allocated,
+            ..FileInfo::new(file, meta.size, meta.modified)
         })
     }
 
@@ -162,97 +433,441 @@ impl FileInfo {
     /// # 错误
     /// - `Error::CacheNotFound` - 目录不存在或无文件
     pub fn collect_from(path: &Path) -> Option<Vec<Self>> {
+        Self::collect_from_fs(&RealFs, path, &NoProgress, &CancellationToken::new())
+    }
+
+    /// 通过指定的文件系统后端收集目录下的所有文件信息，规则同 [`FileInfo::collect_from`]
+    pub fn collect_from_fs(
+        fs: &dyn Fs,
+        path: &Path,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Option<Vec<Self>> {
+        Self::collect_with(fs, path, &WalkOptions::default(), progress, cancel).map(|collected| collected.files)
+    }
+
+    /// 按遍历选项收集目录下的所有文件信息
+    ///
+    /// 依次报告遍历目录和读取元数据两个阶段：遍历完成后发出 `FilesDiscovered` 事件，
+    /// 之后每读取一个文件发出 `MetadataRead` 事件，无法读取元数据的文件发出 `Error` 事件。
+    /// 取消后不再读取剩余文件，返回已读取的部分。
+    ///
+    /// 设置了 `options.one_file_system` 时，与根目录不在同一设备上的目录和文件不参与扫描。
+    ///
+    /// 指向同一 inode 的路径被合并为一个逻辑文件。符号链接按 `options.symlinks` 处理：
+    /// - `Skip`：忽略
+    /// - `Follow`：跟随；指向扫描范围内文件的链接并入目标文件，
+    ///   目标在扫描范围之外的文件链接不参与扫描（删除链接不释放空间），列入 `symlinks`
+    /// - `Report`：不跟随，全部列入 `symlinks`
+    pub fn collect_with(
+        fs: &dyn Fs,
+        path: &Path,
+        options: &WalkOptions,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Option<CollectedFiles> {
         // 先检查路径是否存在
-        if !path.is_dir() {
+        if !fs.is_dir(path) {
             return None;
         }
 
         // 优化1: 首先收集所有文件路径（快速操作）
-        let file_entries: Vec<_> = WalkDir::new(path)
-            .into_iter()
-            .filter_entry(|e| !is_hidden(e))
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-            .collect();
+        progress.on_event(&ProgressEvent::PhaseStarted { phase: Phase::Walk, total: None });
+        let walked = fs.walk_with(path, options);
+        progress.on_event(&ProgressEvent::PhaseFinished { phase: Phase::Walk });
+        let walked = walked.ok()?;
+        let file_entries = walked.files;
 
         if file_entries.is_empty() {
             return None;
         }
+        progress.on_event(&ProgressEvent::FilesDiscovered { count: file_entries.len() });
 
         // 优化2: 预分配容量并使用并行处理进行元数据收集
-        let files: Vec<Self> = file_entries
+        progress.on_event(&ProgressEvent::PhaseStarted {
+            phase: Phase::Metadata,
+            total: Some(file_entries.len() as u64),
+        });
+        let mut files: Vec<Self> = file_entries
             .into_par_iter()
             .filter_map(|entry| {
-                // 优化3: 减少错误处理开销，只记录严重错误
-                match FileInfo::new(entry.path()) {
-                    Ok(info) => Some(info),
-                    Err(_e) => {
-                        // 只在 debug 模式下记录详细日志
-                        #[cfg(debug_assertions)]
-                        log::warn!("Failed to process {}: {}", entry.path().display(), _e);
+                if cancel.is_cancelled() {
+                    return None;
+                }
+                match FileInfo::from_fs(fs, &entry) {
+                    Ok(info) => {
+                        progress.on_event(&ProgressEvent::MetadataRead { path: &entry, size: info.size });
+                        Some(info)
+                    }
+                    Err(e) => {
+                        let message = e.to_string();
+                        progress.on_event(&ProgressEvent::Error { path: Some(&entry), message: &message });
                         None
                     }
                 }
             })
             .collect();
+        progress.on_event(&ProgressEvent::PhaseFinished { phase: Phase::Metadata });
+
+        // 遍历只在目录层面检查挂载点，单独挂载的文件在这里排除
+        if options.one_file_system
+            && let Some(device) = fs.device(path)
+        {
+            files.retain(|file| file.id.is_none_or(|id| id.device == device));
+        }
+
+        let (files, symlinks) = match options.symlinks {
+            SymlinkPolicy::Follow => {
+                let linked: HashSet<PathBuf> = walked.symlinks.into_iter().collect();
+                let (outside, files): (Vec<_>, Vec<_>) = collapse_hard_links(files, &linked)
+                    .into_iter()
+                    .partition(|file| linked.contains(&file.path));
+                (files, outside.into_iter().map(|file| file.path).collect())
+            }
+            _ => (collapse_hard_links(files, &HashSet::new()), walked.symlinks),
+        };
 
         if files.is_empty() {
             None
         } else {
-            Some(files)
+            Some(CollectedFiles { files, symlinks })
         }
     }
+
+    /// 判断文件是否为下载残留
+    ///
+    /// 零字节文件以及未完成下载的临时文件（见 [`LEFTOVER_EXTENSIONS`]）
+    /// 不参与重复检测，而是作为单独的可清理类别。
+    pub fn is_leftover(&self) -> bool {
+        self.size == 0
+            || self
+                .path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| LEFTOVER_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+    }
+
+    /// 根据扩展名判断文件类型
+    pub fn category(&self) -> FileCategory {
+        self.path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(FileCategory::from_extension)
+            .unwrap_or(FileCategory::Other)
+    }
+
+    /// 文件所属的微信账号（路径中以 `wxid_` 开头的目录名）
+    pub fn account(&self) -> Option<String> {
+        self.path
+            .components()
+            .filter_map(|c| c.as_os_str().to_str())
+            .find(|name| name.starts_with(ACCOUNT_DIR_PREFIX))
+            .map(str::to_owned)
+    }
+}
+
+/// 微信账号目录名前缀
+const ACCOUNT_DIR_PREFIX: &str = "wxid_";
+
+/// 把指向同一 inode 的文件合并为一个逻辑文件
+///
+/// 优先选择不在 `symlinks` 中的路径作为代表，其次选择最小的路径，
+/// 其余路径记录在代表文件的 `hard_links` 中。结果按路径排序。
+fn collapse_hard_links(files: Vec<FileInfo>, symlinks: &HashSet<PathBuf>) -> Vec<FileInfo> {
+    // 符号链接的目标通常只有一个硬链接，需要按标识一并合并
+    let linked_ids: HashSet<FileId> = files
+        .iter()
+        .filter(|file| symlinks.contains(&file.path))
+        .filter_map(|file| file.id)
+        .collect();
+    let (linked, mut result): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|file| file.id.is_some_and(|id| file.links > 1 || linked_ids.contains(&id)));
+
+    for (_, mut group) in linked.group_by(|file| file.id) {
+        group.sort_by(|a, b| {
+            symlinks
+                .contains(&a.path)
+                .cmp(&symlinks.contains(&b.path))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        let mut aliases = group.into_iter();
+        let Some(mut primary) = aliases.next() else { continue };
+        for alias in aliases {
+            // 符号链接不计入硬链接数，计入后 `links` 才能与 `hard_links` 的数量比较
+            if symlinks.contains(&alias.path) {
+                primary.links += 1;
+            }
+            primary.hard_links.push(alias.path);
+        }
+        result.push(primary);
+    }
+    result.sort_by(|a, b| a.path.cmp(&b.path));
+    result
+}
+
+/// 文件类型，用于按类型统计缓存占用
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileCategory {
+    Image,
+    Video,
+    Audio,
+    Document,
+    Archive,
+    Other,
+}
+
+impl FileCategory {
+    /// 根据扩展名（不区分大小写）判断文件类型
+    pub fn from_extension(ext: &str) -> Self {
+        match ext.to_ascii_lowercase().as_str() {
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "heic" | "dat" => FileCategory::Image,
+            "mp4" | "mov" | "avi" | "mkv" | "m4v" => FileCategory::Video,
+            "mp3" | "m4a" | "aac" | "wav" | "amr" | "silk" => FileCategory::Audio,
+            "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "txt" | "csv" | "md" => FileCategory::Document,
+            "zip" | "rar" | "7z" | "tar" | "gz" => FileCategory::Archive,
+            _ => FileCategory::Other,
+        }
+    }
+}
+
+impl std::fmt::Display for FileCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FileCategory::Image => "图片",
+            FileCategory::Video => "视频",
+            FileCategory::Audio => "音频",
+            FileCategory::Document => "文档",
+            FileCategory::Archive => "压缩包",
+            FileCategory::Other => "其他",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 将文件名解码并规范化为 NFC
+///
+/// 同一个文件名在 macOS 上同步而来时常为分解形式（NFD），规范化后才能与组合形式视为相同。
+/// 无法解码为 UTF-8 的文件名返回 `None`，而不是替换为 `U+FFFD` 后与其他文件名混在一起。
+pub fn normalize_name(name: &OsStr) -> Option<Cow<'_, str>> {
+    let name = name.to_str()?;
+    match is_nfc_quick(name.chars()) {
+        IsNormalized::Yes => Some(Cow::Borrowed(name)),
+        _ => Some(Cow::Owned(name.nfc().collect())),
+    }
 }
 
 /// 文件名称相关操作 trait
 ///
 /// 为文件对象提供名称提取和模式匹配功能。
 /// 支持基本文件名、基本名称和模式匹配名称提取。
+///
+/// 返回的名称均经过 [`normalize_name`] 规范化，只用于分组和匹配；
+/// 读取和删除文件始终使用原始路径。
 pub trait Named {
-    /// 获取完整文件名（包含扩展名）
+    /// 获取完整文件名（包含扩展名），无法解码时为 `None`
     fn name(&self) -> Option<Cow<'_, str>>;
 
-    /// 获取文件的基本名称（不包含扩展名）
+    /// 获取文件的基本名称（不包含扩展名），无法解码时为 `None`
     fn base_name(&self) -> Option<Cow<'_, str>>;
 
-    /// 根据正则表达式提取模式化名称
+    /// 按模式规则提取文件名对应的原始名称及匹配的规则，文件名无法解码时为 `None`
+    fn pattern_match<'p>(&self, patterns: &'p PatternSet) -> Option<PatternMatch<'p>> {
+        Some(patterns.match_name(&self.name()?))
+    }
+
+    /// 根据模式规则提取模式化名称
     ///
-    /// 优先使用正则表达式匹配，如果匹配失败则移除扩展名。
+    /// 依次尝试各条规则，取第一条匹配规则的捕获组；没有规则匹配时移除扩展名。
     /// 用于识别具有类似模式的文件（如序号后缀、时间戳等）。
-    fn patterned_name(&self, regex: &Regex) -> Option<Cow<'_, str>>;
+    fn patterned_name(&self, patterns: &PatternSet) -> Option<Cow<'_, str>> {
+        self.pattern_match(patterns).map(|matched| Cow::Owned(matched.name))
+    }
 }
 
 impl Named for FileInfo {
     fn name(&self) -> Option<Cow<'_, str>> {
-        self.path.file_name().map(|n| n.to_string_lossy())
+        self.path.file_name().and_then(normalize_name)
     }
 
     fn base_name(&self) -> Option<Cow<'_, str>> {
-        self.path.file_stem().map(|n| n.to_string_lossy())
+        self.path.file_stem().and_then(normalize_name)
     }
+}
 
-    fn patterned_name(&self, regex: &Regex) -> Option<Cow<'_, str>> {
-        let file_name = self.name()?;
+/// 识别副本文件名的模式规则
+///
+/// `regex` 匹配完整的文件名（已规范化为 NFC），第 `group` 个捕获组为副本对应的原始名称（不含扩展名），
+/// 例如 `^(.+?)\(\d+\)\.[a-zA-Z0-9]+$` 从 `a(1).pdf` 中提取 `a`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternRule {
+    /// 规则名称，用于报告和禁用规则
+    pub name: String,
+    /// 匹配副本文件名的正则表达式
+    pub regex: String,
+    /// 原始名称所在的捕获组，默认为 1
+    #[serde(default = "default_capture_group")]
+    pub group: usize,
+}
 
-        // 优先使用正则表达式匹配，提取匹配位置之前的部分作为基本名称
-        if let Some(captures) = regex.captures(file_name.as_ref()) {
-            if let Some(matched) = captures.get(0) {
-                let base_name = &file_name.as_ref()[..matched.start()];
-                if !base_name.is_empty() {
-                    return Some(Cow::Owned(base_name.to_owned()));
+fn default_capture_group() -> usize {
+    1
+}
+
+impl PatternRule {
+    /// 创建模式规则
+    pub fn new(name: impl Into<String>, regex: impl Into<String>, group: usize) -> Self {
+        PatternRule { name: name.into(), regex: regex.into(), group }
+    }
+
+    /// 由旧版的单个正则表达式创建规则：取第一个匹配位置之前的部分作为原始名称
+    pub fn legacy(name: impl Into<String>, pattern: &str) -> Self {
+        Self::new(name, format!("^(.*?)(?:{})", pattern), 1)
+    }
+}
+
+/// 编译后的模式规则集合，按顺序尝试
+#[derive(Debug, Clone)]
+pub struct PatternSet {
+    rules: Vec<(PatternRule, Regex)>,
+}
+
+/// 文件名按模式规则提取的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternMatch<'a> {
+    /// 匹配的规则名称，没有规则匹配（取去掉扩展名的名称）时为 `None`
+    pub rule: Option<&'a str>,
+    /// 用于分组的原始名称
+    pub name: String,
+}
+
+impl PatternSet {
+    /// 编译模式规则
+    ///
+    /// # 错误
+    /// - `Error::Regex` - 正则表达式无效
+    /// - `Error::Config` - 正则表达式没有指定的捕获组
+    pub fn new(rules: &[PatternRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let regex = Regex::new(&rule.regex)?;
+                if rule.group >= regex.captures_len() {
+                    return Err(Error::Config(format!("模式规则 {} 没有第 {} 个捕获组", rule.name, rule.group)));
                 }
+                Ok((rule.clone(), regex))
+            })
+            .collect::<Result<_>>()?;
+        Ok(PatternSet { rules })
+    }
+
+    /// 按顺序排列的规则
+    pub fn rules(&self) -> impl Iterator<Item = &PatternRule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+
+    /// 提取文件名对应的原始名称
+    ///
+    /// 取第一条匹配且捕获组非空的规则；都不匹配时移除扩展名，
+    /// 因此原始文件 `a.pdf` 与副本 `a(1).pdf` 得到相同的名称。
+    pub fn match_name(&self, file_name: &str) -> PatternMatch<'_> {
+        for (rule, regex) in &self.rules {
+            if let Some(base_name) = regex
+                .captures(file_name)
+                .and_then(|captures| captures.get(rule.group))
+                .filter(|base_name| !base_name.is_empty())
+            {
+                return PatternMatch { rule: Some(&rule.name), name: base_name.as_str().to_owned() };
             }
         }
 
-        // 如果正则匹配失败，则移除文件扩展名
-        if let Some(dot_pos) = file_name.as_ref().rfind(".") {
-            Some(Cow::Owned(file_name.as_ref()[..dot_pos].to_owned()))
-        } else {
-            Some(file_name)
+        let name = match file_name.rfind('.') {
+            Some(dot_pos) => &file_name[..dot_pos],
+            None => file_name,
+        };
+        PatternMatch { rule: None, name: name.to_owned() }
+    }
+
+    /// 预览规则对一批文件的分组效果
+    ///
+    /// 只按文件名分组，不读取文件内容，也不区分残留文件和缩略图，
+    /// 用于在规则影响清理之前检查它匹配了哪些文件、会形成哪些组。
+    pub fn preview<T: HasPath + Named>(&self, files: &[T]) -> PatternPreview {
+        let mut matched: Vec<PatternPreviewFile> = files
+            .iter()
+            .map(|file| {
+                let matched = file.pattern_match(self);
+                PatternPreviewFile {
+                    path: file.path().clone(),
+                    rule: matched.as_ref().and_then(|m| m.rule).map(str::to_owned),
+                    name: matched.map(|m| m.name),
+                    group_size: 0,
+                }
+            })
+            .collect();
+        matched.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut groups: HashMap<String, PatternPreviewGroup> = HashMap::new();
+        for file in &matched {
+            let Some(name) = &file.name else { continue };
+            let group = groups.entry(name.clone()).or_insert_with(|| PatternPreviewGroup {
+                name: name.clone(),
+                rules: Vec::new(),
+                files: Vec::new(),
+            });
+            group.files.push(file.path.clone());
+            if let Some(rule) = &file.rule
+                && !group.rules.contains(rule)
+            {
+                group.rules.push(rule.clone());
+            }
         }
+
+        for file in &mut matched {
+            file.group_size = file.name.as_ref().map_or(0, |name| groups[name].files.len());
+        }
+
+        let mut groups: Vec<PatternPreviewGroup> = groups.into_values().filter(|group| group.files.len() > 1).collect();
+        groups.sort_by(|a, b| b.files.len().cmp(&a.files.len()).then_with(|| a.name.cmp(&b.name)));
+        PatternPreview { files: matched, groups }
     }
 }
 
+/// 模式规则对一批文件的分组预览，见 [`PatternSet::preview`]
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternPreview {
+    /// 每个文件的匹配结果，按路径排序
+    pub files: Vec<PatternPreviewFile>,
+    /// 包含多个文件的组，按文件数从多到少排序
+    pub groups: Vec<PatternPreviewGroup>,
+}
+
+/// 单个文件的模式匹配结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternPreviewFile {
+    #[serde(with = "raw_path")]
+    pub path: PathBuf,
+    /// 匹配的规则，没有规则匹配时为 `None`
+    pub rule: Option<String>,
+    /// 模式化名称，即所在组的键；文件名无法解码时为 `None`
+    pub name: Option<String>,
+    /// 所在组的文件数（包括该文件）
+    pub group_size: usize,
+}
+
+/// 按模式化名称形成的组
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternPreviewGroup {
+    /// 模式化名称
+    pub name: String,
+    /// 组内文件匹配到的规则，按首次出现的顺序
+    pub rules: Vec<String>,
+    /// 组内的文件，按路径排序
+    #[serde(with = "raw_path::many")]
+    pub files: Vec<PathBuf>,
+}
+
 /// 文件大小相关操作 trait
 ///
 /// 为文件对象提供获取文件大小的能力。
@@ -281,45 +896,114 @@ impl HasPath for FileInfo {
 /// 文件哈希相关操作 trait
 ///
 /// 为文件对象提供计算哈希值的能力。
-/// 使用 MD5 算法计算文件内容的哈希值，用于精确的重复文件检测。
+/// 默认使用 MD5 算法计算文件内容的哈希值，用于精确的重复文件检测。
 pub trait Hashed {
     /// 计算文件的 MD5 哈希值
     ///
     /// # 返回值
     /// * `Option<String>` - 成功返回哈希值字符串，失败返回 None
-    fn hash(&self) -> Option<String>;
+    fn hash(&self) -> Option<String> {
+        self.hash_with(&RealFs)
+    }
+
+    /// 通过指定的文件系统后端读取内容并计算 MD5 哈希值
+    fn hash_with(&self, fs: &dyn Fs) -> Option<String> {
+        self.hash_using(fs, &Md5Hasher)
+    }
+
+    /// 通过指定的文件系统后端读取内容，使用指定算法计算哈希值
+    fn hash_using(&self, fs: &dyn Fs, hasher: &dyn ContentHasher) -> Option<String>;
 }
 
 impl Hashed for FileInfo {
-    fn hash(&self) -> Option<String> {
-        use md5::{Digest, Md5};
-        use std::io::{BufReader, Read};
+    fn hash_using(&self, fs: &dyn Fs, hasher: &dyn ContentHasher) -> Option<String> {
+        // 由文件系统后端按文件大小选择读取方式
+        fs.hash_file(&self.path, self.size, hasher).ok()
+    }
+}
 
-        // 使用 BufReader 优化 I/O 性能
-        let file = fs::File::open(&self.path).ok()?;
-        let mut reader = BufReader::with_capacity(65536, file); // 64KB 缓冲区
-        let mut hasher = Md5::new();
+/// 计算哈希时使用的上下文
+///
+/// 汇集文件系统后端、哈希算法、进度接收者、取消令牌、摘要缓存和读取方式，
+/// 未设置的项使用默认值：MD5、不报告进度、不可取消、不缓存摘要、不限速并行读取。
+#[derive(Clone, Copy)]
+pub struct HashContext<'a> {
+    fs: &'a dyn Fs,
+    hasher: &'a dyn ContentHasher,
+    progress: &'a dyn ProgressSink,
+    cancel: Option<&'a CancellationToken>,
+    checkpoint: Option<&'a ScanCheckpoint>,
+    throttle: Option<&'a Throttle>,
+    sequential: bool,
+}
 
-        // 根据文件大小动态调整缓冲区大小
-        let buffer_size = if self.size < 1024 * 1024 {
-            8192 // 8KB - 适合小文件
-        } else {
-            32768 // 32KB - 适合大文件
-        };
+impl<'a> HashContext<'a> {
+    /// 使用指定文件系统后端和默认设置创建上下文
+    pub fn new(fs: &'a dyn Fs) -> Self {
+        HashContext {
+            fs,
+            hasher: &Md5Hasher,
+            progress: &NoProgress,
+            cancel: None,
+            checkpoint: None,
+            throttle: None,
+            sequential: false,
+        }
+    }
 
-        let mut buffer = vec![0u8; buffer_size];
+    /// 设置哈希算法
+    pub fn hasher(mut self, hasher: &'a dyn ContentHasher) -> Self {
+        self.hasher = hasher;
+        self
+    }
 
-        // 流式读取文件内容并计算哈希
-        loop {
-            match reader.read(&mut buffer) {
-                Ok(0) => break, // EOF
-                Ok(n) => hasher.update(&buffer[..n]),
-                Err(_) => return None, // 读取失败
-            }
-        }
+    /// 设置进度接收者
+    pub fn progress(mut self, progress: &'a dyn ProgressSink) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// 设置取消令牌
+    pub fn cancel(mut self, cancel: &'a CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
 
-        let result = hasher.finalize();
-        Some(format!("{:x}", result))
+    /// 设置检查点：复用其中已计算的摘要，并记录新计算的摘要
+    pub fn checkpoint(mut self, checkpoint: &'a ScanCheckpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// 设置读取带宽限制
+    pub fn throttle(mut self, throttle: &'a Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// 设置是否按磁盘顺序逐个计算哈希（机械硬盘模式）
+    pub fn sequential(mut self, sequential: bool) -> Self {
+        self.sequential = sequential;
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// 计算单个文件的哈希，优先使用检查点中的摘要
+    fn hash<T: Hashed + HasPath>(&self, item: &T) -> Option<String> {
+        if let Some(digest) = self.checkpoint.and_then(|c| c.digest(item.path())) {
+            return Some(digest);
+        }
+        let digest = match self.throttle {
+            Some(throttle) => item.hash_using(self.fs, &ThrottledHasher::new(self.hasher, throttle))?,
+            None => item.hash_using(self.fs, self.hasher)?,
+        };
+        if let Some(checkpoint) = self.checkpoint {
+            checkpoint.record_digest(item.path(), &digest);
+        }
+        Some(digest)
     }
 }
 
@@ -372,14 +1056,14 @@ pub trait FileGrouper: IntoIterator {
 
     /// 按文件名模式分组
     ///
-    /// 根据正则表达式对文件名进行模式匹配分组。
+    /// 根据模式规则对文件名进行模式匹配分组。
     /// 用于识别具有类似命名模式的文件（如序号后缀、时间戳等）。
-    fn group_by_pattern(self, regex: &Regex) -> HashMap<String, Vec<Self::Item>>
+    fn group_by_pattern(self, patterns: &PatternSet) -> HashMap<String, Vec<Self::Item>>
     where
         Self: Sized,
         Self::Item: Named,
     {
-        self.group_by(|item| item.patterned_name(regex).map(|name| name.into_owned()))
+        self.group_by(|item| item.patterned_name(patterns).map(|name| name.into_owned()))
     }
 
     /// 按文件哈希值分组（并行版本）
@@ -393,15 +1077,56 @@ pub trait FileGrouper: IntoIterator {
     fn group_by_hash(self) -> HashMap<String, Vec<Self::Item>>
     where
         Self: Sized + Send,
-        Self::Item: Hashed + Send,
+        Self::Item: Hashed + HasPath + HasSize + Send,
     {
-        let items: Vec<_> = self.into_iter().collect();
+        self.group_by_hash_with(&RealFs)
+    }
 
-        // 并行计算所有文件的哈希值
-        let hash_pairs: Vec<(String, Self::Item)> = items
-            .into_par_iter()
-            .filter_map(|item| item.hash().map(|hash| (hash, item)))
-            .collect();
+    /// 通过指定的文件系统后端读取内容，按文件哈希值分组
+    fn group_by_hash_with(self, fs: &dyn Fs) -> HashMap<String, Vec<Self::Item>>
+    where
+        Self: Sized + Send,
+        Self::Item: Hashed + HasPath + HasSize + Send,
+    {
+        self.group_by_hash_using(&HashContext::new(fs))
+    }
+
+    /// 按上下文中的文件系统后端和哈希算法按文件哈希值分组
+    ///
+    /// 每个文件完成哈希后发出 `BytesHashed` 事件，读取失败的文件发出 `HashFailed` 事件。
+    /// 取消后不再计算剩余文件的哈希，这些文件不会出现在结果中。
+    /// 上下文设置了检查点时，已记录摘要的文件不再重新读取。
+    /// 顺序模式下按 [`Fs::disk_order`] 逐个读取文件，避免机械硬盘来回寻道。
+    fn group_by_hash_using(self, ctx: &HashContext) -> HashMap<String, Vec<Self::Item>>
+    where
+        Self: Sized + Send,
+        Self::Item: Hashed + HasPath + HasSize + Send,
+    {
+        let mut items: Vec<_> = self.into_iter().collect();
+
+        let hash_one = |item: Self::Item| {
+            if ctx.is_cancelled() {
+                return None;
+            }
+            let Some(hash) = ctx.hash(&item) else {
+                ctx.progress.on_event(&ProgressEvent::HashFailed {
+                    path: item.path(),
+                    bytes: item.size(),
+                    message: "无法读取文件内容",
+                });
+                return None;
+            };
+            ctx.progress.on_event(&ProgressEvent::BytesHashed { path: item.path(), bytes: item.size() });
+            Some((hash, item))
+        };
+
+        let hash_pairs: Vec<(String, Self::Item)> = if ctx.sequential {
+            items.sort_by_cached_key(|item| (ctx.fs.disk_order(item.path()), item.path().to_path_buf()));
+            items.into_iter().filter_map(hash_one).collect()
+        } else {
+            // 并行计算所有文件的哈希值
+            items.into_par_iter().filter_map(hash_one).collect()
+        };
 
         // 按哈希值分组
         let mut map: HashMap<String, Vec<Self::Item>> = HashMap::new();
@@ -442,7 +1167,7 @@ pub trait FileFilter: FileGrouper {
     /// 2. 内容重复：文件内容完全相同的文件
     ///
     /// # 参数
-    /// * `regex` - 用于模式匹配的正则表达式
+    /// * `patterns` - 用于模式匹配的规则
     ///
     /// # 返回值
     /// * `HashMap<String, Vec<Self::Item>>` - 重复文件组，键为识别标识，值为重复文件列表
@@ -452,57 +1177,234 @@ pub trait FileFilter: FileGrouper {
     /// - 只对非模式重复文件进行耗时的哈希计算
     /// - 使用并行处理提升性能
     /// - 按大小预过滤减少不必要的计算
-    fn duplicates_by_pattern(self, regex: &Regex) -> HashMap<String, Vec<Self::Item>>
+    fn duplicates_by_pattern(self, patterns: &PatternSet) -> HashMap<String, Vec<Self::Item>>
     where
         Self: Sized,
-        Self::Item: HasSize + Named + Hashed + Send + Clone,
+        Self::Item: HasSize + HasPath + Named + Hashed + Send + Clone,
     {
+        self.classify_duplicates(patterns)
+            .into_iter()
+            .map(|(key, (_, items))| (key, items))
+            .collect()
+    }
+
+    /// 按模式检测重复文件，并标注每组的识别方式
+    ///
+    /// 检测逻辑与 [`FileFilter::duplicates_by_pattern`] 相同，
+    /// 但会保留每个重复组是通过模式还是内容哈希识别出来的。
+    ///
+    /// # 参数
+    /// * `patterns` - 用于模式匹配的规则
+    ///
+    /// # 返回值
+    /// * `HashMap<String, (DuplicateKind, Vec<Self::Item>)>` - 重复文件组及其识别方式
+    fn classify_duplicates(
+        self,
+        patterns: &PatternSet,
+    ) -> HashMap<String, (DuplicateKind, Vec<Self::Item>)>
+    where
+        Self: Sized,
+        Self::Item: HasSize + HasPath + Named + Hashed + Send + Clone,
+    {
+        self.classify_duplicates_with(patterns, &HashContext::new(&RealFs))
+    }
+
+    /// 按上下文中的文件系统后端和哈希算法计算哈希，检测逻辑同 [`FileFilter::classify_duplicates`]
+    ///
+    /// 分组和哈希阶段的进度都会发送到上下文中的进度接收者；取消后只返回已确认的重复组。
+    fn classify_duplicates_with(self, patterns: &PatternSet, ctx: &HashContext) -> HashMap<String, (DuplicateKind, Vec<Self::Item>)>
+    where
+        Self: Sized,
+        Self::Item: HasSize + HasPath + Named + Hashed + Send + Clone,
+    {
+        let progress = ctx.progress;
+        let items: Vec<Self::Item> = self.into_iter().collect();
+        progress.on_event(&ProgressEvent::PhaseStarted { phase: Phase::Group, total: Some(items.len() as u64) });
+
+        // 文件名无法解码的文件不参与模式匹配，直接按内容检测
+        let (items, unnamed): (Vec<_>, Vec<_>) = items.into_iter().partition(|item| item.name().is_some());
+
         // 第一步：按模式分组，分离模式重复和候选文件
-        let (pattern_duplicates, size_candidates): (Vec<_>, Vec<_>) = self
-            .group_by_pattern(regex)
+        let (pattern_duplicates, size_candidates): (Vec<_>, Vec<_>) = items
+            .group_by_pattern(patterns)
             .into_par_iter()
             .partition(|(_, items)| items.len() > 1);
 
         // 初始化结果集合，先加入模式重复文件
-        let mut duplicates: HashMap<String, Vec<Self::Item>> =
-            pattern_duplicates.into_iter().collect();
+        let mut duplicates: HashMap<String, (DuplicateKind, Vec<Self::Item>)> = pattern_duplicates
+            .into_iter()
+            .map(|(key, items)| (key, (DuplicateKind::Pattern, items)))
+            .collect();
+        let pattern_count = duplicates.values().map(|(_, items)| items.len()).sum();
+        progress.on_event(&ProgressEvent::FilesGrouped { count: pattern_count });
 
         // 第二步：对非模式重复文件进行哈希检测
-        if !size_candidates.is_empty() {
+        if size_candidates.is_empty() && unnamed.is_empty() {
+            progress.on_event(&ProgressEvent::PhaseFinished { phase: Phase::Group });
+        } else {
             // 收集所有候选文件
             let candidates: Vec<Self::Item> = size_candidates
                 .into_par_iter()
                 .flat_map(|(_, items)| items)
+                .chain(unnamed)
                 .collect();
+            let candidates_count = candidates.len();
 
             // 按大小分组后再按哈希检测
-            let hash_duplicate: HashMap<String, Vec<Self::Item>> = candidates
+            let same_size = candidates
                 .group_by_size()
                 .into_par_iter()
                 .filter(|(_, item)| item.len() > 1) // 只处理大小相同的文件组
                 .flat_map(|(_, items)| items)
-                .collect::<Vec<_>>()
-                .group_by_hash()
+                .collect::<Vec<_>>();
+            progress.on_event(&ProgressEvent::FilesGrouped { count: candidates_count });
+            progress.on_event(&ProgressEvent::PhaseFinished { phase: Phase::Group });
+
+            let total_bytes = same_size.iter().map(HasSize::size).sum();
+            progress.on_event(&ProgressEvent::PhaseStarted { phase: Phase::Hash, total: Some(total_bytes) });
+            let hash_duplicate = same_size
+                .group_by_hash_using(ctx)
                 .into_iter()
                 .filter(|(_, items)| items.len() > 1) // 只保留真正重复的文件组
-                .collect();
+                .map(|(hash, items)| (hash, (DuplicateKind::Content, items)));
 
             duplicates.extend(hash_duplicate);
+            progress.on_event(&ProgressEvent::PhaseFinished { phase: Phase::Hash });
         }
 
         duplicates
     }
+
+    /// 按派生文件规则拆分文件集合
+    ///
+    /// 在同一目录中按命名约定将缩略图等派生文件与原始文件配对：
+    /// 找到原始文件的派生文件归入 `attached`，原始文件已不存在的归入 `orphaned`，
+    /// 其余文件原样保留在 `others` 中。原始文件按去掉扩展名后的名称匹配，
+    /// 因此 `video_thumb.jpg` 可以与 `video.mp4` 配对。
+    ///
+    /// # 参数
+    /// * `rules` - 派生文件命名规则，通常为 [`DERIVATIVE_RULES`]
+    fn split_derivatives(self, rules: &[DerivativeRule]) -> Derivatives<Self::Item>
+    where
+        Self: Sized,
+        Self::Item: HasPath + Named,
+    {
+        let mut derivatives = Derivatives::default();
+
+        for (_, files) in self.group_by_parent() {
+            let (derived, originals): (Vec<_>, Vec<_>) = files.into_iter().partition(|file| {
+                rules.iter().any(|rule| rule.original_stem(file).is_some())
+            });
+
+            let original_stems: HashSet<String> = originals
+                .iter()
+                .filter_map(|file| file.base_name().map(Cow::into_owned))
+                .collect();
+
+            for file in derived {
+                let has_original = rules
+                    .iter()
+                    .filter_map(|rule| rule.original_stem(&file))
+                    .any(|stem| original_stems.contains(&stem));
+                if has_original {
+                    derivatives.attached.push(file);
+                } else {
+                    derivatives.orphaned.push(file);
+                }
+            }
+            derivatives.others.extend(originals);
+        }
+        derivatives
+    }
+}
+
+/// 派生文件（缩略图等）命名规则
+///
+/// 描述派生文件名相对于原始文件名多出的前缀或后缀，
+/// 只对 `extensions` 中列出的文件类型生效，避免误判普通文件。
+#[derive(Debug, Clone, Copy)]
+pub struct DerivativeRule {
+    /// 规则名称
+    pub name: &'static str,
+    /// 派生文件名的前缀，如 `th_`
+    pub prefix: &'static str,
+    /// 派生文件名（不含扩展名）的后缀，如 `_thumb`
+    pub suffix: &'static str,
+    /// 适用的派生文件扩展名（小写）
+    pub extensions: &'static [&'static str],
 }
 
+/// 微信缩略图常见的扩展名
+const THUMBNAIL_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "heic", "dat"];
+
+/// 微信缓存中常见的派生文件规则
+pub const DERIVATIVE_RULES: &[DerivativeRule] = &[
+    DerivativeRule { name: "thumb 后缀", prefix: "", suffix: "_thumb", extensions: THUMBNAIL_EXTENSIONS },
+    DerivativeRule { name: "t 后缀", prefix: "", suffix: "_t", extensions: THUMBNAIL_EXTENSIONS },
+    DerivativeRule { name: "th 前缀", prefix: "th_", suffix: "", extensions: THUMBNAIL_EXTENSIONS },
+];
+
+impl DerivativeRule {
+    /// 如果文件符合该规则，返回其原始文件的名称（不含扩展名）
+    pub fn original_stem<T: HasPath + Named>(&self, file: &T) -> Option<String> {
+        let extension = file.path().extension()?.to_str()?.to_lowercase();
+        if !self.extensions.contains(&extension.as_str()) {
+            return None;
+        }
+
+        let stem = file.base_name()?;
+        let original = stem.strip_prefix(self.prefix)?.strip_suffix(self.suffix)?;
+        if original.is_empty() || original.len() == stem.len() {
+            None
+        } else {
+            Some(original.to_owned())
+        }
+    }
+}
+
+/// 派生文件拆分结果
+#[derive(Debug)]
+pub struct Derivatives<T> {
+    /// 原始文件已不存在的派生文件
+    pub orphaned: Vec<T>,
+    /// 原始文件仍然存在的派生文件
+    pub attached: Vec<T>,
+    /// 非派生文件
+    pub others: Vec<T>,
+}
+
+impl<T> Default for Derivatives<T> {
+    fn default() -> Self {
+        Derivatives {
+            orphaned: Vec::new(),
+            attached: Vec::new(),
+            others: Vec::new(),
+        }
+    }
+}
+
+/// 重复文件组的识别方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateKind {
+    /// 文件名符合副本模式，未比对内容
+    Pattern,
+    /// 文件内容哈希一致，已验证为重复
+    Content,
+}
+
+/// 文件删除操作
+///
+/// 删除通过 `fs` 进行，传入 [`DryRunFs`](crate::filesystem::DryRunFs) 时不会修改文件系统。
 pub trait FileProcessor {
     type ProcessResult;
-    fn delete(&self) -> Result<Self::ProcessResult>;
+    fn delete(&self, fs: &dyn Fs) -> Result<Self::ProcessResult>;
 }
 
 impl FileProcessor for FileInfo {
     type ProcessResult = bool;
-    fn delete(&self) -> Result<bool> {
-        fs::remove_file(&self.path)
+    fn delete(&self, fs: &dyn Fs) -> Result<bool> {
+        fs.remove_file(&self.path)
             .map(|_| {
                 log::debug!("已删除: {}", self.path.display());
                 true
@@ -518,9 +1420,9 @@ impl FileProcessor for FileInfo {
 impl FileProcessor for Vec<FileInfo> {
     type ProcessResult = Vec<FileInfo>;
     // TODO: 检验是否会因为错误中断
-    fn delete(&self) -> Result<Vec<FileInfo>> {
-        self.into_iter()
-            .filter_map(|f| match f.delete() {
+    fn delete(&self, fs: &dyn Fs) -> Result<Vec<FileInfo>> {
+        self.iter()
+            .filter_map(|f| match f.delete(fs) {
                 Ok(true) => Some(Ok(f.to_owned())),
                 Ok(false) => None,
                 Err(e) => Some(Err(e))
@@ -530,6 +1432,60 @@ impl FileProcessor for Vec<FileInfo> {
 }
 
 
+/// 删除清理后新产生的空目录
+///
+/// 从给定目录开始逐级向上删除空目录，直到遇到非空目录或缓存根目录为止。
+/// 缓存根目录本身以及根目录之外的路径永远不会被删除。
+///
+/// # 参数
+/// * `dirs` - 刚被删除过文件的目录
+/// * `roots` - 缓存根目录列表
+///
+/// # 返回值
+/// * `Vec<PathBuf>` - 实际删除的目录
+pub fn prune_empty_dirs<I>(dirs: I, roots: &[PathBuf]) -> Vec<PathBuf>
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    prune_empty_dirs_with(&RealFs, dirs, roots)
+}
+
+/// 通过指定的文件系统后端删除空目录，规则同 [`prune_empty_dirs`]
+pub fn prune_empty_dirs_with<I>(fs: &dyn Fs, dirs: I, roots: &[PathBuf]) -> Vec<PathBuf>
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    let is_inside_root = |dir: &Path| roots.iter().any(|root| dir != root && dir.starts_with(root));
+
+    // 先处理层级最深的目录，保证父目录在子目录删除后再检查；
+    // 同一层级再按路径排序，使重复的目录相邻以便去重
+    let mut candidates: Vec<PathBuf> = dirs.into_iter().collect();
+    candidates.sort_by(|a, b| b.components().count().cmp(&a.components().count()).then_with(|| a.cmp(b)));
+    candidates.dedup();
+
+    let mut removed = Vec::new();
+    for dir in candidates {
+        let mut current = Some(dir.as_path());
+        while let Some(dir) = current {
+            if !is_inside_root(dir) || !fs.is_empty_dir(dir) {
+                break;
+            }
+            match fs.remove_dir(dir) {
+                Ok(()) => {
+                    log::debug!("已删除空目录: {}", dir.display());
+                    removed.push(dir.to_path_buf());
+                }
+                Err(e) => {
+                    log::warn!("删除空目录失败: {} - {}", dir.display(), e);
+                    break;
+                }
+            }
+            current = dir.parent();
+        }
+    }
+    removed
+}
+
 // Trait 实现
 /// FileFilter trait 为 FileInfo 的实现
 impl FileFilter for Vec<FileInfo> {}
@@ -542,11 +1498,15 @@ impl<T> FileGrouper for Vec<T> {}
 #[cfg(feature = "display")]
 impl crate::display::DisplayValue for FileInfo {
     fn format_display(&self) -> String {
-        format!("{} ({})", 
+        let name = format!("{} ({})", 
                 self.path.file_name()
                     .unwrap_or_default()
                     .to_string_lossy(),
-                crate::display::format_size(self.size))
+                crate::display::format_size(self.size));
+        match self.hard_links.len() {
+            0 => name,
+            n => format!("{} +{} 个硬链接", name, n),
+        }
     }
     
     fn format_display_summary(&self) -> String {
@@ -554,9 +1514,11 @@ impl crate::display::DisplayValue for FileInfo {
     }
     
     fn format_display_details(&self) -> String {
-        format!("{} ({})",
+        let path = format!("{} ({})",
                 self.path.display(),
-                crate::display::format_size(self.size))
+                crate::display::format_size(self.size));
+        // 硬链接随文件一起删除，逐个列出，确认前能看到所有会被删除的路径
+        self.hard_links.iter().fold(path, |text, link| format!("{}，硬链接 {}", text, link.display()))
     }
 }
 
@@ -589,10 +1551,9 @@ impl WechatCacheResolver {
         // 尝试不同平台的微信路径
         let search_paths = Self::get_platform_paths(&home);
 
-        for base_path in search_paths {
-            return Self::scan_wechat_directory(&base_path);
-        }
-        None
+        search_paths
+            .iter()
+            .find_map(|base_path| Self::scan_wechat_directory(base_path))
     }
 
     /// 获取平台特定的微信路径
@@ -677,14 +1638,14 @@ impl WechatCacheResolver {
                 let path = entry.path();
                 if path.is_dir() {
                     // 检查是否为微信用户目录（以 wxid_ 开头或包含微信特征）
-                    if let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) {
-                        if dir_name.starts_with("wxid_") || dir_name.contains("WeChat") {
-                            // 在用户目录中查找缓存子目录
-                            for subdir in &cache_subdirs {
-                                let cache_path = path.join(subdir);
-                                if cache_path.exists() {
-                                    return Some(cache_path);
-                                }
+                    if let Some(dir_name) = path.file_name().and_then(|n| n.to_str())
+                        && (dir_name.starts_with("wxid_") || dir_name.contains("WeChat"))
+                    {
+                        // 在用户目录中查找缓存子目录
+                        for subdir in &cache_subdirs {
+                            let cache_path = path.join(subdir);
+                            if cache_path.exists() {
+                                return Some(cache_path);
                             }
                         }
                     }
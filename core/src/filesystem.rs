@@ -0,0 +1,710 @@
+//! 文件系统后端模块
+//!
+//! 扫描（遍历目录、读取元数据、计算哈希）和清理（校验、删除文件和空目录）
+//! 对文件系统的访问都通过 [`Fs`] 完成：
+//! - [`RealFs`]：直接操作真实文件系统
+//! - [`DryRunFs`]：读取真实文件系统，但只记录删除操作而不执行，用于 `clean --dry-run`
+//! - [`MemoryFs`]：完全在内存中的文件树，可以控制修改时间并注入 I/O 错误，用于测试
+//!
+//! 遍历目录时对符号链接和挂载点的处理由 [`WalkOptions`] 决定，默认跳过符号链接、不跨越文件系统边界。
+
+use crate::errors::{Error, Result};
+use crate::hasher::ContentHasher;
+use crate::reader::{self, SMALL_BUFFER};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use serde::{Deserialize, Serialize};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// 文件元数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMeta {
+    pub size: u64,
+    /// 最后修改时间（Unix 时间戳，秒）
+    pub modified: u64,
+    /// 设备号和 inode，平台不支持时为 `None`
+    pub id: Option<FileId>,
+    /// 硬链接数，平台不支持时为 1
+    pub links: u64,
+    /// 实际分配的磁盘空间（字节），平台不支持时为 `None`
+    ///
+    /// 稀疏文件和压缩文件系统上的文件可能小于 `size`，按块分配的小文件通常大于 `size`。
+    pub allocated: Option<u64>,
+}
+
+/// 文件在文件系统中的唯一标识
+///
+/// 指向同一 inode 的硬链接（以及跟随后的符号链接）具有相同的标识。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct FileId {
+    pub device: u64,
+    pub inode: u64,
+}
+
+/// 符号链接的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkPolicy {
+    /// 忽略符号链接
+    #[default]
+    Skip,
+    /// 跟随符号链接扫描其指向的文件和目录
+    Follow,
+    /// 不跟随，但在扫描结果中列出
+    Report,
+}
+
+impl SymlinkPolicy {
+    /// 配置文件和命令行中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymlinkPolicy::Skip => "skip",
+            SymlinkPolicy::Follow => "follow",
+            SymlinkPolicy::Report => "report",
+        }
+    }
+}
+
+impl std::str::FromStr for SymlinkPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(SymlinkPolicy::Skip),
+            "follow" => Ok(SymlinkPolicy::Follow),
+            "report" => Ok(SymlinkPolicy::Report),
+            _ => Err(Error::InvalidOperation(format!("不支持的符号链接处理方式: {}", s))),
+        }
+    }
+}
+
+/// 遍历目录的选项
+#[derive(Debug, Clone, Copy)]
+pub struct WalkOptions {
+    pub symlinks: SymlinkPolicy,
+    /// 不进入与根目录不在同一文件系统上的目录（挂载点）
+    pub one_file_system: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        WalkOptions { symlinks: SymlinkPolicy::default(), one_file_system: true }
+    }
+}
+
+/// 遍历目录的结果
+#[derive(Debug, Default)]
+pub struct WalkResult {
+    /// 需要扫描的文件
+    pub files: Vec<PathBuf>,
+    /// 遇到的符号链接
+    ///
+    /// `Report` 模式下为所有未跟随的符号链接；`Follow` 模式下为已跟随、
+    /// 同时出现在 `files` 中的文件符号链接；`Skip` 模式下为空。
+    pub symlinks: Vec<PathBuf>,
+}
+
+/// 文件系统后端
+pub trait Fs: Send + Sync {
+    /// 递归列出目录下的所有文件，跳过隐藏文件和隐藏目录（以 `.` 开头）
+    fn walk(&self, root: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// 按选项递归列出目录下的文件和符号链接，隐藏文件的规则同 [`Fs::walk`]
+    ///
+    /// 默认忽略选项，等同于 [`Fs::walk`]，适用于没有符号链接的后端。
+    fn walk_with(&self, root: &Path, _options: &WalkOptions) -> io::Result<WalkResult> {
+        Ok(WalkResult { files: self.walk(root)?, symlinks: Vec::new() })
+    }
+
+    /// 判断路径是否为目录
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// 读取文件元数据
+    fn metadata(&self, path: &Path) -> io::Result<FileMeta>;
+
+    /// 打开文件读取内容
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>>;
+
+    /// 读取文件全部内容并计算摘要
+    ///
+    /// `size` 是扫描时记录的文件大小，后端可以据此选择读取方式；
+    /// 默认通过 [`Fs::open`] 以 64 KiB 缓冲区读取。
+    fn hash_file(&self, path: &Path, _size: u64, hasher: &dyn ContentHasher) -> io::Result<String> {
+        hasher.hash_reader(&mut BufReader::with_capacity(SMALL_BUFFER, self.open(path)?))
+    }
+
+    /// 删除文件
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// 删除空目录
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// 判断目录是否为空，目录不可读时视为非空
+    fn is_empty_dir(&self, path: &Path) -> bool;
+
+    /// 是否只模拟修改操作
+    fn is_simulated(&self) -> bool {
+        false
+    }
+
+    /// 路径所在设备的编号，与 [`FileMeta::id`] 中的设备号一致
+    ///
+    /// 无法确定时返回 `None`。
+    fn device(&self, _path: &Path) -> Option<u64> {
+        None
+    }
+
+    /// 路径所在文件系统的可用空间（字节），用于比较清理前后的变化
+    ///
+    /// 无法测量时返回 `None`。
+    fn available_space(&self, _path: &Path) -> Option<u64> {
+        None
+    }
+
+    /// 文件在磁盘上的大致位置，用于机械硬盘模式下按磁盘顺序读取
+    ///
+    /// 无法确定时返回 `None`，调用方退回到按路径排序。
+    fn disk_order(&self, _path: &Path) -> Option<u64> {
+        None
+    }
+}
+
+/// 真实文件系统
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+/// 文件名是否以 `.` 开头
+fn is_hidden_name(name: &std::ffi::OsStr) -> bool {
+    name.to_str().is_some_and(|s| s.starts_with('.'))
+}
+
+/// 遍历到的文件是否经由符号链接到达：文件本身或上级目录是链接时，真实路径与遍历路径不一致
+///
+/// 无法解析真实路径时按链接处理，交由调用方按扫描范围外的链接列出。
+fn reached_through_link(root: &Path, canonical_root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else { return true };
+    fs::canonicalize(path).map_or(true, |real| real != canonical_root.join(relative))
+}
+
+impl Fs for RealFs {
+    fn walk(&self, root: &Path) -> io::Result<Vec<PathBuf>> {
+        Ok(self.walk_with(root, &WalkOptions::default())?.files)
+    }
+
+    /// 跟随符号链接时由 WalkDir 检测循环链接，形成循环的目录被跳过
+    fn walk_with(&self, root: &Path, options: &WalkOptions) -> io::Result<WalkResult> {
+        if !root.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, root.display().to_string()));
+        }
+        let follow = options.symlinks == SymlinkPolicy::Follow;
+        let canonical_root = if follow { Some(fs::canonicalize(root)?) } else { None };
+        let mut result = WalkResult::default();
+        let entries = WalkDir::new(root)
+            .follow_links(follow)
+            .same_file_system(options.one_file_system)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !is_hidden_name(e.file_name()))
+            .filter_map(|e| e.ok());
+        for entry in entries {
+            let file_type = entry.file_type();
+            if file_type.is_file() {
+                // 经由符号链接（包括链接到的目录）到达的文件，真实路径与遍历路径不一致
+                if let Some(canonical_root) = &canonical_root
+                    && reached_through_link(root, canonical_root, entry.path())
+                {
+                    result.symlinks.push(entry.path().to_path_buf());
+                }
+                result.files.push(entry.into_path());
+            } else if file_type.is_symlink() && options.symlinks == SymlinkPolicy::Report {
+                result.symlinks.push(entry.into_path());
+            }
+        }
+        Ok(result)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMeta> {
+        let metadata = fs::metadata(path)?;
+        if !metadata.is_file() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} 不是文件", path.display())));
+        }
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_err(io::Error::other)?
+            .as_secs();
+        #[cfg(unix)]
+        let (id, links, allocated) = {
+            use std::os::unix::fs::MetadataExt;
+            // st_blocks 总是以 512 字节为单位，与文件系统的块大小无关
+            let id = FileId { device: metadata.dev(), inode: metadata.ino() };
+            (Some(id), metadata.nlink(), Some(metadata.blocks() * 512))
+        };
+        #[cfg(not(unix))]
+        let (id, links, allocated) = (None, 1, None);
+        Ok(FileMeta { size: metadata.len(), modified, id, links, allocated })
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(fs::File::open(path)?))
+    }
+
+    /// 按文件大小选择缓冲区大小读取，见 [`reader`] 模块
+    fn hash_file(&self, path: &Path, size: u64, hasher: &dyn ContentHasher) -> io::Result<String> {
+        reader::hash_file(path, size, hasher)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(path)
+    }
+
+    fn is_empty_dir(&self, path: &Path) -> bool {
+        fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_none())
+    }
+
+    #[cfg(unix)]
+    fn device(&self, path: &Path) -> Option<u64> {
+        use std::os::unix::fs::MetadataExt;
+        fs::metadata(path).ok().map(|metadata| metadata.dev())
+    }
+
+    /// Unix 上通过 `statvfs` 读取非特权用户可用的空间
+    #[cfg(unix)]
+    fn available_space(&self, path: &Path) -> Option<u64> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: path 是以 NUL 结尾的字符串，stat 在调用成功后才被读取
+        let stat = unsafe {
+            if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+                return None;
+            }
+            stat.assume_init()
+        };
+        // 字段类型因平台而异
+        #[allow(clippy::unnecessary_cast)]
+        let available = stat.f_bavail as u64 * stat.f_frsize as u64;
+        Some(available)
+    }
+
+    /// Unix 上使用 inode 编号：常见文件系统按分配顺序编号，与数据块的位置大致对应
+    #[cfg(unix)]
+    fn disk_order(&self, path: &Path) -> Option<u64> {
+        use std::os::unix::fs::MetadataExt;
+        fs::metadata(path).ok().map(|metadata| metadata.ino())
+    }
+}
+
+/// 模拟运行的文件系统
+///
+/// 读取操作访问真实文件系统；删除操作只被记录，之后的读取会把已记录的路径视为不存在，
+/// 因此模拟结果（包括空目录清理）与真实执行一致。模拟删除不改变可用空间，因此不测量可用空间。
+#[derive(Debug, Default)]
+pub struct DryRunFs {
+    removed: Mutex<HashSet<PathBuf>>,
+}
+
+impl DryRunFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 模拟删除的所有路径
+    pub fn removed(&self) -> Vec<PathBuf> {
+        let mut removed: Vec<PathBuf> = self.lock().iter().cloned().collect();
+        removed.sort();
+        removed
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashSet<PathBuf>> {
+        self.removed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_removed(&self, path: &Path) -> bool {
+        self.lock().contains(path)
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, format!("{} 已在模拟中删除", path.display()))
+    }
+}
+
+impl Fs for DryRunFs {
+    fn walk(&self, root: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut files = RealFs.walk(root)?;
+        let removed = self.lock();
+        files.retain(|path| !removed.contains(path));
+        Ok(files)
+    }
+
+    fn walk_with(&self, root: &Path, options: &WalkOptions) -> io::Result<WalkResult> {
+        let mut result = RealFs.walk_with(root, options)?;
+        let removed = self.lock();
+        result.files.retain(|path| !removed.contains(path));
+        result.symlinks.retain(|path| !removed.contains(path));
+        Ok(result)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        !self.is_removed(path) && path.is_dir()
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMeta> {
+        if self.is_removed(path) {
+            return Err(Self::not_found(path));
+        }
+        RealFs.metadata(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        if self.is_removed(path) {
+            return Err(Self::not_found(path));
+        }
+        RealFs.open(path)
+    }
+
+    fn hash_file(&self, path: &Path, size: u64, hasher: &dyn ContentHasher) -> io::Result<String> {
+        if self.is_removed(path) {
+            return Err(Self::not_found(path));
+        }
+        RealFs.hash_file(path, size, hasher)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        if self.is_removed(path) || !path.is_file() {
+            return Err(Self::not_found(path));
+        }
+        self.lock().insert(path.to_path_buf());
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        if !self.is_empty_dir(path) {
+            return Err(io::Error::new(io::ErrorKind::DirectoryNotEmpty, path.display().to_string()));
+        }
+        self.lock().insert(path.to_path_buf());
+        Ok(())
+    }
+
+    fn is_empty_dir(&self, path: &Path) -> bool {
+        if self.is_removed(path) {
+            return false;
+        }
+        let Ok(entries) = fs::read_dir(path) else {
+            return false;
+        };
+        let removed = self.lock();
+        entries
+            .filter_map(|entry| entry.ok())
+            .all(|entry| removed.contains(&entry.path()))
+    }
+
+    fn is_simulated(&self) -> bool {
+        true
+    }
+
+    fn device(&self, path: &Path) -> Option<u64> {
+        RealFs.device(path)
+    }
+
+    fn disk_order(&self, path: &Path) -> Option<u64> {
+        RealFs.disk_order(path)
+    }
+}
+
+/// 内存中的文件
+#[derive(Debug, Clone)]
+struct MemoryFile {
+    content: Vec<u8>,
+    modified: u64,
+    inode: u64,
+    allocated: u64,
+}
+
+#[derive(Debug, Default)]
+struct MemoryTree {
+    files: BTreeMap<PathBuf, MemoryFile>,
+    dirs: BTreeSet<PathBuf>,
+    /// 访问时返回错误的路径
+    errors: HashMap<PathBuf, io::ErrorKind>,
+    /// 每个 inode 的硬链接数
+    links: HashMap<u64, u64>,
+    next_inode: u64,
+    /// 文件系统总容量，未设置时不报告可用空间
+    capacity: Option<u64>,
+    /// 挂载点及其设备号
+    mounts: BTreeMap<PathBuf, u64>,
+}
+
+impl MemoryTree {
+    fn check(&self, path: &Path) -> io::Result<()> {
+        match self.errors.get(path) {
+            Some(kind) => Err(io::Error::new(*kind, format!("注入的错误: {}", path.display()))),
+            None => Ok(()),
+        }
+    }
+
+    fn has_children(&self, dir: &Path) -> bool {
+        self.files.keys().chain(self.dirs.iter()).any(|p| p.parent() == Some(dir))
+    }
+
+    /// 插入文件（自动创建父目录），替换同名文件时减少原 inode 的链接数
+    fn insert(&mut self, path: &Path, file: MemoryFile) {
+        if let Some(parent) = path.parent() {
+            self.dirs.extend(parent.ancestors().map(Path::to_path_buf));
+        }
+        *self.links.entry(file.inode).or_default() += 1;
+        if let Some(old) = self.files.insert(path.to_path_buf(), file) {
+            self.unlink(old.inode);
+        }
+    }
+
+    /// 路径所在的设备：最近的上级挂载点的设备号
+    fn device_of(&self, path: &Path) -> u64 {
+        path.ancestors()
+            .find_map(|dir| self.mounts.get(dir).copied())
+            .unwrap_or(MEMORY_DEVICE)
+    }
+
+    fn unlink(&mut self, inode: u64) {
+        if let Some(count) = self.links.get_mut(&inode) {
+            *count -= 1;
+            if *count == 0 {
+                self.links.remove(&inode);
+            }
+        }
+    }
+}
+
+/// 内存文件系统中所有文件所在的设备号
+const MEMORY_DEVICE: u64 = 1;
+
+/// 内存文件系统的块大小，文件默认按整块分配
+const MEMORY_BLOCK_SIZE: u64 = 4096;
+
+/// 内存文件系统
+///
+/// 用合成的目录树测试扫描、预览和清理逻辑：文件内容和修改时间完全可控，
+/// 还可以通过 [`MemoryFs::inject_error`] 让指定路径的访问失败。
+///
+/// ```
+/// use std::path::Path;
+/// use wechat_cleaner::filesystem::{Fs, MemoryFs};
+///
+/// let fs = MemoryFs::new();
+/// fs.add_file("/cache/2024-01/photo.jpg", b"photo", 1704067200);
+/// assert!(fs.is_dir(Path::new("/cache/2024-01")));
+/// assert_eq!(fs.metadata(Path::new("/cache/2024-01/photo.jpg")).unwrap().size, 5);
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    tree: Mutex<MemoryTree>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加文件（自动创建父目录），已存在的文件会被覆盖
+    pub fn add_file(&self, path: impl AsRef<Path>, content: impl Into<Vec<u8>>, modified: u64) -> &Self {
+        let mut tree = self.lock();
+        tree.next_inode += 1;
+        let inode = tree.next_inode;
+        let content = content.into();
+        let allocated = (content.len() as u64).div_ceil(MEMORY_BLOCK_SIZE) * MEMORY_BLOCK_SIZE;
+        tree.insert(path.as_ref(), MemoryFile {
+            content,
+            modified,
+            inode,
+            allocated,
+        });
+        self
+    }
+
+    /// 设置文件实际分配的磁盘空间，模拟稀疏文件或压缩文件系统
+    ///
+    /// 同一文件的所有硬链接一起修改；文件不存在时不做任何修改。
+    pub fn set_allocated(&self, path: impl AsRef<Path>, allocated: u64) -> &Self {
+        let mut tree = self.lock();
+        if let Some(inode) = tree.files.get(path.as_ref()).map(|file| file.inode) {
+            tree.files
+                .values_mut()
+                .filter(|file| file.inode == inode)
+                .for_each(|file| file.allocated = allocated);
+        }
+        self
+    }
+
+    /// 把目录（包括所有上级目录）标记为另一个设备的挂载点，其下的文件属于该设备
+    pub fn mount(&self, path: impl AsRef<Path>, device: u64) -> &Self {
+        let mut tree = self.lock();
+        let path = path.as_ref();
+        tree.dirs.extend(path.ancestors().map(Path::to_path_buf));
+        tree.mounts.insert(path.to_path_buf(), device);
+        self
+    }
+
+    /// 设置文件系统总容量，之后可用空间为容量减去所有文件分配的空间
+    pub fn set_capacity(&self, capacity: u64) -> &Self {
+        self.lock().capacity = Some(capacity);
+        self
+    }
+
+    /// 为已存在的文件添加硬链接（自动创建父目录）
+    ///
+    /// 两个路径共享同一 inode，元数据中的硬链接数随之增加；
+    /// `existing` 不存在时不做任何修改。
+    pub fn add_hard_link(&self, existing: impl AsRef<Path>, link: impl AsRef<Path>) -> &Self {
+        let mut tree = self.lock();
+        if let Some(file) = tree.files.get(existing.as_ref()).cloned() {
+            tree.insert(link.as_ref(), file);
+        }
+        self
+    }
+
+    /// 添加目录（包括所有上级目录）
+    pub fn add_dir(&self, path: impl AsRef<Path>) -> &Self {
+        self.lock().dirs.extend(path.as_ref().ancestors().map(Path::to_path_buf));
+        self
+    }
+
+    /// 之后对该路径的读取和删除都返回指定类型的错误
+    pub fn inject_error(&self, path: impl AsRef<Path>, kind: io::ErrorKind) -> &Self {
+        self.lock().errors.insert(path.as_ref().to_path_buf(), kind);
+        self
+    }
+
+    /// 路径是否存在（文件或目录）
+    pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+        let tree = self.lock();
+        let path = path.as_ref();
+        tree.files.contains_key(path) || tree.dirs.contains(path)
+    }
+
+    /// 所有文件路径，按路径排序
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.lock().files.keys().cloned().collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MemoryTree> {
+        self.tree.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Fs for MemoryFs {
+    fn walk(&self, root: &Path) -> io::Result<Vec<PathBuf>> {
+        let tree = self.lock();
+        if !tree.dirs.contains(root) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, root.display().to_string()));
+        }
+        Ok(tree
+            .files
+            .keys()
+            .filter(|path| {
+                path.strip_prefix(root)
+                    .is_ok_and(|relative| !relative.iter().any(is_hidden_name))
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn walk_with(&self, root: &Path, options: &WalkOptions) -> io::Result<WalkResult> {
+        let mut files = self.walk(root)?;
+        if options.one_file_system {
+            let tree = self.lock();
+            let device = tree.device_of(root);
+            files.retain(|path| tree.device_of(path) == device);
+        }
+        Ok(WalkResult { files, symlinks: Vec::new() })
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.lock().dirs.contains(path)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMeta> {
+        let tree = self.lock();
+        tree.check(path)?;
+        let file = tree
+            .files
+            .get(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))?;
+        Ok(FileMeta {
+            size: file.content.len() as u64,
+            modified: file.modified,
+            id: Some(FileId { device: tree.device_of(path), inode: file.inode }),
+            links: tree.links.get(&file.inode).copied().unwrap_or(1),
+            allocated: Some(file.allocated),
+        })
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        let tree = self.lock();
+        tree.check(path)?;
+        let file = tree
+            .files
+            .get(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))?;
+        Ok(Box::new(io::Cursor::new(file.content.clone())))
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut tree = self.lock();
+        tree.check(path)?;
+        let file = tree
+            .files
+            .remove(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))?;
+        tree.unlink(file.inode);
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let mut tree = self.lock();
+        tree.check(path)?;
+        if !tree.dirs.contains(path) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, path.display().to_string()));
+        }
+        if tree.has_children(path) {
+            return Err(io::Error::new(io::ErrorKind::DirectoryNotEmpty, path.display().to_string()));
+        }
+        tree.dirs.remove(path);
+        Ok(())
+    }
+
+    fn is_empty_dir(&self, path: &Path) -> bool {
+        let tree = self.lock();
+        tree.dirs.contains(path) && !tree.has_children(path)
+    }
+
+    fn device(&self, path: &Path) -> Option<u64> {
+        let tree = self.lock();
+        (tree.dirs.contains(path) || tree.files.contains_key(path)).then(|| tree.device_of(path))
+    }
+
+    /// 硬链接共享的空间只计算一次
+    fn available_space(&self, _path: &Path) -> Option<u64> {
+        let tree = self.lock();
+        let mut seen = HashSet::new();
+        let used: u64 = tree
+            .files
+            .values()
+            .filter(|file| seen.insert(file.inode))
+            .map(|file| file.allocated)
+            .sum();
+        tree.capacity.map(|capacity| capacity.saturating_sub(used))
+    }
+}
@@ -0,0 +1,54 @@
+//! 文件内容哈希模块
+//!
+//! 内容重复检测通过 [`ContentHasher`] 计算文件摘要，默认使用 [`Md5Hasher`]。
+//! 嵌入核心库的调用方可以通过 `ScannerBuilder::hasher` 替换为其他算法。
+
+use std::io::{self, Read};
+
+/// 文件内容哈希算法
+pub trait ContentHasher: Send + Sync {
+    /// 算法名称，参与扫描设置指纹的计算
+    fn name(&self) -> &str;
+
+    /// 读取全部内容并返回十六进制摘要
+    fn hash_reader(&self, reader: &mut dyn Read) -> io::Result<String>;
+
+    /// 计算内存中内容（如内存映射的大文件）的摘要
+    ///
+    /// 默认通过 [`ContentHasher::hash_reader`] 读取，算法可以覆盖以直接处理整块内容。
+    fn hash_bytes(&self, bytes: &[u8]) -> io::Result<String> {
+        self.hash_reader(&mut &*bytes)
+    }
+}
+
+/// MD5 哈希（默认算法）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Md5Hasher;
+
+impl ContentHasher for Md5Hasher {
+    fn name(&self) -> &str {
+        "md5"
+    }
+
+    fn hash_reader(&self, reader: &mut dyn Read) -> io::Result<String> {
+        use md5::{Digest, Md5};
+
+        let mut hasher = Md5::new();
+        let mut buffer = vec![0u8; 32768];
+
+        // 流式读取文件内容并计算哈希
+        loop {
+            match reader.read(&mut buffer)? {
+                0 => break, // EOF
+                n => hasher.update(&buffer[..n]),
+            }
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    fn hash_bytes(&self, bytes: &[u8]) -> io::Result<String> {
+        use md5::{Digest, Md5};
+
+        Ok(format!("{:x}", Md5::digest(bytes)))
+    }
+}
@@ -0,0 +1,318 @@
+//! 扫描历史模块
+//!
+//! 每次扫描的结果以编号命名保存在 `save_path/wechat-cleaner/scans/` 下，
+//! 同目录的 `index.json` 记录各次扫描的摘要，列出历史时无需加载扫描结果本身。
+//! 清理结果（包括被取消的部分清理）保存在 `scans/cleanings/<编号>.json`。
+//!
+//! 扫描编号由扫描时间生成（如 `20240101-120000`），按字典序排列即为时间顺序；
+//! 未指定编号时使用最新的一次扫描。
+
+use crate::cleaner::CleaningResult;
+use crate::config::settings::ScannerSettings;
+use crate::errors::{Error, Result};
+use crate::scanner::ScanResult;
+use crate::storage::{self, StorageFormat};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 扫描历史索引文件名
+const INDEX_FILE: &str = "index.json";
+
+/// 清理记录子目录
+const CLEANINGS_DIR: &str = "cleanings";
+
+/// 引入扫描历史之前使用的单一扫描结果文件名
+const LEGACY_FILES: &[&str] = &["scan-result.json", "scan-result.bin"];
+
+/// 无法导入的旧扫描结果改名时追加的扩展名
+const UNREADABLE_SUFFIX: &str = ".unreadable";
+
+/// 当前 Unix 时间戳（秒）
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 由扫描时间生成扫描编号，格式为 `YYYYMMDD-HHMMSS`（UTC）
+pub fn scan_id(created_at: u64) -> String {
+    let rfc3339 = format_timestamp(created_at);
+    let digits: String = rfc3339.chars().filter(char::is_ascii_digit).collect();
+    format!("{}-{}", &digits[..8], &digits[8..14])
+}
+
+/// 将 Unix 时间戳格式化为 RFC 3339 字符串（UTC）
+pub fn format_timestamp(secs: u64) -> String {
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(secs)).to_string()
+}
+
+/// 扫描历史中的一条记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanEntry {
+    /// 扫描编号
+    pub id: String,
+    /// 扫描时间（Unix 时间戳，秒）
+    pub created_at: u64,
+    /// 扫描结果文件名（相对于历史目录）
+    pub file: String,
+    pub total_files_count: usize,
+    pub duplicate_count: usize,
+    /// 是否已经用于清理（清理后扫描结果中的文件多已不存在）
+    #[serde(default)]
+    pub cleaned: bool,
+    /// 扫描是否被取消而只包含部分结果
+    #[serde(default)]
+    pub interrupted: bool,
+}
+
+impl ScanEntry {
+    fn from_result(result: &ScanResult) -> Self {
+        ScanEntry {
+            id: result.id.clone(),
+            created_at: result.created_at,
+            file: result
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            total_files_count: result.total_files_count,
+            duplicate_count: result.duplicate_count,
+            cleaned: false,
+            interrupted: result.interrupted,
+        }
+    }
+}
+
+/// 扫描历史
+pub struct ScanHistory {
+    dir: PathBuf,
+    /// 按时间升序排列（即按时间从旧到新）
+    entries: Vec<ScanEntry>,
+}
+
+impl ScanHistory {
+    /// 打开扫描历史
+    ///
+    /// 旧版本的单一扫描结果文件会被导入历史；索引文件丢失时根据目录中的扫描结果重建。
+    pub fn open(settings: &ScannerSettings) -> Result<Self> {
+        let dir = settings.history_dir();
+        let index = dir.join(INDEX_FILE);
+
+        let entries = if index.exists() {
+            serde_json::from_str(&fs::read_to_string(&index)?)?
+        } else {
+            Self::rebuild_index(&dir)?
+        };
+
+        let mut history = ScanHistory { dir, entries };
+        history.sort();
+        history.import_legacy(settings)?;
+        Ok(history)
+    }
+
+    /// 所有扫描记录，从旧到新
+    pub fn entries(&self) -> &[ScanEntry] {
+        &self.entries
+    }
+
+    /// 最新的扫描记录
+    pub fn latest(&self) -> Option<&ScanEntry> {
+        self.entries.last()
+    }
+
+    /// 按编号查找扫描记录，支持唯一前缀
+    pub fn find(&self, id: &str) -> Result<&ScanEntry> {
+        if let Some(entry) = self.entries.iter().find(|e| e.id == id) {
+            return Ok(entry);
+        }
+        let mut matches = self.entries.iter().filter(|e| e.id.starts_with(id));
+        match (matches.next(), matches.next()) {
+            (Some(entry), None) => Ok(entry),
+            (Some(_), Some(_)) => Err(Error::InvalidOperation(format!("扫描编号 {} 不唯一，请输入更长的编号", id))),
+            (None, _) => Err(Error::ScanNotFound(id.to_string())),
+        }
+    }
+
+    /// 按编号查找扫描记录，未指定编号时返回最新的一次
+    pub fn resolve(&self, id: Option<&str>) -> Result<&ScanEntry> {
+        match id {
+            Some(id) => self.find(id),
+            None => self
+                .latest()
+                .ok_or_else(|| Error::FileProcessing("未找到扫描结果文件，请先执行扫描命令".to_string())),
+        }
+    }
+
+    /// 扫描结果文件路径
+    pub fn path_of(&self, entry: &ScanEntry) -> PathBuf {
+        self.dir.join(&entry.file)
+    }
+
+    /// 加载扫描结果
+    pub fn load(&self, id: &str) -> Result<ScanResult> {
+        ScanResult::load(&self.path_of(self.find(id)?))
+    }
+
+    /// 保存扫描结果并记录到历史
+    ///
+    /// 与已有记录编号冲突时（同一秒内多次扫描）会追加序号，并相应更新保存路径。
+    pub fn record(&mut self, result: &mut ScanResult) -> Result<()> {
+        if self.entries.iter().any(|e| e.id == result.id) {
+            let base = result.id.clone();
+            let id = (2..)
+                .map(|n| format!("{}-{}", base, n))
+                .find(|id| self.entries.iter().all(|e| &e.id != id))
+                .unwrap_or(base);
+            let extension = result.path.extension().map(|e| e.to_os_string()).unwrap_or_default();
+            result.path = self.dir.join(&id).with_extension(extension);
+            result.id = id;
+        }
+
+        result.save()?;
+        self.entries.push(ScanEntry::from_result(result));
+        self.sort();
+        self.save_index()
+    }
+
+    /// 将扫描记录标记为已清理
+    pub fn mark_cleaned(&mut self, id: &str) -> Result<()> {
+        let id = self.find(id)?.id.clone();
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) {
+            entry.cleaned = true;
+        }
+        self.save_index()
+    }
+
+    /// 清理记录文件路径
+    pub fn cleaning_log_path(&self, id: &str) -> PathBuf {
+        self.dir.join(CLEANINGS_DIR).join(id).with_extension("json")
+    }
+
+    /// 保存一次清理的结果，记录实际删除、跳过和失败的文件
+    ///
+    /// 同一扫描多次清理时（如上次清理被取消）覆盖之前的记录。
+    pub fn save_cleaning(&self, id: &str, result: &CleaningResult) -> Result<PathBuf> {
+        let id = &self.find(id)?.id;
+        let path = self.cleaning_log_path(id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(result)?)?;
+        Ok(path)
+    }
+
+    /// 删除扫描记录及其扫描结果文件
+    pub fn remove(&mut self, id: &str) -> Result<ScanEntry> {
+        let id = self.find(id)?.id.clone();
+        let position = self.entries.iter().position(|e| e.id == id).expect("记录已找到");
+        let entry = self.entries.remove(position);
+
+        for path in [self.path_of(&entry), self.cleaning_log_path(&entry.id)] {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+        self.save_index()?;
+        Ok(entry)
+    }
+
+    fn sort(&mut self) {
+        self.entries.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+    }
+
+    fn save_index(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        fs::write(self.dir.join(INDEX_FILE), json)?;
+        Ok(())
+    }
+
+    /// 读取目录中每个扫描结果的头部重建索引
+    fn rebuild_index(dir: &Path) -> Result<Vec<ScanEntry>> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(dir)? {
+            let path = dir_entry?.path();
+            let is_scan = path
+                .extension()
+                .is_some_and(|ext| ext == StorageFormat::Json.extension() || ext == StorageFormat::Binary.extension());
+            if !is_scan || path.file_name().is_some_and(|n| n == INDEX_FILE) {
+                continue;
+            }
+
+            match load_header(&path) {
+                Ok(result) => entries.push(ScanEntry::from_result(&result)),
+                Err(e) => log::warn!("跳过无法读取的扫描结果 {}: {}", path.display(), e),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// 将旧版本的 `wechat-cleaner/scan-result.*` 移入扫描历史
+    ///
+    /// 无法读取的文件（损坏、截断或来自更新版本的工具）不会阻止打开扫描历史，
+    /// 而是记录警告并改名为 `scan-result.*.unreadable`，以后不再尝试导入。
+    fn import_legacy(&mut self, settings: &ScannerSettings) -> Result<()> {
+        let Some(parent) = self.dir.parent().map(Path::to_path_buf) else {
+            return Ok(());
+        };
+
+        for name in LEGACY_FILES {
+            let legacy_path = parent.join(name);
+            if !legacy_path.exists() {
+                continue;
+            }
+
+            let mut result = match ScanResult::load(&legacy_path) {
+                Ok(result) => result,
+                Err(e) => {
+                    set_aside(&legacy_path, &e);
+                    continue;
+                }
+            };
+            if result.created_at == 0 {
+                result.created_at = fs::metadata(&legacy_path)?
+                    .modified()?
+                    .duration_since(UNIX_EPOCH)?
+                    .as_secs();
+            }
+            result.id = scan_id(result.created_at);
+            result.path = settings.scan_path(&result.id);
+            self.record(&mut result)?;
+            fs::remove_file(&legacy_path)?;
+            log::info!("已将旧的扫描结果导入扫描历史: {}", result.id);
+        }
+        Ok(())
+    }
+}
+
+/// 将无法导入的旧扫描结果改名，保留文件以便排查
+fn set_aside(path: &Path, error: &Error) {
+    let mut unreadable = path.as_os_str().to_owned();
+    unreadable.push(UNREADABLE_SUFFIX);
+    match fs::rename(path, &unreadable) {
+        Ok(()) => log::warn!(
+            "无法导入旧的扫描结果 {}: {}，已改名为 {}",
+            path.display(),
+            error,
+            Path::new(&unreadable).display()
+        ),
+        Err(e) => log::warn!("无法导入旧的扫描结果 {}: {}（改名失败: {}）", path.display(), error, e),
+    }
+}
+
+/// 读取扫描结果头部，二进制格式不加载重复组
+fn load_header(path: &Path) -> Result<ScanResult> {
+    let mut result = match StorageFormat::detect(path)? {
+        StorageFormat::Binary => storage::open_binary(path)?.0,
+        StorageFormat::Json => ScanResult::load(&path.to_path_buf())?,
+    };
+    result.path = path.to_path_buf();
+    Ok(result)
+}
@@ -1,11 +1,57 @@
+//! 微信缓存清理工具核心库
+//!
+//! 命令行工具之外，核心库也可以直接嵌入其他程序使用，不依赖配置文件：
+//! - [`Scanner`]（[`scanner::FileScanner`]）：通过 [`Scanner::builder`] 指定缓存根目录、
+//!   文件名模式、哈希算法（[`ContentHasher`]）和文件系统后端（[`Fs`]）后扫描
+//! - [`ScanResult`]：扫描结果，字段均为公开字段，可以序列化保存或直接构造
+//! - [`FileInfo`]：文件路径、大小和修改时间，通过 [`FileInfo::new`] 或 [`FileInfo::from_path`] 创建
+//! - [`cleaner::CleaningPreview`]：根据扫描结果生成清理预览并执行清理
+//!
+//! 以上类型以及 crate 根目录下的重新导出是稳定的公开接口。
+//!
+//! ```
+//! use std::sync::Arc;
+//! use wechat_cleaner::{MemoryFs, Scanner};
+//! use wechat_cleaner::config::settings::ScannerSettings;
+//! use wechat_cleaner::storage::StorageFormat;
+//!
+//! let fs = MemoryFs::new();
+//! fs.add_file("/cache/a.jpg", b"photo", 1).add_file("/cache/a(1).jpg", b"photo", 2);
+//!
+//! let scanner = Scanner::builder().root("/cache").fs(Arc::new(fs)).build().unwrap();
+//! let settings = ScannerSettings {
+//!     save_path: "/results".into(),
+//!     storage: StorageFormat::Json,
+//!     ..Default::default()
+//! };
+//! let result = scanner.scan(&settings).unwrap();
+//! assert_eq!(result.duplicate_count, 2);
+//! ```
+
+pub mod cancel;
+pub mod checkpoint;
 pub mod config;
 pub mod errors;
 pub mod cleaner;
 pub mod scanner;
 pub mod file_utils;
 pub mod progress;
-// 无用模块：migrator 模块没有被使用
-// pub mod migrator;
+pub mod report;
+pub mod migrator;
+pub mod storage;
+pub mod history;
+pub mod diff;
+pub mod filesystem;
+pub mod hasher;
+pub mod reader;
+pub mod throttle;
+
+pub use cancel::CancellationToken;
+pub use checkpoint::ScanCheckpoint;
+pub use file_utils::FileInfo;
+pub use filesystem::{Fs, MemoryFs, RealFs};
+pub use hasher::{ContentHasher, Md5Hasher};
+pub use scanner::{ScanResult, Scanner, ScannerBuilder};
 
 // Display 功能模块（可选）
 #[cfg(feature = "display")]
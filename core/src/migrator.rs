@@ -0,0 +1,150 @@
+//! 扫描结果格式迁移模块
+//!
+//! 扫描结果文件带有 `format_version` 头部。加载时先按原始 JSON 读取，
+//! 逐级执行迁移步骤升级到 [`CURRENT_FORMAT_VERSION`]，再反序列化为 `ScanResult`。
+//!
+//! # 版本历史
+//! - **1**: 无版本头部，`scan_time` 为 serde 默认的 `{ secs, nanos }` 结构
+//! - **2**: 新增 `format_version`、`tool_version`、`settings_fingerprint` 头部，
+//!   `scan_time` 改为毫秒数 `scan_time_ms`，分类字段全部必填
+//! - **3**: 新增扫描历史所需的 `id` 与 `created_at`（Unix 时间戳，秒）
+//! - **4**: 新增按账号和文件类型的占用统计 `usage`
+//! - **5**: 文件信息新增 `id`、`links`、`hard_links`，结果新增 `symlinks` 与 `hard_links`；
+//!   二进制格式的重复组记录随之变化，更早的二进制文件无法迁移
+//! - **6**: 文件信息新增实际分配的磁盘空间 `allocated`，旧文件中为 `null`（按文件大小计算）
+//! - **7**: 新增缓存根目录的设备号 `root_devices` 和允许清理的设备 `devices`
+//! - **8**: 新增文件名无法解码的文件 `undecodable_files`；路径按原始字节保存，
+//!   二进制格式中的路径改为带标签的记录，更早的二进制文件无法迁移
+//! - **9**: 新增按模式规则统计的重复组 `pattern_rules`
+
+use crate::errors::{Error, Result};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// 当前扫描结果格式版本
+pub const CURRENT_FORMAT_VERSION: u32 = 9;
+
+/// 迁移步骤：将版本 `n` 的文档升级到版本 `n + 1`
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+/// 按版本顺序排列的迁移步骤，下标 0 对应 1 → 2
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4, migrate_v4_to_v5, migrate_v5_to_v6, migrate_v6_to_v7, migrate_v7_to_v8, migrate_v8_to_v9];
+
+/// 读取文档的格式版本，没有版本头部的视为版本 1
+pub fn format_version(document: &Value) -> Result<u32> {
+    match document.get("format_version") {
+        None => Ok(1),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| Error::FileProcessing(format!("无效的扫描结果格式版本: {}", version))),
+    }
+}
+
+/// 将扫描结果文档迁移到当前版本
+///
+/// # 错误
+/// - `Error::UnsupportedFormatVersion` - 文件来自更新版本的工具
+/// - `Error::FileProcessing` - 文档结构不符合其声明的版本
+pub fn migrate(mut document: Value) -> Result<Value> {
+    let mut version = format_version(&document)?;
+    if version > CURRENT_FORMAT_VERSION {
+        return Err(Error::UnsupportedFormatVersion {
+            found: version,
+            supported: CURRENT_FORMAT_VERSION,
+        });
+    }
+
+    let object = document
+        .as_object_mut()
+        .ok_or_else(|| Error::FileProcessing("扫描结果文件不是 JSON 对象".to_string()))?;
+
+    while version < CURRENT_FORMAT_VERSION {
+        MIGRATIONS[(version - 1) as usize](object)?;
+        version += 1;
+        object.insert("format_version".to_string(), Value::from(version));
+        log::debug!("扫描结果已迁移到格式版本 {}", version);
+    }
+
+    Ok(document)
+}
+
+/// 1 → 2：补全头部信息与分类字段，`scan_time` 转换为毫秒
+fn migrate_v1_to_v2(object: &mut Map<String, Value>) -> Result<()> {
+    let scan_time_ms = match object.remove("scan_time") {
+        Some(Value::Object(duration)) => {
+            let secs = duration.get("secs").and_then(Value::as_u64).unwrap_or(0);
+            let nanos = duration.get("nanos").and_then(Value::as_u64).unwrap_or(0);
+            secs * 1000 + nanos / 1_000_000
+        }
+        None => 0,
+        Some(other) => {
+            return Err(Error::FileProcessing(format!("无效的扫描耗时字段: {}", other)));
+        }
+    };
+    object.insert("scan_time_ms".to_string(), Value::from(scan_time_ms));
+
+    object
+        .entry("tool_version")
+        .or_insert_with(|| Value::from("unknown"));
+    object
+        .entry("settings_fingerprint")
+        .or_insert_with(|| Value::from(""));
+
+    for field in ["roots", "leftover_files", "orphaned_derivatives", "attached_derivatives", "verified_groups"] {
+        object
+            .entry(field)
+            .or_insert_with(|| Value::Array(Vec::new()));
+    }
+    Ok(())
+}
+
+/// 2 → 3：以文件名作为扫描编号，扫描时间未知时记为 0
+fn migrate_v2_to_v3(object: &mut Map<String, Value>) -> Result<()> {
+    let id = object
+        .get("path")
+        .and_then(Value::as_str)
+        .and_then(|path| Path::new(path).file_stem())
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    object.entry("id").or_insert_with(|| Value::from(id));
+    object.entry("created_at").or_insert_with(|| Value::from(0));
+    Ok(())
+}
+
+/// 3 → 4：旧的扫描结果没有占用统计
+fn migrate_v3_to_v4(object: &mut Map<String, Value>) -> Result<()> {
+    object.entry("usage").or_insert_with(|| Value::Array(Vec::new()));
+    Ok(())
+}
+
+/// 4 → 5：旧的扫描结果没有链接信息，文件信息中的新字段使用默认值
+fn migrate_v4_to_v5(object: &mut Map<String, Value>) -> Result<()> {
+    object.entry("symlinks").or_insert_with(|| Value::Array(Vec::new()));
+    object.entry("hard_links").or_insert_with(|| Value::from(0));
+    Ok(())
+}
+
+/// 5 → 6：结果头部没有变化，文件信息中的 `allocated` 使用默认值
+fn migrate_v5_to_v6(_object: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+/// 6 → 7：旧的扫描结果没有设备信息，清理时不检查设备
+fn migrate_v6_to_v7(object: &mut Map<String, Value>) -> Result<()> {
+    object.entry("root_devices").or_insert_with(|| Value::Object(Map::new()));
+    object.entry("devices").or_insert_with(|| Value::Array(Vec::new()));
+    Ok(())
+}
+
+/// 7 → 8：补全 `undecodable_files`，JSON 中的路径字符串格式不变
+fn migrate_v7_to_v8(object: &mut Map<String, Value>) -> Result<()> {
+    object.entry("undecodable_files").or_insert_with(|| Value::Array(Vec::new()));
+    Ok(())
+}
+
+/// 8 → 9：旧的扫描结果没有按规则的统计
+fn migrate_v8_to_v9(object: &mut Map<String, Value>) -> Result<()> {
+    object.entry("pattern_rules").or_insert_with(|| Value::Array(Vec::new()));
+    Ok(())
+}
@@ -1,81 +1,115 @@
-//! 简化的进度报告系统
+//! 进度与事件报告
 //!
-//! 提供统一的进度报告接口，直接支持 indicatif::ProgressBar
+//! 扫描和清理过程通过 [`ProgressSink`] 发出类型化的 [`ProgressEvent`]，
+//! 调用方可以据此实现自己的界面、日志或指标统计。
+//!
+//! 核心库只提供两个简单实现：
+//! - [`NoProgress`]：忽略所有事件
+//! - [`LogProgress`]：把事件写入 `log` 日志
+//!
+//! 命令行的进度条适配器位于 CLI 中。
 
-/// 统一的进度报告器
-pub enum Progress {
-    /// 无进度显示
-    None,
-    /// CLI 进度条（需要 indicatif feature）
-    #[cfg(feature = "cli")]
-    Bar(indicatif::ProgressBar),
+use std::fmt;
+use std::path::Path;
+
+/// 扫描和清理的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// 遍历目录
+    Walk,
+    /// 读取文件元数据
+    Metadata,
+    /// 按文件名模式和大小分组，筛选需要计算哈希的候选文件
+    Group,
+    /// 计算候选文件的内容哈希
+    Hash,
+    /// 删除文件
+    Clean,
+    /// 删除清理后产生的空目录
+    Prune,
 }
 
-impl Progress {
-    /// 创建无进度显示的实例
-    pub fn none() -> Self {
-        Progress::None
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Phase::Walk => "遍历目录",
+            Phase::Metadata => "读取文件元数据",
+            Phase::Group => "按大小分组",
+            Phase::Hash => "计算文件哈希",
+            Phase::Clean => "清理文件",
+            Phase::Prune => "清理空目录",
+        };
+        f.write_str(name)
     }
+}
 
-    /// 创建 CLI 进度条实例
-    #[cfg(feature = "cli")]
-    pub fn bar(bar: indicatif::ProgressBar) -> Self {
-        Progress::Bar(bar)
-    }
+/// 进度事件
+///
+/// 事件只借用数据，处理器需要保留时自行复制。
+#[derive(Debug, Clone, Copy)]
+pub enum ProgressEvent<'a> {
+    /// 阶段开始，`total` 为该阶段的工作总量（哈希阶段为字节数，其他阶段为文件数）
+    PhaseStarted { phase: Phase, total: Option<u64> },
+    /// 阶段结束
+    PhaseFinished { phase: Phase },
+    /// 遍历目录发现的文件数
+    FilesDiscovered { count: usize },
+    /// 读取了一个文件的元数据
+    MetadataRead { path: &'a Path, size: u64 },
+    /// 完成分组的文件数
+    FilesGrouped { count: usize },
+    /// 一个文件完成哈希计算
+    BytesHashed { path: &'a Path, bytes: u64 },
+    /// 一个文件无法读取，未能计算哈希；`bytes` 为该文件计入哈希阶段总量的字节数
+    HashFailed { path: &'a Path, bytes: u64, message: &'a str },
+    /// 发现一个重复组
+    GroupFound { key: &'a str, files: usize },
+    /// 删除了一个文件（模拟运行时为计划删除）
+    FileDeleted { path: &'a Path, size: u64 },
+    /// 因扫描后发生变化而跳过的文件
+    FileSkipped { path: &'a Path, reason: &'a str },
+    /// 单个文件处理失败，或者与具体文件无关的错误
+    Error { path: Option<&'a Path>, message: &'a str },
+    /// 其他提示信息
+    Message(&'a str),
+}
 
-    /// 更新进度
-    pub fn update(&self, current: usize, total: usize, message: &str) {
-        match self {
-            Progress::None => { 
-                // 在None模式下，我们只显示消息，不需要current和total
-                let _ = (current, total);
-                println!("{message}") 
-            },
-            #[cfg(feature = "cli")]
-            Progress::Bar(bar) => {
-                if total > 0 {
-                    bar.set_length(total as u64);
-                    bar.set_position(current as u64);
-                }
-                bar.set_message(message.to_string());
-            },
-        }
-    }
+/// 进度事件接收器
+///
+/// 哈希计算是并行执行的，因此事件可能从多个线程同时发出。
+pub trait ProgressSink: Send + Sync {
+    /// 处理一个事件
+    fn on_event(&self, event: &ProgressEvent);
+}
 
-    /// 设置消息
-    pub fn set_message(&self, message: &str) {
-        match self {
-            Progress::None => { println!("{message}") },
-            #[cfg(feature = "cli")]
-            Progress::Bar(bar) => {
-                bar.set_message(message.to_string());
-            },
-        }
-    }
+/// 忽略所有事件
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
 
-    /// 完成进度
-    pub fn finish(&self, message: &str) {
-        match self {
-            Progress::None => { println!("{message}") },
-            #[cfg(feature = "cli")]
-            Progress::Bar(bar) => {
-                bar.finish_with_message(message.to_string());
-            },
-        }
-    }
+impl ProgressSink for NoProgress {
+    fn on_event(&self, _event: &ProgressEvent) {}
+}
 
-    /// 增量更新进度
-    pub fn increment(&self, message: &str) {
-        match self {
-            Progress::None => {
-                // 在None模式下，我们不需要显示消息
-                let _ = message;
-            },
-            #[cfg(feature = "cli")]
-            Progress::Bar(bar) => {
-                bar.inc(1);
-                bar.set_message(message.to_string());
-            },
+/// 把事件写入 `log` 日志：阶段和提示为 info，逐个文件的事件为 debug，错误为 warn
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogProgress;
+
+impl ProgressSink for LogProgress {
+    fn on_event(&self, event: &ProgressEvent) {
+        match *event {
+            ProgressEvent::PhaseStarted { phase, .. } => log::info!("{phase}..."),
+            ProgressEvent::PhaseFinished { phase } => log::info!("{phase}完成"),
+            ProgressEvent::FilesDiscovered { count } => log::info!("发现 {count} 个文件"),
+            ProgressEvent::MetadataRead { path, size } => log::trace!("元数据: {} ({size} 字节)", path.display()),
+            ProgressEvent::FilesGrouped { count } => log::debug!("已分组 {count} 个文件"),
+            ProgressEvent::BytesHashed { path, bytes } => log::debug!("已哈希: {} ({bytes} 字节)", path.display()),
+            ProgressEvent::HashFailed { path, message, .. } => log::warn!("{}: {message}", path.display()),
+            ProgressEvent::GroupFound { key, files } => log::debug!("重复组 {key}: {files} 个文件"),
+            ProgressEvent::FileDeleted { path, .. } => log::debug!("已删除: {}", path.display()),
+            ProgressEvent::FileSkipped { path, reason } => log::warn!("已跳过: {} - {reason}", path.display()),
+            ProgressEvent::Error { path: Some(path), message } => log::warn!("{}: {message}", path.display()),
+            ProgressEvent::Error { path: None, message } => log::warn!("{message}"),
+            ProgressEvent::Message(message) => log::info!("{message}"),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,155 @@
+//! 文件内容读取策略
+//!
+//! 计算哈希时按文件大小选择读取方式：
+//! - 小于 1 MiB 的文件使用 64 KiB 缓冲区
+//! - 1 MiB 及以上的文件使用 1 MiB 的页对齐缓冲区，减少系统调用次数
+//!
+//! 扫描时不使用内存映射：微信运行时会改写和截断自己的缓存文件，映射期间文件被截断会使进程
+//! 收到 SIGBUS 直接退出，绕过取消和检查点机制。内存映射只在通过 `unsafe` 的 [`hash_file_with`]
+//! 显式选择 [`ReadStrategy::Mapped`] 时使用（如基准测试）。
+//!
+//! Linux 上读取前通过 `posix_fadvise` 提示内核顺序预读，读取完成后释放该文件的页缓存，
+//! 避免扫描数 GB 的视频时把其他程序正在使用的页缓存挤出内存。
+
+use crate::hasher::ContentHasher;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// 小文件的读取缓冲区大小
+pub const SMALL_BUFFER: usize = 64 * 1024;
+
+/// 大文件的读取缓冲区大小
+pub const LARGE_BUFFER: usize = 1024 * 1024;
+
+/// 使用大缓冲区的最小文件大小
+pub const LARGE_FILE_THRESHOLD: u64 = 1024 * 1024;
+
+/// 缓冲区对齐的字节数（常见的页大小）
+const ALIGNMENT: usize = 4096;
+
+/// 文件内容读取方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadStrategy {
+    /// 通过指定大小的页对齐缓冲区读取
+    Buffered(usize),
+    /// 内存映射整个文件，文件在读取期间被截断时进程会收到 SIGBUS，只应在确定文件不会被修改时使用
+    Mapped,
+}
+
+impl ReadStrategy {
+    /// 按文件大小选择读取方式，不会选择内存映射
+    pub fn for_size(size: u64) -> Self {
+        if size >= LARGE_FILE_THRESHOLD {
+            ReadStrategy::Buffered(LARGE_BUFFER)
+        } else {
+            ReadStrategy::Buffered(SMALL_BUFFER)
+        }
+    }
+}
+
+/// 使用页对齐缓冲区的读取器
+///
+/// 每次从底层读取整个缓冲区，再分批交给调用方，作用与 `BufReader` 相同，
+/// 但缓冲区起始地址按页对齐。
+pub struct AlignedReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    offset: usize,
+    capacity: usize,
+    pos: usize,
+    filled: usize,
+}
+
+impl<R: Read> AlignedReader<R> {
+    /// 创建缓冲区大小为 `capacity` 的读取器
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        let capacity = capacity.max(1);
+        // 多分配一页，从中取出按页对齐的一段作为缓冲区
+        let buf = vec![0; capacity + ALIGNMENT];
+        let offset = buf.as_ptr().align_offset(ALIGNMENT).min(ALIGNMENT);
+        AlignedReader { inner, buf, offset, capacity, pos: 0, filled: 0 }
+    }
+}
+
+impl<R: Read> Read for AlignedReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.filled {
+            let start = self.offset;
+            self.filled = self.inner.read(&mut self.buf[start..start + self.capacity])?;
+            self.pos = 0;
+        }
+        let available = &self.buf[self.offset + self.pos..self.offset + self.filled];
+        let n = available.len().min(out.len());
+        out[..n].copy_from_slice(&available[..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// 按文件大小选择读取方式计算摘要
+///
+/// 读取完成后（无论成功与否）提示内核释放该文件的页缓存。
+pub fn hash_file(path: &Path, size: u64, hasher: &dyn ContentHasher) -> io::Result<String> {
+    let file = File::open(path)?;
+    let digest = hash_open_file(&file, ReadStrategy::for_size(size), hasher);
+    advise(&file, Advice::DontNeed);
+    digest
+}
+
+/// 使用指定读取方式计算文件摘要，不释放页缓存
+///
+/// # Safety
+///
+/// 选择 [`ReadStrategy::Mapped`] 时，调用方必须保证文件在读取期间不会被任何进程截断或改写：
+/// 映射期间文件被截断时访问映射内存会使进程收到 SIGBUS，被改写时读到的内容没有定义。
+/// 使用 [`ReadStrategy::Buffered`] 时没有额外要求。
+pub unsafe fn hash_file_with(path: &Path, strategy: ReadStrategy, hasher: &dyn ContentHasher) -> io::Result<String> {
+    hash_open_file(&File::open(path)?, strategy, hasher)
+}
+
+fn hash_open_file(file: &File, strategy: ReadStrategy, hasher: &dyn ContentHasher) -> io::Result<String> {
+    advise(file, Advice::Sequential);
+    match strategy {
+        ReadStrategy::Buffered(capacity) => hasher.hash_reader(&mut AlignedReader::with_capacity(capacity, file)),
+        ReadStrategy::Mapped => hash_mapped(file, hasher),
+    }
+}
+
+fn hash_mapped(file: &File, hasher: &dyn ContentHasher) -> io::Result<String> {
+    // SAFETY: 映射只读。文件在映射期间被其他进程截断会导致访问越界（SIGBUS），
+    // 因此扫描不使用内存映射，只有 `unsafe fn hash_file_with` 的调用方才会走到这里，
+    // 由其按该函数的安全要求保证文件在读取期间不被修改。
+    let map = unsafe { Mmap::map(file)? };
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
+    hasher.hash_bytes(&map)
+}
+
+/// 页缓存提示
+#[derive(Debug, Clone, Copy)]
+enum Advice {
+    /// 即将顺序读取整个文件
+    Sequential,
+    /// 不再需要该文件的页缓存
+    DontNeed,
+}
+
+/// 向内核提示文件的访问方式，只是提示，失败不影响读取
+#[cfg(target_os = "linux")]
+fn advise(file: &File, advice: Advice) {
+    use std::os::unix::io::AsRawFd;
+
+    let advice = match advice {
+        Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+        Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+    };
+    // SAFETY: 文件描述符在 `file` 的生命周期内有效，posix_fadvise 不访问用户内存
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, advice);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn advise(_file: &File, _advice: Advice) {}
@@ -0,0 +1,331 @@
+//! 扫描结果导出模块
+//!
+//! 将 [`ScanResult`] 导出为便于分享的报告文件，支持 JSON、CSV 和 HTML 三种格式。
+//! 与 [`ScanResult::save`] 保存的内部格式不同，报告格式是稳定且有文档的，
+//! 详见 `docs/scan-report-formats.md`。
+//!
+//! # 格式
+//! - **JSON**: 结构化报告，重复组带有稳定的组编号
+//! - **CSV**: 每个文件一行，重复文件带有所属组编号
+//! - **HTML**: 自包含的单文件报告，重复组可折叠展开
+
+use crate::errors::{Error, Result};
+use crate::file_utils::{raw_path, FileInfo, HasPath, HasSize};
+use crate::scanner::ScanResult;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// 报告格式版本，字段发生不兼容变更时递增
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// 报告导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Html,
+}
+
+impl ReportFormat {
+    /// 根据文件扩展名推断报告格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            "html" | "htm" => Ok(ReportFormat::Html),
+            _ => Err(Error::InvalidOperation(format!("不支持的报告格式: {}", s))),
+        }
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+            ReportFormat::Html => "html",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 扫描报告（JSON 格式的顶层结构）
+#[derive(Debug, Serialize)]
+pub struct ScanReport {
+    pub schema_version: u32,
+    #[serde(with = "raw_path::many")]
+    pub roots: Vec<PathBuf>,
+    pub total_files: usize,
+    pub duplicate_files: usize,
+    pub duplicate_size: u64,
+    pub scan_time_ms: u128,
+    pub groups: Vec<ReportGroup>,
+    pub leftover_files: Vec<ReportFile>,
+    pub orphaned_derivatives: Vec<ReportFile>,
+    pub attached_derivatives: Vec<ReportFile>,
+}
+
+/// 报告中的重复组
+#[derive(Debug, Serialize)]
+pub struct ReportGroup {
+    /// 组编号，从 1 开始，按组标识排序后分配
+    pub id: usize,
+    /// 组标识：模式组为文件名模式，内容组为哈希值
+    pub key: String,
+    /// 识别方式：`pattern` 或 `content`
+    pub kind: &'static str,
+    pub total_size: u64,
+    pub files: Vec<ReportFile>,
+}
+
+/// 报告中的文件
+#[derive(Debug, Serialize)]
+pub struct ReportFile {
+    #[serde(with = "raw_path")]
+    pub path: PathBuf,
+    pub size: u64,
+    /// 最后修改时间（Unix 时间戳，秒）
+    pub modified: u64,
+}
+
+impl From<&FileInfo> for ReportFile {
+    fn from(file: &FileInfo) -> Self {
+        ReportFile {
+            path: file.path().clone(),
+            size: file.size(),
+            modified: file.modified,
+        }
+    }
+}
+
+impl ScanReport {
+    /// 从扫描结果生成报告，组编号在同一扫描结果上保持稳定
+    pub fn from(scan_result: &ScanResult) -> Self {
+        let mut keys: Vec<&String> = scan_result.duplicate_files.keys().collect();
+        keys.sort();
+
+        let groups: Vec<ReportGroup> = keys
+            .into_iter()
+            .enumerate()
+            .map(|(idx, key)| {
+                let mut files: Vec<ReportFile> = scan_result.duplicate_files[key]
+                    .iter()
+                    .map(ReportFile::from)
+                    .collect();
+                files.sort_by(|a, b| a.path.cmp(&b.path));
+                ReportGroup {
+                    id: idx + 1,
+                    key: key.clone(),
+                    kind: if scan_result.verified_groups.contains(key) { "content" } else { "pattern" },
+                    total_size: files.iter().map(|f| f.size).sum(),
+                    files,
+                }
+            })
+            .collect();
+
+        let to_report = |files: &[FileInfo]| files.iter().map(ReportFile::from).collect::<Vec<_>>();
+
+        ScanReport {
+            schema_version: REPORT_SCHEMA_VERSION,
+            roots: scan_result.roots.clone(),
+            total_files: scan_result.total_files_count,
+            duplicate_files: scan_result.duplicate_count,
+            duplicate_size: groups.iter().map(|g| g.total_size).sum(),
+            scan_time_ms: scan_result.scan_time.as_millis(),
+            groups,
+            leftover_files: to_report(&scan_result.leftover_files),
+            orphaned_derivatives: to_report(&scan_result.orphaned_derivatives),
+            attached_derivatives: to_report(&scan_result.attached_derivatives),
+        }
+    }
+
+    /// 按指定格式写出报告
+    pub fn write_to<W: Write>(&self, writer: &mut W, format: ReportFormat) -> Result<()> {
+        match format {
+            ReportFormat::Json => {
+                serde_json::to_writer_pretty(&mut *writer, self)?;
+                writeln!(writer)?;
+            }
+            ReportFormat::Csv => self.write_csv(writer)?,
+            ReportFormat::Html => self.write_html(writer)?,
+        }
+        Ok(())
+    }
+
+    /// 导出报告到文件
+    pub fn export(&self, path: &Path, format: ReportFormat) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        self.write_to(&mut writer, format)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// CSV：每个文件一行，列为 `group_id,category,kind,key,path,size,modified`
+    fn write_csv<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "group_id,category,kind,key,path,size,modified")?;
+        for group in &self.groups {
+            for file in &group.files {
+                write_csv_row(writer, &group.id.to_string(), "duplicate", group.kind, &group.key, file)?;
+            }
+        }
+        let categories = [
+            ("leftover", &self.leftover_files),
+            ("orphaned_derivative", &self.orphaned_derivatives),
+            ("derivative", &self.attached_derivatives),
+        ];
+        for (category, files) in categories {
+            for file in files {
+                write_csv_row(writer, "", category, "", "", file)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// HTML：自包含报告，内联样式，重复组使用 `<details>` 折叠
+    fn write_html<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(writer, "<title>微信缓存扫描报告</title>")?;
+        writeln!(writer, "<style>{}</style>\n</head>\n<body>", HTML_STYLE)?;
+        writeln!(writer, "<h1>微信缓存扫描报告</h1>")?;
+
+        writeln!(writer, "<table class=\"totals\">")?;
+        let roots = self
+            .roots
+            .iter()
+            .map(|root| escape_html(&root.display().to_string()))
+            .collect::<Vec<_>>()
+            .join("<br>");
+        let totals = [
+            ("扫描目录", roots),
+            ("总文件数", self.total_files.to_string()),
+            ("重复组数", self.groups.len().to_string()),
+            ("重复文件数", self.duplicate_files.to_string()),
+            ("重复文件大小", format_size(self.duplicate_size)),
+            ("残留文件数", self.leftover_files.len().to_string()),
+            ("孤立缩略图数", self.orphaned_derivatives.len().to_string()),
+            ("扫描耗时", format!("{} ms", self.scan_time_ms)),
+        ];
+        for (name, value) in totals {
+            writeln!(writer, "<tr><th>{}</th><td>{}</td></tr>", name, value)?;
+        }
+        writeln!(writer, "</table>")?;
+
+        writeln!(writer, "<h2>重复文件</h2>")?;
+        for group in &self.groups {
+            writeln!(
+                writer,
+                "<details><summary>#{} {} <span class=\"kind\">{}</span> — {} 个文件，{}</summary>",
+                group.id,
+                escape_html(&group.key),
+                group.kind,
+                group.files.len(),
+                format_size(group.total_size)
+            )?;
+            write_html_files(writer, &group.files)?;
+            writeln!(writer, "</details>")?;
+        }
+
+        let categories = [
+            ("残留文件", &self.leftover_files),
+            ("孤立缩略图", &self.orphaned_derivatives),
+            ("其他缩略图", &self.attached_derivatives),
+        ];
+        for (title, files) in categories {
+            if files.is_empty() {
+                continue;
+            }
+            writeln!(writer, "<h2>{}</h2>", title)?;
+            writeln!(writer, "<details><summary>{} 个文件</summary>", files.len())?;
+            write_html_files(writer, files)?;
+            writeln!(writer, "</details>")?;
+        }
+
+        writeln!(writer, "</body>\n</html>")
+    }
+}
+
+impl ScanResult {
+    /// 导出扫描报告到文件
+    pub fn export(&self, path: &Path, format: ReportFormat) -> Result<()> {
+        ScanReport::from(self).export(path, format)
+    }
+}
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,'PingFang SC','Microsoft YaHei',sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin:.5em 0}th,td{border:1px solid #ddd;padding:4px 8px;text-align:left}\
+th{background:#f5f5f5}details{margin:.3em 0}summary{cursor:pointer}\
+.kind{font-size:.8em;color:#fff;background:#888;border-radius:3px;padding:0 4px}\
+td.num{text-align:right;white-space:nowrap}";
+
+fn write_csv_row<W: Write>(
+    writer: &mut W,
+    group_id: &str,
+    category: &str,
+    kind: &str,
+    key: &str,
+    file: &ReportFile,
+) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "{},{},{},{},{},{},{}",
+        group_id,
+        category,
+        kind,
+        escape_csv(key),
+        escape_csv(&file.path.display().to_string()),
+        file.size,
+        file.modified
+    )
+}
+
+fn write_html_files<W: Write>(writer: &mut W, files: &[ReportFile]) -> std::io::Result<()> {
+    writeln!(writer, "<table><tr><th>路径</th><th>大小</th><th>修改时间</th></tr>")?;
+    for file in files {
+        writeln!(
+            writer,
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            escape_html(&file.path.display().to_string()),
+            format_size(file.size),
+            humantime::format_rfc3339_seconds(std::time::UNIX_EPOCH + std::time::Duration::from_secs(file.modified))
+        )?;
+    }
+    writeln!(writer, "</table>")
+}
+
+/// 按 RFC 4180 转义 CSV 字段
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_size(bytes: u64) -> String {
+    bytesize::ByteSize(bytes).to_string_as(true)
+}
@@ -1,11 +1,19 @@
-use crate::config::settings::{ScannerSettings, WechatSettings};
-use crate::file_utils::{FileFilter, FileInfo};
+use crate::cancel::CancellationToken;
+use crate::checkpoint::ScanCheckpoint;
+use crate::config::settings::{default_active_pattern_rules, ScannerSettings, WechatSettings};
+use crate::file_utils::{raw_path, CollectedFiles, DuplicateKind, FileCategory, FileFilter, FileInfo, HasSize, HashContext, Named, PatternRule, PatternSet, DERIVATIVE_RULES};
 use crate::errors::{Error, Result};
-use crate::progress::Progress;
-use regex::{Regex};
+use crate::filesystem::{Fs, RealFs, SymlinkPolicy, WalkOptions};
+use crate::hasher::{ContentHasher, Md5Hasher};
+use crate::history;
+use crate::migrator::{self, CURRENT_FORMAT_VERSION};
+use crate::progress::{NoProgress, ProgressEvent, ProgressSink};
+use crate::storage::{self, StorageFormat};
+use crate::throttle::Throttle;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path,PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::fs;
 
@@ -13,10 +21,29 @@ use std::fs;
 use crate::Display;
 
 /// 扫描结果数据结构（用于序列化/反序列化）
-#[derive(Debug, Serialize, Deserialize, Default)]
+///
+/// 文件格式带有版本头部，旧版本文件由 [`migrator`] 在加载时升级。
+/// 所有字段都是公开的，嵌入核心库时可以用 `..Default::default()` 构造。
+#[derive(Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "display", derive(Display))]
 pub struct ScanResult {
+    /// 文件格式版本
+    pub format_version: u32,
+
+    /// 生成该文件的工具版本
+    pub tool_version: String,
+
+    #[cfg_attr(feature = "display", display(summary, name="扫描编号"))]
+    pub id: String,
+
+    /// 扫描时间（Unix 时间戳，秒）
+    pub created_at: u64,
+
+    /// 扫描设置指纹，用于判断扫描结果是否对应当前设置
+    pub settings_fingerprint: String,
+
     #[cfg_attr(feature = "display", display(summary, name="保存位置"))]
+    #[serde(with = "raw_path")]
     pub path: PathBuf,
 
     #[cfg_attr(feature = "display", display(summary, name="总文件数"))]
@@ -24,43 +51,240 @@ pub struct ScanResult {
     
     #[cfg_attr(feature = "display", display(summary, name="重复文件数"))]
     pub duplicate_count: usize,
+
+    /// 扫描的缓存根目录
+    #[serde(with = "raw_path::many")]
+    pub roots: Vec<PathBuf>,
+
+    /// 各缓存根目录所在的设备号，无法确定的根目录不在其中
+    #[serde(default, with = "raw_path::keys")]
+    pub root_devices: BTreeMap<PathBuf, u64>,
+
+    /// 缓存根目录和扫描到的文件所在的设备，清理时只删除这些设备上的文件
+    ///
+    /// 为空表示设备未知（旧的扫描结果或平台不支持），此时不检查设备。
+    #[serde(default)]
+    pub devices: BTreeSet<u64>,
     
     #[cfg_attr(feature = "display", display(details, name="重复文件详情"))]
     pub duplicate_files: HashMap<String, Vec<FileInfo>>,
+
+    /// 通过内容哈希验证的重复组（`duplicate_files` 中的键）
+    pub verified_groups: HashSet<String>,
+
+    /// 零字节或未完成下载的残留文件
+    #[cfg_attr(feature = "display", display(summary, name="残留文件"))]
+    pub leftover_files: Vec<FileInfo>,
+
+    /// 原始文件已不存在的缩略图等派生文件
+    #[cfg_attr(feature = "display", display(summary, name="孤立缩略图"))]
+    pub orphaned_derivatives: Vec<FileInfo>,
+
+    /// 原始文件仍然存在的派生文件
+    #[cfg_attr(feature = "display", display(summary, name="其他缩略图"))]
+    pub attached_derivatives: Vec<FileInfo>,
+
+    /// 扫描是否被取消而提前结束
+    ///
+    /// 中断的扫描只包含已确认的重复组，文件总数和占用统计也只覆盖已读取的文件。
+    #[serde(default)]
+    pub interrupted: bool,
+
+    /// 未作为文件扫描的符号链接（`Report` 模式下的全部链接，`Follow` 模式下目标不在扫描范围内的文件链接）
+    #[serde(default, with = "raw_path::many")]
+    #[cfg_attr(feature = "display", display(summary, name="符号链接"))]
+    pub symlinks: Vec<PathBuf>,
+
+    /// 合并到其他文件的硬链接路径数
+    #[serde(default)]
+    #[cfg_attr(feature = "display", display(summary, name="合并的硬链接"))]
+    pub hard_links: usize,
+
+    /// 文件名无法解码为 UTF-8 的文件，不参与按文件名的重复检测，只按内容检测
+    #[serde(default)]
+    #[cfg_attr(feature = "display", display(summary, details, name="无法解码的文件名"))]
+    pub undecodable_files: Vec<FileInfo>,
+
+    /// 各条启用的模式规则识别出的重复组，用于找出误判较多的规则
+    #[serde(default)]
+    #[cfg_attr(feature = "display", display(details, name="模式规则"))]
+    pub pattern_rules: Vec<PatternRuleStat>,
+
+    /// 按账号和文件类型统计的缓存占用（包含所有扫描到的文件）
+    #[cfg_attr(feature = "display", display(details, name="占用统计"))]
+    pub usage: Vec<UsageStat>,
     
+    #[serde(rename = "scan_time_ms", with = "duration_ms")]
     #[cfg_attr(feature = "display", display(summary, name="扫描耗时"))]
     pub scan_time: Duration,
 }
 
-impl ScanResult {
-    fn new(save_path: PathBuf, total_files_count: usize, duplicate_files: HashMap<String, Vec<FileInfo>>, start_time: Instant) -> Self {
+impl Default for ScanResult {
+    fn default() -> Self {
         ScanResult {
-            path: save_path,
-            total_files_count,
-            duplicate_count: duplicate_files.values().map(Vec::len).sum(),
-            duplicate_files,
-            scan_time: start_time.elapsed(),
+            format_version: CURRENT_FORMAT_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            id: String::new(),
+            created_at: 0,
+            settings_fingerprint: String::new(),
+            path: PathBuf::new(),
+            total_files_count: 0,
+            duplicate_count: 0,
+            roots: Vec::new(),
+            root_devices: BTreeMap::new(),
+            devices: BTreeSet::new(),
+            duplicate_files: HashMap::new(),
+            verified_groups: HashSet::new(),
+            leftover_files: Vec::new(),
+            orphaned_derivatives: Vec::new(),
+            attached_derivatives: Vec::new(),
+            interrupted: false,
+            symlinks: Vec::new(),
+            hard_links: 0,
+            undecodable_files: Vec::new(),
+            pattern_rules: Vec::new(),
+            usage: Vec::new(),
+            scan_time: Duration::ZERO,
         }
     }
+}
+
+/// 一条模式规则在本次扫描中识别出的重复组
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternRuleStat {
+    /// 规则名称
+    pub name: String,
+    /// 含有该规则所匹配文件的模式重复组（`duplicate_files` 中的键），按键排序
+    pub groups: Vec<String>,
+    /// 模式重复组中由该规则匹配的文件数
+    pub files_count: usize,
+}
+
+impl PatternRuleStat {
+    /// 统计每条规则在模式重复组中匹配的文件，按规则顺序排列，未匹配任何文件的规则同样列出
+    ///
+    /// 没有规则匹配、按去掉扩展名的名称加入组的文件（通常是原始文件）不计入任何规则。
+    pub fn collect(patterns: &PatternSet, duplicates: &HashMap<String, (DuplicateKind, Vec<FileInfo>)>) -> Vec<Self> {
+        let mut stats: Vec<PatternRuleStat> = patterns
+            .rules()
+            .map(|rule| PatternRuleStat { name: rule.name.clone(), groups: Vec::new(), files_count: 0 })
+            .collect();
+
+        let mut keys: Vec<&String> = duplicates
+            .iter()
+            .filter(|(_, (kind, _))| *kind == DuplicateKind::Pattern)
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort();
 
+        for key in keys {
+            for file in &duplicates[key].1 {
+                let Some(rule) = file.pattern_match(patterns).and_then(|matched| matched.rule) else {
+                    continue;
+                };
+                if let Some(stat) = stats.iter_mut().find(|stat| stat.name == rule) {
+                    stat.files_count += 1;
+                    if stat.groups.last() != Some(key) {
+                        stat.groups.push(key.clone());
+                    }
+                }
+            }
+        }
+        stats
+    }
+}
+
+/// 一个账号下一种文件类型的占用统计
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageStat {
+    /// 微信账号，无法从路径识别时为 `None`
+    pub account: Option<String>,
+    pub category: FileCategory,
+    pub files_count: usize,
+    pub total_size: u64,
+}
+
+impl UsageStat {
+    /// 按账号和文件类型汇总，结果按账号、类型排序
+    pub fn collect(files: &[FileInfo]) -> Vec<Self> {
+        let mut usage: BTreeMap<(Option<String>, FileCategory), (usize, u64)> = BTreeMap::new();
+        for file in files {
+            let entry = usage.entry((file.account(), file.category())).or_default();
+            entry.0 += 1;
+            entry.1 += file.size();
+        }
+
+        usage
+            .into_iter()
+            .map(|((account, category), (files_count, total_size))| UsageStat {
+                account,
+                category,
+                files_count,
+                total_size,
+            })
+            .collect()
+    }
+}
+
+/// 以毫秒数序列化 `Duration`
+pub(crate) mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+/// 把标注了识别方式的重复组拆分为重复文件和经过内容验证的组键
+fn split_verified(classified: HashMap<String, (DuplicateKind, Vec<FileInfo>)>) -> (HashMap<String, Vec<FileInfo>>, HashSet<String>) {
+    let mut duplicate_files = HashMap::with_capacity(classified.len());
+    let mut verified_groups = HashSet::new();
+    for (key, (kind, files)) in classified {
+        if kind == DuplicateKind::Content {
+            verified_groups.insert(key.clone());
+        }
+        duplicate_files.insert(key, files);
+    }
+    (duplicate_files, verified_groups)
+}
+
+impl ScanResult {
     /// 保存扫描结果到临时文件
+    ///
+    /// 文件扩展名为 `.bin` 时使用二进制格式，否则使用 JSON。
     pub fn save(&self) -> Result<()> {
-        let json = serde_json::to_string_pretty(self)?;
         if let Some(parent) = Path::new(&self.path).parent() {
             fs::create_dir_all(parent)?; // 递归创建所有缺失的目录
         }
+        if self.path.extension().is_some_and(|ext| ext == "bin") {
+            return storage::save_binary(self, &self.path);
+        }
+        let json = serde_json::to_string_pretty(self)?;
         fs::write(&self.path, json)?;
         Ok(())
     }
 
     /// 从临时文件加载扫描结果
+    ///
+    /// 旧版本的文件会被自动迁移到当前格式；来自更新版本工具的文件会返回
+    /// `Error::UnsupportedFormatVersion`。
     pub fn load(file: &PathBuf) -> Result<Self> {
         if !file.exists() {
             return Err(Error::FileProcessing("未找到扫描结果文件，请先执行扫描命令".to_string()));
         };
 
+        if StorageFormat::detect(file)? == StorageFormat::Binary {
+            return storage::load_binary(file);
+        }
+
         let json = fs::read_to_string(file)?;
-        serde_json::from_str(&json).map_err(|e| Error::Json(e))
+        let document = migrator::migrate(serde_json::from_str(&json)?)?;
+        serde_json::from_value(document).map_err(Error::Json)
     }
 
     /// 删除扫描结果文件
@@ -74,52 +298,354 @@ impl ScanResult {
 }
 
 /// 文件扫描器
+///
+/// 可以通过 [`FileScanner::new`] 从配置创建，也可以通过 [`FileScanner::builder`]
+/// 直接指定缓存根目录、文件名模式、哈希算法和文件系统后端。
 pub struct FileScanner {
     path: PathBuf,
-    pattern: Regex,
+    patterns: PatternSet,
+    hasher: Arc<dyn ContentHasher>,
+    fs: Arc<dyn Fs>,
+    cancel: CancellationToken,
+    checkpoint: Option<PathBuf>,
+    resume: bool,
+    symlinks: SymlinkPolicy,
+    one_file_system: bool,
 }
 
+/// [`FileScanner`] 的别名，便于嵌入核心库的调用方使用
+pub type Scanner = FileScanner;
+
 impl FileScanner {
     /// 创建新的文件扫描器
     pub fn new(settings: &WechatSettings) -> Result<Self> {
-        let path = settings.cache_path.clone().ok_or(Error::CacheNotFound)?;
-        let pattern = Regex::new(&settings.cache_patterns)?;
-        Ok(FileScanner { path, pattern })
+        Self::with_fs(settings, Arc::new(RealFs))
+    }
+
+    /// 创建使用指定文件系统后端的文件扫描器
+    pub fn with_fs(settings: &WechatSettings, fs: Arc<dyn Fs>) -> Result<Self> {
+        ScannerBuilder::from_settings(settings).fs(fs).build()
+    }
+
+    /// 创建扫描器构建器
+    ///
+    /// # 示例
+    /// ```
+    /// use std::sync::Arc;
+    /// use wechat_cleaner::filesystem::MemoryFs;
+    /// use wechat_cleaner::hasher::Md5Hasher;
+    /// use wechat_cleaner::scanner::Scanner;
+    ///
+    /// let fs = MemoryFs::new();
+    /// fs.add_file("/cache/a.jpg", b"photo", 1).add_file("/cache/a(1).jpg", b"photo", 2);
+    ///
+    /// let scanner = Scanner::builder()
+    ///     .root("/cache")
+    ///     .pattern(r"\(\d+\)\.[a-zA-Z0-9]+$")
+    ///     .hasher(Md5Hasher)
+    ///     .fs(Arc::new(fs))
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(!scanner.settings_fingerprint().is_empty());
+    /// ```
+    pub fn builder() -> ScannerBuilder {
+        ScannerBuilder::default()
+    }
+
+    /// 扫描设置指纹
+    ///
+    /// 由缓存根目录、文件名模式、哈希算法、符号链接处理方式和是否跨越文件系统计算，
+    /// 设置不变时指纹保持一致。
+    pub fn settings_fingerprint(&self) -> String {
+        use md5::{Digest, Md5};
+
+        let mut hasher = Md5::new();
+        hasher.update(self.path.as_os_str().as_encoded_bytes());
+        hasher.update([0]);
+        for rule in self.patterns.rules() {
+            hasher.update(rule.name.as_bytes());
+            hasher.update([0]);
+            hasher.update(rule.regex.as_bytes());
+            hasher.update(rule.group.to_le_bytes());
+        }
+        hasher.update([0]);
+        hasher.update(self.hasher.name().as_bytes());
+        hasher.update([0]);
+        hasher.update(self.symlinks.as_str().as_bytes());
+        hasher.update([0, u8::from(self.one_file_system)]);
+        format!("{:x}", hasher.finalize())
     }
 
     /// 执行文件扫描
     pub fn scan(&self, settings: &ScannerSettings) -> Option<ScanResult> {
-        self.scan_with_progress(settings, &Progress::none())
+        self.scan_with_progress(settings, &NoProgress)
     }
 
-    /// 带进度显示的文件扫描
-    pub fn scan_with_progress(&self, settings: &ScannerSettings, progress: &Progress) -> Option<ScanResult> {
+    /// 带进度报告的文件扫描
+    ///
+    /// 依次发出遍历、读取元数据、分组和哈希各阶段的事件，以及每个重复组的 `GroupFound` 事件。
+    /// 通过 [`ScannerBuilder::cancellation`] 设置的令牌被取消时提前结束，
+    /// 返回 `interrupted` 为 `true` 的部分结果。
+    ///
+    /// 设置了检查点路径时，扫描过程中会把文件列表和已计算的摘要写入检查点，
+    /// 中断后保留检查点，完成后删除；以恢复模式扫描时从匹配的检查点继续。
+    ///
+    /// 扫描设置中的线程数、读取带宽和机械硬盘模式决定读取文件的方式。
+    pub fn scan_with_progress(&self, settings: &ScannerSettings, progress: &dyn ProgressSink) -> Option<ScanResult> {
+        match settings.threads.filter(|&threads| threads > 0) {
+            Some(threads) => match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+                Ok(pool) => pool.install(|| self.scan_in_pool(settings, progress)),
+                Err(e) => {
+                    log::warn!("无法创建扫描线程池，使用默认线程数: {}", e);
+                    self.scan_in_pool(settings, progress)
+                }
+            },
+            None => self.scan_in_pool(settings, progress),
+        }
+    }
+
+    /// 在当前 rayon 线程池中执行扫描
+    fn scan_in_pool(&self, settings: &ScannerSettings, progress: &dyn ProgressSink) -> Option<ScanResult> {
         let start_time = Instant::now();
-        progress.set_message("开始扫描微信缓存文件...");
-        
-        progress.set_message("收集文件元数据...");
-        let all_files = FileInfo::collect_from(&self.path)?;
-        let all_files_count = &all_files.len();
+        let roots = vec![self.path.clone()];
+        let settings_fingerprint = self.settings_fingerprint();
+        let checkpoint = self.open_checkpoint(&settings_fingerprint, &roots, progress);
 
-        if all_files_count == &0 {
-            progress.finish("无重复文件");
-            return None;
-        }
+        let resumed = checkpoint
+            .as_ref()
+            .and_then(|checkpoint| Some(CollectedFiles { files: checkpoint.files()?, symlinks: checkpoint.symlinks() }));
+        let CollectedFiles { files: all_files, symlinks } = match resumed {
+            Some(collected) => collected,
+            None => {
+                let options = WalkOptions { symlinks: self.symlinks, one_file_system: self.one_file_system };
+                let collected = FileInfo::collect_with(self.fs.as_ref(), &self.path, &options, progress, &self.cancel)?;
+                if let Some(checkpoint) = &checkpoint
+                    && !self.cancel.is_cancelled()
+                    && let Err(e) = checkpoint.record_files(&collected.files, &collected.symlinks)
+                {
+                    let message = format!("无法写入扫描检查点: {}", e);
+                    progress.on_event(&ProgressEvent::Error { path: Some(checkpoint.path()), message: &message });
+                }
+                collected
+            }
+        };
+        let all_files_count = all_files.len();
+        let hard_links = all_files.iter().map(|file| file.hard_links().len()).sum();
+        let root_devices: BTreeMap<PathBuf, u64> = roots
+            .iter()
+            .filter_map(|root| Some((root.clone(), self.fs.device(root)?)))
+            .collect();
+        let devices = root_devices
+            .values()
+            .copied()
+            .chain(all_files.iter().filter_map(|file| file.id().map(|id| id.device)))
+            .collect();
+        let usage = UsageStat::collect(&all_files);
 
-        let duplicate_files = all_files.duplicates_by_pattern(&self.pattern);
+        // 残留文件单独归类，不参与重复检测（零字节文件的哈希全部相同）
+        let (leftover_files, candidates): (Vec<_>, Vec<_>) =
+            all_files.into_iter().partition(FileInfo::is_leftover);
 
-        let save_path = settings
-            .save_path
-            .join("wechat-cleaner/scan-result.json");
+        // 文件名无法解码的文件单独列出，分组时不按替换字符后的名称与其他文件混为一组，只按内容检测
+        let undecodable_files: Vec<FileInfo> =
+            candidates.iter().filter(|file| file.has_undecodable_name()).cloned().collect();
 
-        let result = ScanResult::new(
-            save_path,
-            *all_files_count,
+        // 缩略图等派生文件同样单独归类
+        let mut derivatives = candidates.split_derivatives(DERIVATIVE_RULES);
+        let candidates = std::mem::take(&mut derivatives.others);
+
+        let throttle = settings.read_limit_mbps().map(Throttle::from_mb_per_sec);
+        let mut ctx = HashContext::new(self.fs.as_ref())
+            .hasher(self.hasher.as_ref())
+            .progress(progress)
+            .cancel(&self.cancel)
+            .sequential(settings.hdd_mode);
+        if let Some(checkpoint) = &checkpoint {
+            ctx = ctx.checkpoint(checkpoint);
+        }
+        if let Some(throttle) = &throttle {
+            ctx = ctx.throttle(throttle);
+        }
+        let duplicate_files = candidates.classify_duplicates_with(&self.patterns, &ctx);
+        for (key, (_, files)) in &duplicate_files {
+            progress.on_event(&ProgressEvent::GroupFound { key, files: files.len() });
+        }
+        let pattern_rules = PatternRuleStat::collect(&self.patterns, &duplicate_files);
+
+        let created_at = history::now();
+        let id = history::scan_id(created_at);
+        let save_path = settings.scan_path(&id);
+
+        let interrupted = self.cancel.is_cancelled();
+        if interrupted {
+            progress.on_event(&ProgressEvent::Message("扫描已取消，结果只包含已完成的部分"));
+        }
+        if let Some(checkpoint) = &checkpoint {
+            let saved = if interrupted { checkpoint.save() } else { checkpoint.remove() };
+            if let Err(e) = saved {
+                let message = format!("无法更新扫描检查点: {}", e);
+                progress.on_event(&ProgressEvent::Error { path: Some(checkpoint.path()), message: &message });
+            }
+        }
+        let (duplicate_files, verified_groups) = split_verified(duplicate_files);
+        Some(ScanResult {
+            id,
+            created_at,
+            settings_fingerprint,
+            path: save_path,
+            interrupted,
+            total_files_count: all_files_count,
+            duplicate_count: duplicate_files.values().map(Vec::len).sum(),
+            roots,
+            root_devices,
+            devices,
             duplicate_files,
-            start_time,
-        );
+            verified_groups,
+            leftover_files,
+            orphaned_derivatives: derivatives.orphaned,
+            attached_derivatives: derivatives.attached,
+            undecodable_files,
+            symlinks,
+            hard_links,
+            pattern_rules,
+            usage,
+            scan_time: start_time.elapsed(),
+            ..Default::default()
+        })
+    }
+
+    /// 打开本次扫描使用的检查点
+    ///
+    /// 恢复模式下读取与当前根目录和设置指纹匹配的检查点，找不到时从头开始扫描。
+    fn open_checkpoint(&self, settings_fingerprint: &str, roots: &[PathBuf], progress: &dyn ProgressSink) -> Option<ScanCheckpoint> {
+        let path = self.checkpoint.as_ref()?;
+        if self.resume {
+            match ScanCheckpoint::load(path, settings_fingerprint, roots) {
+                Some(checkpoint) if checkpoint.files().is_some() => {
+                    let message = format!("从检查点恢复扫描，已计算 {} 个文件的摘要", checkpoint.digests_count());
+                    progress.on_event(&ProgressEvent::Message(&message));
+                    return Some(checkpoint);
+                }
+                _ => progress.on_event(&ProgressEvent::Message("没有可恢复的检查点，重新开始扫描")),
+            }
+        }
+        Some(ScanCheckpoint::new(path.clone(), settings_fingerprint, roots))
+    }
+}
+
+/// [`FileScanner`] 构建器
+///
+/// 只有缓存根目录是必需的；未指定时文件名模式使用默认的微信副本模式，
+/// 哈希算法使用 [`Md5Hasher`]，文件系统后端使用 [`RealFs`]。
+#[derive(Default)]
+pub struct ScannerBuilder {
+    root: Option<PathBuf>,
+    patterns: Option<Vec<PatternRule>>,
+    hasher: Option<Arc<dyn ContentHasher>>,
+    fs: Option<Arc<dyn Fs>>,
+    cancel: Option<CancellationToken>,
+    checkpoint: Option<PathBuf>,
+    resume: bool,
+    symlinks: SymlinkPolicy,
+    one_file_system: Option<bool>,
+}
+
+impl ScannerBuilder {
+    /// 使用配置中的缓存根目录和文件名模式规则创建构建器
+    pub fn from_settings(settings: &WechatSettings) -> Self {
+        let builder = ScannerBuilder::default().pattern_rules(settings.active_pattern_rules());
+        match &settings.cache_path {
+            Some(path) => builder.root(path),
+            None => builder,
+        }
+    }
+
+    /// 设置要扫描的缓存根目录
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// 只使用单个正则表达式识别自动生成的副本，取第一个匹配位置之前的部分作为原始名称
+    ///
+    /// 等同于只包含 [`PatternRule::legacy`] 一条规则的 [`ScannerBuilder::pattern_rules`]。
+    pub fn pattern(self, pattern: impl AsRef<str>) -> Self {
+        self.pattern_rules(vec![PatternRule::legacy("pattern", pattern.as_ref())])
+    }
+
+    /// 设置识别自动生成副本的文件名模式规则，按顺序尝试
+    ///
+    /// 未设置时使用内置规则中默认启用的部分。
+    pub fn pattern_rules(mut self, rules: Vec<PatternRule>) -> Self {
+        self.patterns = Some(rules);
+        self
+    }
+
+    /// 设置内容重复检测使用的哈希算法
+    pub fn hasher(mut self, hasher: impl ContentHasher + 'static) -> Self {
+        self.hasher = Some(Arc::new(hasher));
+        self
+    }
+
+    /// 设置文件系统后端
+    pub fn fs(mut self, fs: Arc<dyn Fs>) -> Self {
+        self.fs = Some(fs);
+        self
+    }
+
+    /// 设置取消令牌
+    pub fn cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// 设置检查点文件路径，扫描过程中定期写入已完成的进度
+    pub fn checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    /// 设置是否从检查点恢复扫描
+    ///
+    /// 只有检查点的缓存根目录和扫描设置指纹与本次扫描一致时才会复用。
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// 设置符号链接的处理方式，默认跳过
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// 设置是否只扫描与缓存根目录在同一文件系统上的文件，默认开启
+    ///
+    /// 开启时不进入挂载点，避免把挂载在缓存目录中的备份盘当作重复文件清理。
+    pub fn one_file_system(mut self, one_file_system: bool) -> Self {
+        self.one_file_system = Some(one_file_system);
+        self
+    }
 
-        progress.finish("扫描完成");
-        Some(result)
+    /// 创建扫描器
+    ///
+    /// # 错误
+    /// - `Error::CacheNotFound` - 未设置缓存根目录
+    /// - `Error::Regex` - 文件名模式不是合法的正则表达式
+    /// - `Error::Config` - 模式规则引用了不存在的捕获组
+    pub fn build(self) -> Result<FileScanner> {
+        let path = self.root.ok_or(Error::CacheNotFound)?;
+        let patterns = PatternSet::new(&self.patterns.unwrap_or_else(default_active_pattern_rules))?;
+        Ok(FileScanner {
+            path,
+            patterns,
+            hasher: self.hasher.unwrap_or_else(|| Arc::new(Md5Hasher)),
+            fs: self.fs.unwrap_or_else(|| Arc::new(RealFs)),
+            cancel: self.cancel.unwrap_or_default(),
+            checkpoint: self.checkpoint,
+            resume: self.resume,
+            symlinks: self.symlinks,
+            one_file_system: self.one_file_system.unwrap_or(true),
+        })
     }
 }
@@ -0,0 +1,244 @@
+//! 扫描结果存储模块
+//!
+//! 扫描结果支持两种存储格式（由 `ScannerSettings::storage` 选择）：
+//! - **JSON**: 便于阅读和排查，加载时需要将整个文件读入内存
+//! - **二进制**: 紧凑编码，重复组逐条存储，可以流式遍历而无需一次性加载
+//!
+//! # 二进制文件布局
+//! ```text
+//! "WCSR" | 头部长度 (u32 LE) | 头部 JSON | 重复组数量 (u64 LE) | 重复组记录 (bincode) ...
+//! ```
+//! 头部是不含重复组的 `ScanResult` JSON，因此与 JSON 格式共用版本迁移逻辑；
+//! 重复组记录使用 bincode 编码（定长整数），其结构变化时需要提升格式版本并重新扫描。
+//!
+//! 读取时头部长度、重复组数量和每条记录的大小都有上限，损坏的文件返回错误而不会耗尽内存。
+
+use crate::errors::{Error, Result};
+use crate::file_utils::FileInfo;
+use crate::migrator;
+use crate::scanner::ScanResult;
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// 二进制扫描结果文件的魔数
+const MAGIC: &[u8; 4] = b"WCSR";
+
+/// 重复组记录与当前结构一致的最低格式版本，更早的二进制文件需要重新扫描
+const MIN_BINARY_FORMAT_VERSION: u32 = 8;
+
+/// 头部 JSON 的最大长度
+const MAX_HEADER_LEN: u64 = 256 * 1024 * 1024;
+
+/// 单条重复组记录的最大长度
+const MAX_GROUP_LEN: u64 = 256 * 1024 * 1024;
+
+/// 一条重复组记录的最小长度：组标识长度 (u64)、是否验证 (u8)、文件数 (u64)
+const MIN_GROUP_LEN: u64 = 17;
+
+/// 重复组记录的编码选项
+///
+/// 与 `bincode::serialize_into` 的默认编码相同（定长整数、小端序），另外限制单条记录的大小。
+fn codec() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(MAX_GROUP_LEN)
+}
+
+/// 损坏的二进制扫描结果文件
+fn corrupted(path: &Path, what: &str) -> Error {
+    Error::FileProcessing(format!("二进制扫描结果已损坏（{}）: {}", what, path.display()))
+}
+
+/// 扫描结果存储格式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// 格式化的 JSON
+    #[default]
+    Json,
+    /// 紧凑的二进制编码，支持流式读取
+    Binary,
+}
+
+impl StorageFormat {
+    /// 该格式的扫描结果文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StorageFormat::Json => "json",
+            StorageFormat::Binary => "bin",
+        }
+    }
+
+    /// 根据文件内容判断存储格式
+    pub fn detect(path: &Path) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        let mut file = fs::File::open(path)?;
+        match file.read_exact(&mut magic) {
+            Ok(()) if &magic == MAGIC => Ok(StorageFormat::Binary),
+            _ => Ok(StorageFormat::Json),
+        }
+    }
+}
+
+/// 一个重复组及其识别方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    /// 组标识（`ScanResult::duplicate_files` 中的键）
+    pub key: String,
+    /// 是否经过内容哈希验证
+    pub verified: bool,
+    pub files: Vec<FileInfo>,
+}
+
+impl ScanResult {
+    /// 遍历所有重复组
+    pub fn duplicate_groups(&self) -> impl Iterator<Item = DuplicateGroup> + '_ {
+        self.duplicate_files.iter().map(|(key, files)| DuplicateGroup {
+            key: key.clone(),
+            verified: self.verified_groups.contains(key),
+            files: files.clone(),
+        })
+    }
+}
+
+/// 以二进制格式写出扫描结果
+pub fn save_binary(result: &ScanResult, path: &Path) -> Result<()> {
+    let header_json = serde_json::to_vec(&header(result))?;
+    if header_json.len() as u64 > MAX_HEADER_LEN {
+        return Err(Error::FileProcessing("扫描结果头部过大，请改用 JSON 存储格式".to_string()));
+    }
+
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&(header_json.len() as u32).to_le_bytes())?;
+    writer.write_all(&header_json)?;
+    writer.write_all(&(result.duplicate_files.len() as u64).to_le_bytes())?;
+    for (key, files) in &result.duplicate_files {
+        // 字段顺序与 DuplicateGroup 一致，避免为写出而复制文件列表
+        let verified = result.verified_groups.contains(key);
+        codec().serialize_into(&mut writer, &(key, verified, files))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// 复制扫描结果中除重复组以外的部分
+fn header(result: &ScanResult) -> ScanResult {
+    ScanResult {
+        format_version: result.format_version,
+        tool_version: result.tool_version.clone(),
+        id: result.id.clone(),
+        created_at: result.created_at,
+        settings_fingerprint: result.settings_fingerprint.clone(),
+        path: result.path.clone(),
+        total_files_count: result.total_files_count,
+        duplicate_count: result.duplicate_count,
+        roots: result.roots.clone(),
+        root_devices: result.root_devices.clone(),
+        devices: result.devices.clone(),
+        duplicate_files: HashMap::new(),
+        verified_groups: HashSet::new(),
+        leftover_files: result.leftover_files.clone(),
+        orphaned_derivatives: result.orphaned_derivatives.clone(),
+        attached_derivatives: result.attached_derivatives.clone(),
+        interrupted: result.interrupted,
+        symlinks: result.symlinks.clone(),
+        hard_links: result.hard_links,
+        undecodable_files: result.undecodable_files.clone(),
+        pattern_rules: result.pattern_rules.clone(),
+        usage: result.usage.clone(),
+        scan_time: result.scan_time,
+    }
+}
+
+/// 打开二进制扫描结果
+///
+/// 只读取头部，返回不含重复组的 `ScanResult` 以及按需读取重复组的迭代器。
+pub fn open_binary(path: &Path) -> Result<(ScanResult, GroupReader<BufReader<fs::File>>)> {
+    let file = fs::File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(Error::FileProcessing(format!("不是二进制扫描结果文件: {}", path.display())));
+    }
+
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let header_len = u64::from(u32::from_le_bytes(len));
+    // 魔数、头部长度和重复组数量共占 16 字节
+    if header_len > MAX_HEADER_LEN || header_len + 16 > file_len {
+        return Err(corrupted(path, "头部长度无效"));
+    }
+    let mut header_json = vec![0u8; header_len as usize];
+    reader.read_exact(&mut header_json)?;
+
+    let header: serde_json::Value = serde_json::from_slice(&header_json)?;
+    let version = migrator::format_version(&header)?;
+    if version < MIN_BINARY_FORMAT_VERSION {
+        return Err(Error::FileProcessing(format!(
+            "二进制扫描结果的格式版本 {} 已过时，请重新扫描: {}",
+            version,
+            path.display()
+        )));
+    }
+    let header = migrator::migrate(header)?;
+    let header: ScanResult = serde_json::from_value(header)?;
+
+    let mut count = [0u8; 8];
+    reader.read_exact(&mut count)?;
+    let remaining = u64::from_le_bytes(count);
+    if remaining > (file_len - header_len - 16) / MIN_GROUP_LEN {
+        return Err(corrupted(path, "重复组数量无效"));
+    }
+
+    Ok((header, GroupReader { reader, remaining }))
+}
+
+/// 加载完整的二进制扫描结果
+pub fn load_binary(path: &Path) -> Result<ScanResult> {
+    let (mut result, groups) = open_binary(path)?;
+    for group in groups {
+        let group = group?;
+        if group.verified {
+            result.verified_groups.insert(group.key.clone());
+        }
+        result.duplicate_files.insert(group.key, group.files);
+    }
+    Ok(result)
+}
+
+/// 按需读取二进制扫描结果中的重复组
+pub struct GroupReader<R> {
+    reader: R,
+    remaining: u64,
+}
+
+impl<R: Read> Iterator for GroupReader<R> {
+    type Item = Result<DuplicateGroup>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let group = codec().deserialize_from(&mut self.reader).map_err(Error::from);
+        if group.is_err() {
+            // 记录损坏后无法继续定位下一条记录
+            self.remaining = 0;
+        }
+        Some(group)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining as usize;
+        (remaining, Some(remaining))
+    }
+}
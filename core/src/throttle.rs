@@ -0,0 +1,97 @@
+//! 读取限速
+//!
+//! 计算哈希时按配置的带宽上限读取文件内容，避免扫描占满磁盘 I/O。
+//! 所有哈希线程共享同一个 [`Throttle`]，因此限制的是整个扫描的总读取速度。
+
+use crate::hasher::ContentHasher;
+use std::io::{self, Read};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 读取带宽限制器
+///
+/// 记录开始以来读取的总字节数，读取速度超过上限时让当前线程休眠到平均速度回到上限以内。
+#[derive(Debug)]
+pub struct Throttle {
+    bytes_per_sec: u64,
+    state: Mutex<ThrottleState>,
+}
+
+#[derive(Debug)]
+struct ThrottleState {
+    started: Instant,
+    consumed: u64,
+}
+
+impl Throttle {
+    /// 创建每秒最多读取 `bytes_per_sec` 字节的限制器
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Throttle {
+            bytes_per_sec: bytes_per_sec.max(1),
+            state: Mutex::new(ThrottleState { started: Instant::now(), consumed: 0 }),
+        }
+    }
+
+    /// 创建每秒最多读取 `mb_per_sec` MB 的限制器
+    pub fn from_mb_per_sec(mb_per_sec: u64) -> Self {
+        Self::new(mb_per_sec.saturating_mul(1024 * 1024))
+    }
+
+    /// 每秒允许读取的字节数
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
+
+    /// 记录读取了 `bytes` 字节，必要时休眠
+    pub fn consume(&self, bytes: u64) {
+        let delay = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.consumed += bytes;
+            let expected = Duration::from_secs_f64(state.consumed as f64 / self.bytes_per_sec as f64);
+            expected.saturating_sub(state.started.elapsed())
+        };
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+    }
+}
+
+/// 受限速的读取器
+struct ThrottledReader<'a> {
+    inner: &'a mut dyn Read,
+    throttle: &'a Throttle,
+}
+
+impl Read for ThrottledReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.throttle.consume(read as u64);
+        Ok(read)
+    }
+}
+
+/// 读取内容受限速的哈希算法
+///
+/// 包装另一个 [`ContentHasher`]，算法名称与被包装的算法相同，因此不影响扫描设置指纹。
+pub struct ThrottledHasher<'a> {
+    inner: &'a dyn ContentHasher,
+    throttle: &'a Throttle,
+}
+
+impl<'a> ThrottledHasher<'a> {
+    /// 用 `throttle` 限制 `inner` 的读取速度
+    pub fn new(inner: &'a dyn ContentHasher, throttle: &'a Throttle) -> Self {
+        ThrottledHasher { inner, throttle }
+    }
+}
+
+impl ContentHasher for ThrottledHasher<'_> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn hash_reader(&self, reader: &mut dyn Read) -> io::Result<String> {
+        self.inner.hash_reader(&mut ThrottledReader { inner: reader, throttle: self.throttle })
+    }
+}
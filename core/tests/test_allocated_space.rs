@@ -0,0 +1,87 @@
+//! 磁盘占用统计集成测试
+//!
+//! 清理预览和清理结果同时统计逻辑大小和实际分配的磁盘空间，
+//! 并记录清理前后缓存根目录所在文件系统的可用空间。
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use wechat_cleaner::cancel::CancellationToken;
+use wechat_cleaner::cleaner::{CleaningPreview, FreeSpace};
+use wechat_cleaner::config::settings::{CleanerSettings, ScannerSettings};
+use wechat_cleaner::file_utils::FileInfo;
+use wechat_cleaner::progress::NoProgress;
+use wechat_cleaner::storage::StorageFormat;
+use wechat_cleaner::{Fs, MemoryFs, RealFs, Scanner};
+
+#[test]
+fn test_preview_and_result_report_disk_usage() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"hello".to_vec(), 1)
+        .add_file("/cache/a(1).pdf", b"hello".to_vec(), 2)
+        .add_file("/cache/v.mp4", vec![7u8; 10_000], 1)
+        .add_file("/cache/v(1).mp4", vec![7u8; 10_000], 2)
+        // 稀疏文件：只分配了一个块
+        .set_allocated("/cache/v(1).mp4", 4096)
+        .set_capacity(1_000_000);
+
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    let result = Scanner::builder().root("/cache").fs(fs.clone()).build().unwrap().scan(&settings).unwrap();
+
+    // 小文件按整块分配，稀疏文件小于逻辑大小
+    let preview = CleaningPreview::from(&result).unwrap();
+    assert_eq!(preview.estimated_freed_space, 5 + 10_000);
+    assert_eq!(preview.estimated_freed_disk_space, 4096 + 4096);
+
+    let before = fs.available_space(Path::new("/cache")).unwrap();
+    let cleaned = preview
+        .clean_with_fs(fs.as_ref(), &CleanerSettings::default(), &NoProgress, &CancellationToken::new())
+        .unwrap();
+    assert_eq!(cleaned.freed_space, 5 + 10_000);
+    assert_eq!(cleaned.freed_disk_space, 4096 + 4096);
+    assert_eq!(cleaned.free_space, [FreeSpace { root: "/cache".into(), before, after: before + 8192 }]);
+    assert_eq!(cleaned.free_space[0].gained(), 8192);
+}
+
+#[test]
+fn test_budget_counts_disk_usage() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/v.mp4", vec![1u8; 10_000], 1)
+        .add_file("/cache/v(1).mp4", vec![1u8; 10_000], 2)
+        .add_file("/cache/w.mp4", vec![2u8; 9_000], 1)
+        .add_file("/cache/w(1).mp4", vec![2u8; 9_000], 3)
+        .set_allocated("/cache/v(1).mp4", 0);
+
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    let result = Scanner::builder().root("/cache").fs(fs).build().unwrap().scan(&settings).unwrap();
+
+    // 不占用磁盘空间的稀疏副本对预算没有帮助，不会被选入
+    let preview = CleaningPreview::with_budget(&result, 1).unwrap();
+    assert_eq!(preview.estimated_files_count, 1);
+    assert_eq!(preview.estimated_freed_disk_space, 12_288);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_real_fs_reports_allocated_blocks() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("sparse.mp4");
+    fs::File::create(&path).unwrap().set_len(16 * 1024 * 1024).unwrap();
+
+    let meta = RealFs.metadata(&path).unwrap();
+    assert_eq!(meta.size, 16 * 1024 * 1024);
+    assert!(meta.allocated.unwrap() < meta.size);
+
+    let file = FileInfo::from_path(&path).unwrap();
+    assert_eq!(file.allocated_size(), meta.allocated.unwrap());
+    assert!(RealFs.available_space(temp_dir.path()).is_some());
+}
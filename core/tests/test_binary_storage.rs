@@ -0,0 +1,144 @@
+//! 二进制扫描结果存储集成测试
+//!
+//! 测试二进制格式的保存与加载、重复组的流式读取，
+//! 以及从二进制文件生成的清理预览与 JSON 格式一致。
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tempfile::TempDir;
+use wechat_cleaner::cleaner::CleaningPreview;
+use wechat_cleaner::config::settings::CleanerSettings;
+use wechat_cleaner::file_utils::FileInfo;
+use wechat_cleaner::scanner::ScanResult;
+use wechat_cleaner::storage::{self, StorageFormat};
+
+/// 通过 JSON 构造 FileInfo（构造函数是私有的）
+fn create_scan_result(temp_dir: &TempDir, file_name: &str) -> ScanResult {
+    let mut duplicate_files = HashMap::new();
+    duplicate_files.insert(
+        "photo".to_string(),
+        vec![
+            FileInfo::new("/cache/2024-01/photo.jpg", 100, 1704067200),
+            FileInfo::new("/cache/2024-01/photo(1).jpg", 100, 1704067300),
+        ],
+    );
+    duplicate_files.insert(
+        "0cc175b9c0f1b6a831c399e269772661".to_string(),
+        vec![
+            FileInfo::new("/cache/2024-02/a.pdf", 50, 1706745600),
+            FileInfo::new("/cache/2024-02/a(1).pdf", 50, 1706745700),
+            FileInfo::new("/cache/2024-02/a(2).pdf", 50, 1706745800),
+        ],
+    );
+
+    ScanResult {
+        path: temp_dir.path().join("wechat-cleaner").join(file_name),
+        total_files_count: 6,
+        duplicate_count: 5,
+        duplicate_files,
+        verified_groups: HashSet::from(["0cc175b9c0f1b6a831c399e269772661".to_string()]),
+        leftover_files: vec![FileInfo::new("/cache/2024-03/video.mp4.part", 0, 1709251200)],
+        scan_time: Duration::from_millis(1200),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_binary_save_and_load_roundtrip() {
+    let temp_dir = TempDir::new().unwrap();
+    let result = create_scan_result(&temp_dir, "scan-result.bin");
+    result.save().unwrap();
+
+    assert_eq!(StorageFormat::detect(&result.path).unwrap(), StorageFormat::Binary);
+
+    let loaded = ScanResult::load(&result.path).unwrap();
+    assert_eq!(loaded.total_files_count, 6);
+    assert_eq!(loaded.scan_time, Duration::from_millis(1200));
+    assert_eq!(loaded.duplicate_files.len(), 2);
+    assert_eq!(loaded.duplicate_files["photo"].len(), 2);
+    assert_eq!(loaded.verified_groups, result.verified_groups);
+    assert_eq!(loaded.leftover_files.len(), 1);
+}
+
+#[test]
+fn test_open_binary_streams_groups() {
+    let temp_dir = TempDir::new().unwrap();
+    let result = create_scan_result(&temp_dir, "scan-result.bin");
+    result.save().unwrap();
+
+    let (header, groups) = storage::open_binary(&result.path).unwrap();
+    assert!(header.duplicate_files.is_empty());
+    assert_eq!(header.duplicate_count, 5);
+    assert_eq!(groups.size_hint(), (2, Some(2)));
+
+    let mut groups: Vec<_> = groups.map(Result::unwrap).collect();
+    groups.sort_by(|a, b| a.key.cmp(&b.key));
+    assert!(groups[0].verified);
+    assert_eq!(groups[0].files.len(), 3);
+    assert_eq!(groups[1].key, "photo");
+    assert!(!groups[1].verified);
+}
+
+#[test]
+fn test_streamed_preview_matches_in_memory_preview() {
+    let temp_dir = TempDir::new().unwrap();
+    let result = create_scan_result(&temp_dir, "scan-result.bin");
+    result.save().unwrap();
+
+    let settings = CleanerSettings {
+        clean_leftovers: true,
+        ..Default::default()
+    };
+    let expected = CleaningPreview::with_settings(&result, &settings).unwrap();
+
+    let (header, groups) = storage::open_binary(&result.path).unwrap();
+    let streamed = CleaningPreview::from_groups(&header, groups, &settings)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(streamed.estimated_files_count, expected.estimated_files_count);
+    assert_eq!(streamed.estimated_freed_space, expected.estimated_freed_space);
+    assert_eq!(streamed.file_groups.len(), expected.file_groups.len());
+    assert_eq!(streamed.leftover_files.len(), 1);
+
+    let (header, groups) = storage::open_binary(&result.path).unwrap();
+    let budget = CleaningPreview::budget_from_groups(&header, groups, 50)
+        .unwrap()
+        .unwrap();
+    assert_eq!(budget.estimated_files_count, 1);
+}
+
+#[test]
+fn test_corrupted_binary_files_return_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    let result = create_scan_result(&temp_dir, "scan-result.bin");
+    result.save().unwrap();
+    let bytes = std::fs::read(&result.path).unwrap();
+    let header_len = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    let groups_start = 8 + header_len + 8;
+    let corrupted = temp_dir.path().join("corrupted.bin");
+
+    // 头部长度远大于文件本身
+    let mut data = bytes.clone();
+    data[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    std::fs::write(&corrupted, &data).unwrap();
+    assert!(storage::open_binary(&corrupted).is_err());
+
+    // 重复组数量远大于文件能容纳的记录数
+    let mut data = bytes.clone();
+    data[8 + header_len..groups_start].copy_from_slice(&u64::MAX.to_le_bytes());
+    std::fs::write(&corrupted, &data).unwrap();
+    assert!(storage::open_binary(&corrupted).is_err());
+
+    // 重复组记录中的组标识长度被改为巨大的值
+    let mut data = bytes.clone();
+    data[groups_start..groups_start + 8].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+    std::fs::write(&corrupted, &data).unwrap();
+    let (_, mut groups) = storage::open_binary(&corrupted).unwrap();
+    assert!(groups.next().unwrap().is_err());
+    assert!(groups.next().is_none());
+
+    // 文件在重复组记录中间被截断
+    std::fs::write(&corrupted, &bytes[..bytes.len() - 10]).unwrap();
+    assert!(ScanResult::load(&corrupted).is_err());
+}
@@ -0,0 +1,133 @@
+//! 协作式取消集成测试
+//!
+//! 在进度事件中触发取消，检查扫描和清理在完成当前文件后停止，
+//! 并返回准确反映已完成操作的部分结果。
+
+use std::sync::Arc;
+use tempfile::TempDir;
+use wechat_cleaner::cleaner::{CleaningPreview, Outcome};
+use wechat_cleaner::config::settings::{CleanerSettings, ScannerSettings};
+use wechat_cleaner::history::ScanHistory;
+use wechat_cleaner::progress::{NoProgress, Phase, ProgressEvent, ProgressSink};
+use wechat_cleaner::storage::StorageFormat;
+use wechat_cleaner::{CancellationToken, MemoryFs, ScanResult, Scanner};
+
+/// 收到指定事件时请求取消
+struct CancelOn<F: Fn(&ProgressEvent) -> bool + Send + Sync> {
+    token: CancellationToken,
+    trigger: F,
+}
+
+impl<F: Fn(&ProgressEvent) -> bool + Send + Sync> ProgressSink for CancelOn<F> {
+    fn on_event(&self, event: &ProgressEvent) {
+        if (self.trigger)(event) {
+            self.token.cancel();
+        }
+    }
+}
+
+fn create_fs() -> Arc<MemoryFs> {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"same".to_vec(), 1)
+        .add_file("/cache/b.pdf", b"same".to_vec(), 2)
+        .add_file("/cache/photo.jpg", b"photo".to_vec(), 3)
+        .add_file("/cache/photo(1).jpg", b"photo".to_vec(), 4)
+        .add_file("/cache/photo(2).jpg", b"photo".to_vec(), 5);
+    fs
+}
+
+fn scanner_settings(save_path: &str) -> ScannerSettings {
+    ScannerSettings {
+        save_path: save_path.into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    }
+}
+
+fn scan(fs: &Arc<MemoryFs>, token: &CancellationToken, progress: &dyn ProgressSink) -> Option<ScanResult> {
+    Scanner::builder()
+        .root("/cache")
+        .fs(fs.clone())
+        .cancellation(token.clone())
+        .build()
+        .unwrap()
+        .scan_with_progress(&scanner_settings("/results"), progress)
+}
+
+#[test]
+fn test_scan_cancelled_before_hashing_keeps_pattern_groups() {
+    let fs = create_fs();
+    let token = CancellationToken::new();
+    let sink = CancelOn {
+        token: token.clone(),
+        trigger: |e: &ProgressEvent| matches!(e, ProgressEvent::PhaseStarted { phase: Phase::Hash, .. }),
+    };
+
+    let result = scan(&fs, &token, &sink).unwrap();
+
+    assert!(result.interrupted);
+    assert_eq!(result.total_files_count, 5);
+    // 模式重复组在哈希之前已经确认，内容重复组没有机会计算
+    assert_eq!(result.duplicate_files.len(), 1);
+    assert_eq!(result.duplicate_files["photo"].len(), 3);
+    assert!(result.verified_groups.is_empty());
+}
+
+#[test]
+fn test_clean_stops_after_current_file() {
+    let fs = create_fs();
+    let result = scan(&fs, &CancellationToken::new(), &NoProgress).unwrap();
+    assert!(!result.interrupted);
+    let preview = CleaningPreview::from(&result).unwrap();
+    assert_eq!(preview.estimated_files_count, 3);
+
+    let token = CancellationToken::new();
+    let sink = CancelOn {
+        token: token.clone(),
+        trigger: |e: &ProgressEvent| matches!(e, ProgressEvent::FileDeleted { .. }),
+    };
+    let settings = CleanerSettings {
+        prune_empty_dirs: true,
+        ..Default::default()
+    };
+    let cleaned = preview.clean_with_fs(fs.as_ref(), &settings, &sink, &token).unwrap();
+
+    assert!(cleaned.cancelled);
+    assert_eq!(cleaned.outcomes.len(), 1);
+    assert_eq!(cleaned.outcomes[0].outcome, Outcome::Deleted);
+    assert!(!fs.exists(&cleaned.outcomes[0].path));
+    assert_eq!(fs.files().len(), 4);
+    assert_eq!(cleaned.empty_dirs_removed, 0);
+}
+
+#[test]
+fn test_cleaning_log_is_saved_and_removed_with_scan() {
+    let temp_dir = TempDir::new().unwrap();
+    let settings = scanner_settings(temp_dir.path().to_str().unwrap());
+    let mut history = ScanHistory::open(&settings).unwrap();
+    let mut result = ScanResult {
+        id: "20240101-120000".to_string(),
+        path: settings.scan_path("20240101-120000"),
+        interrupted: true,
+        ..Default::default()
+    };
+    history.record(&mut result).unwrap();
+
+    let cleaned = wechat_cleaner::cleaner::CleaningResult {
+        files_deleted: 1,
+        cancelled: true,
+        ..Default::default()
+    };
+    let log = history.save_cleaning("20240101", &cleaned).unwrap();
+    let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&log).unwrap()).unwrap();
+    assert_eq!(saved["cancelled"], true);
+    assert_eq!(saved["files_deleted"], 1);
+
+    // 清理记录不影响索引重建，删除扫描时一并删除
+    std::fs::remove_file(settings.history_dir().join("index.json")).unwrap();
+    let mut history = ScanHistory::open(&settings).unwrap();
+    assert_eq!(history.entries().len(), 1);
+    assert!(history.entries()[0].interrupted);
+    history.remove("20240101-120000").unwrap();
+    assert!(!log.exists());
+}
@@ -0,0 +1,109 @@
+//! 空间预算清理集成测试
+//!
+//! 测试 `CleaningPreview::with_budget` 的候选排序与截止逻辑。
+
+use std::collections::{HashMap, HashSet};
+use wechat_cleaner::cleaner::CleaningPreview;
+use wechat_cleaner::file_utils::{FileInfo, HasPath, HasSize};
+use wechat_cleaner::scanner::ScanResult;
+
+/// 通过 JSON 构造 FileInfo（构造函数是私有的）
+/// 一个内容验证组和一个模式组，共 4 个可删除文件
+fn create_scan_result() -> ScanResult {
+    let mut duplicate_files = HashMap::new();
+    duplicate_files.insert(
+        "d41d8cd98f00b204e9800998ecf8427e".to_string(),
+        vec![
+            FileInfo::new("/cache/2024-01/video.mp4", 300, 100),
+            FileInfo::new("/cache/2024-01/video(1).mp4", 300, 500),
+        ],
+    );
+    duplicate_files.insert(
+        "photo".to_string(),
+        vec![
+            FileInfo::new("/cache/2024-02/photo.jpg", 50, 10),
+            FileInfo::new("/cache/2024-02/photo(1).jpg", 200, 20),
+            FileInfo::new("/cache/2024-02/photo(2).jpg", 400, 20),
+            FileInfo::new("/cache/2024-02/photo(3).jpg", 100, 30),
+        ],
+    );
+
+    ScanResult {
+        total_files_count: 6,
+        duplicate_count: 6,
+        duplicate_files,
+        verified_groups: HashSet::from(["d41d8cd98f00b204e9800998ecf8427e".to_string()]),
+        ..Default::default()
+    }
+}
+
+fn deleted_paths(preview: &CleaningPreview) -> Vec<String> {
+    let mut paths: Vec<String> = preview
+        .file_groups
+        .values()
+        .flat_map(|g| g.files_to_delete.iter())
+        .map(|f| f.path().display().to_string())
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn test_budget_prefers_verified_duplicates() {
+    let scan_result = create_scan_result();
+    let preview = CleaningPreview::with_budget(&scan_result, 300).unwrap();
+
+    // 验证组中较新的文件虽然比模式组文件新，仍然最先被选中
+    assert_eq!(deleted_paths(&preview), vec!["/cache/2024-01/video(1).mp4"]);
+    assert_eq!(preview.estimated_freed_space, 300);
+    assert_eq!(preview.target_freed_space, Some(300));
+}
+
+#[test]
+fn test_budget_orders_by_age_then_size() {
+    let scan_result = create_scan_result();
+    let preview = CleaningPreview::with_budget(&scan_result, 400).unwrap();
+
+    // 300 (验证组) 之后选择同龄文件中较大的 photo(2)，累计达到目标后停止
+    assert_eq!(
+        deleted_paths(&preview),
+        vec!["/cache/2024-01/video(1).mp4", "/cache/2024-02/photo(2).jpg"]
+    );
+    assert_eq!(preview.estimated_files_count, 2);
+    assert_eq!(preview.estimated_freed_space, 700);
+}
+
+#[test]
+fn test_budget_larger_than_candidates_selects_all() {
+    let scan_result = create_scan_result();
+    let full = CleaningPreview::from(&scan_result).unwrap();
+    let preview = CleaningPreview::with_budget(&scan_result, u64::MAX).unwrap();
+
+    assert_eq!(deleted_paths(&preview), deleted_paths(&full));
+    assert_eq!(preview.estimated_freed_space, 1000);
+    assert!(preview.estimated_freed_space < preview.target_freed_space.unwrap());
+}
+
+#[test]
+fn test_preview_keeps_groups_sharing_a_folder() {
+    let mut scan_result = create_scan_result();
+    scan_result.duplicate_files.insert(
+        "voice".to_string(),
+        vec![
+            FileInfo::new("/cache/2024-02/voice.amr", 10, 5),
+            FileInfo::new("/cache/2024-02/voice(1).amr", 10, 6),
+        ],
+    );
+
+    // 同一文件夹下的两个重复组各自保留一个文件
+    let preview = CleaningPreview::from(&scan_result).unwrap();
+    assert_eq!(preview.file_groups.len(), 3);
+    assert_eq!(preview.estimated_files_count, 5);
+    let total: u64 = preview
+        .file_groups
+        .values()
+        .flat_map(|g| g.files_to_delete.iter())
+        .map(|f| f.size())
+        .sum();
+    assert_eq!(preview.estimated_freed_space, total);
+}
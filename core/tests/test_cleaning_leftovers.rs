@@ -0,0 +1,182 @@
+//! 残留文件与空目录清理集成测试
+//!
+//! 在临时目录中构造缓存结构，测试扫描阶段的残留文件归类、
+//! 残留文件的清理开关和最短存在时间，以及清理后的空目录删除逻辑。
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+use wechat_cleaner::cleaner::CleaningPreview;
+use wechat_cleaner::config::settings::{CleanerSettings, ScannerSettings, WechatSettings};
+use wechat_cleaner::file_utils::{prune_empty_dirs, HasPath, Named};
+use wechat_cleaner::scanner::FileScanner;
+use wechat_cleaner::storage::StorageFormat;
+
+fn write(root: &Path, name: &str, content: &[u8]) {
+    let path = root.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+/// 构造缓存目录：一个只含副本的月份目录、一个含残留文件的月份目录
+fn create_cache() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let cache = temp_dir.path().join("msg/file");
+    write(&cache, "2024-01/report.pdf", b"report");
+    write(&cache, "2024-02/nested/photo.jpg", b"photo");
+    write(&cache, "2024-02/nested/photo(1).jpg", b"photo");
+    write(&cache, "2024-03/video.mp4.part", b"partial");
+    write(&cache, "2024-03/empty.txt", b"");
+    temp_dir
+}
+
+/// 清理残留文件且不限制存在时间的设置
+fn with_leftovers() -> CleanerSettings {
+    CleanerSettings {
+        clean_leftovers: true,
+        leftover_min_age_hours: 0,
+        ..Default::default()
+    }
+}
+
+fn scan(temp_dir: &TempDir) -> CleaningPreview {
+    scan_with(temp_dir, &with_leftovers())
+}
+
+fn scan_with(temp_dir: &TempDir, settings: &CleanerSettings) -> CleaningPreview {
+    let wechat = WechatSettings {
+        cache_path: Some(temp_dir.path().join("msg/file")),
+        cache_patterns: r"\(\d+\)\.[a-zA-Z0-9]+$".to_string(),
+        ..Default::default()
+    };
+    let scanner_settings = ScannerSettings {
+        save_path: temp_dir.path().join("results"),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    let scan_result = FileScanner::new(&wechat).unwrap().scan(&scanner_settings).unwrap();
+    CleaningPreview::with_settings(&scan_result, settings).unwrap()
+}
+
+#[test]
+fn test_scan_reports_leftover_files() {
+    let temp_dir = create_cache();
+    let preview = scan(&temp_dir);
+
+    let mut leftovers: Vec<String> = preview
+        .leftover_files
+        .iter()
+        .filter_map(|f| f.name().map(|n| n.into_owned()))
+        .collect();
+    leftovers.sort();
+
+    assert_eq!(leftovers, vec!["empty.txt", "video.mp4.part"]);
+    // 1 个副本 + 2 个残留文件
+    assert_eq!(preview.estimated_files_count, 3);
+    assert_eq!(preview.roots, vec![temp_dir.path().join("msg/file")]);
+}
+
+#[test]
+fn test_clean_prunes_new_empty_dirs_but_keeps_root() {
+    let temp_dir = create_cache();
+    let cache = temp_dir.path().join("msg/file");
+    fs::create_dir_all(cache.join("2024-04")).unwrap();
+    let preview = scan(&temp_dir);
+
+    let settings = CleanerSettings {
+        prune_empty_dirs: true,
+        ..Default::default()
+    };
+    let result = preview.clean(&settings).unwrap();
+
+    assert_eq!(result.files_deleted, 1);
+    assert_eq!(result.leftover_files_deleted, 2);
+    assert_eq!(result.empty_dirs_removed, 1);
+    assert!(!cache.join("2024-03").exists());
+    // 副本所在目录仍保留一个文件，因此不会被删除
+    let remaining = fs::read_dir(cache.join("2024-02/nested")).unwrap().count();
+    assert_eq!(remaining, 1);
+    // 清理前已存在的空目录不受影响
+    assert!(cache.join("2024-04").exists());
+    assert!(cache.exists());
+}
+
+#[test]
+fn test_clean_without_pruning_keeps_dirs() {
+    let temp_dir = create_cache();
+    let preview = scan(&temp_dir);
+    let leftover = preview.leftover_files[0].path().clone();
+
+    let result = preview.clean(&CleanerSettings::default()).unwrap();
+
+    assert_eq!(result.empty_dirs_removed, 0);
+    assert!(!leftover.exists());
+    assert!(temp_dir.path().join("msg/file/2024-03").exists());
+}
+
+#[test]
+fn test_clean_continues_after_failed_deletion() {
+    let temp_dir = create_cache();
+    let preview = scan(&temp_dir);
+    // 扫描之后文件被外部删除，清理时删除失败
+    let missing = preview.leftover_files[0].path().clone();
+    fs::remove_file(&missing).unwrap();
+
+    let result = preview.clean(&CleanerSettings::default()).unwrap();
+
+    assert!(result.is_partial());
+    let failures: Vec<_> = result.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].path, missing);
+    assert_eq!(result.files_deleted, 1);
+    assert_eq!(result.leftover_files_deleted, 1);
+}
+
+#[test]
+fn test_leftovers_are_kept_by_default() {
+    let temp_dir = create_cache();
+    let preview = scan_with(&temp_dir, &CleanerSettings::default());
+
+    assert!(preview.leftover_files.is_empty());
+    // 只有 1 个副本
+    assert_eq!(preview.estimated_files_count, 1);
+
+    preview.clean(&CleanerSettings::default()).unwrap();
+    let cache = temp_dir.path().join("msg/file");
+    assert!(cache.join("2024-03/video.mp4.part").exists());
+    assert!(cache.join("2024-03/empty.txt").exists());
+}
+
+#[test]
+fn test_recent_leftovers_are_not_cleaned() {
+    let temp_dir = create_cache();
+    // 未完成的下载一天半之前就不再变化，空文件刚刚创建
+    let stale = temp_dir.path().join("msg/file/2024-03/video.mp4.part");
+    let modified = SystemTime::now() - Duration::from_secs(36 * 3600);
+    fs::File::options().write(true).open(&stale).unwrap().set_modified(modified).unwrap();
+
+    let settings = CleanerSettings {
+        clean_leftovers: true,
+        ..Default::default()
+    };
+    let preview = scan_with(&temp_dir, &settings);
+
+    let leftovers: Vec<_> = preview.leftover_files.iter().map(|f| f.path().clone()).collect();
+    assert_eq!(leftovers, vec![stale]);
+}
+
+#[test]
+fn test_prune_visits_each_dir_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    for dir in ["a/x", "a/y", "b"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+
+    // 同一目录多次出现且不相邻时也只处理一次
+    let dirs = ["a/x", "b", "a/y", "a/x", "b"].map(|dir| root.join(dir));
+    let mut removed = prune_empty_dirs(dirs, std::slice::from_ref(&root));
+    removed.sort();
+    assert_eq!(removed, ["a", "a/x", "a/y", "b"].map(|dir| root.join(dir)));
+}
@@ -6,7 +6,6 @@
 #[cfg(feature = "display")]
 mod display_tests {
     use std::collections::HashMap;
-    use std::path::PathBuf;
     use std::fs;
     use std::io::Write;
     use tempfile::TempDir;
@@ -56,7 +55,7 @@ mod display_tests {
             files_to_delete: files_to_delete1,
         };
         
-        file_groups.insert(PathBuf::from(temp_dir.path().join("dir1")), group1);
+        file_groups.insert(temp_dir.path().join("dir1"), group1);
         
         // 创建第二个预览组
         let content2 = b"test document content with more data to make it larger";
@@ -70,12 +69,13 @@ mod display_tests {
             files_to_delete: files_to_delete2,
         };
         
-        file_groups.insert(PathBuf::from(temp_dir.path().join("dir2")), group2);
+        file_groups.insert(temp_dir.path().join("dir2"), group2);
         
         let preview = CleaningPreview {
             estimated_files_count: 3,
             estimated_freed_space: content1.len() as u64 * 2 + content2.len() as u64,
             file_groups,
+            ..Default::default()
         };
         
         (preview, temp_dir)
@@ -160,7 +160,7 @@ mod display_tests {
         let summary = group.display_summary();
         
         // 验证包含删除文件列表的数量
-        assert!(summary.contains("删除文件列表: 2 items"));
+        assert!(summary.contains("删除文件列表: 2 项"));
         
         println!("PreviewGroup Summary:");
         println!("{}", summary);
@@ -197,6 +197,7 @@ mod display_tests {
             estimated_files_count: 0,
             estimated_freed_space: 0,
             file_groups: HashMap::new(),
+            ..Default::default()
         };
         
         let summary = empty_preview.display_summary();
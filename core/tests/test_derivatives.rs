@@ -0,0 +1,76 @@
+//! 缩略图等派生文件识别集成测试
+//!
+//! 测试派生文件与原始文件的配对规则，以及清理预览中的派生文件类别。
+
+use wechat_cleaner::cleaner::CleaningPreview;
+use wechat_cleaner::config::settings::CleanerSettings;
+use wechat_cleaner::file_utils::{FileFilter, FileInfo, Named, DERIVATIVE_RULES};
+use wechat_cleaner::scanner::ScanResult;
+
+/// 通过 JSON 构造 FileInfo（构造函数是私有的）
+fn file_info(path: &str) -> FileInfo {
+    FileInfo::new(path, 10, 1640995200)
+}
+
+fn names(files: &[FileInfo]) -> Vec<String> {
+    let mut names: Vec<String> = files
+        .iter()
+        .filter_map(|f| f.name().map(|n| n.into_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_split_derivatives_pairs_by_name_and_directory() {
+    let files = vec![
+        file_info("/cache/2024-01/video.mp4"),
+        file_info("/cache/2024-01/video_thumb.jpg"),
+        file_info("/cache/2024-01/th_photo.jpg"),
+        file_info("/cache/2024-01/image_t.png"),
+        file_info("/cache/2024-01/report_t.pdf"),
+        file_info("/cache/2024-02/image.png"),
+    ];
+
+    let derivatives = files.split_derivatives(DERIVATIVE_RULES);
+
+    assert_eq!(names(&derivatives.attached), vec!["video_thumb.jpg"]);
+    // image.png 在另一个目录中，不能作为 image_t.png 的原始文件
+    assert_eq!(names(&derivatives.orphaned), vec!["image_t.png", "th_photo.jpg"]);
+    // 非图片类型的文件不按缩略图规则处理
+    assert_eq!(
+        names(&derivatives.others),
+        vec!["image.png", "report_t.pdf", "video.mp4"]
+    );
+}
+
+#[test]
+fn test_preview_includes_attached_derivatives_only_when_requested() {
+    let scan_result = ScanResult {
+        orphaned_derivatives: vec![file_info("/cache/2024-01/th_photo.jpg")],
+        attached_derivatives: vec![file_info("/cache/2024-01/video_thumb.jpg")],
+        ..Default::default()
+    };
+
+    // 缩略图只按文件名识别，默认不清理
+    assert!(CleaningPreview::from(&scan_result).is_none());
+
+    let settings = CleanerSettings {
+        clean_orphaned_thumbnails: true,
+        ..Default::default()
+    };
+    let preview = CleaningPreview::with_settings(&scan_result, &settings).unwrap();
+    assert_eq!(names(&preview.derivative_files), vec!["th_photo.jpg"]);
+    assert_eq!(preview.estimated_files_count, 1);
+
+    let settings = CleanerSettings {
+        keep_only_originals: true,
+        ..Default::default()
+    };
+    let preview = CleaningPreview::with_settings(&scan_result, &settings).unwrap();
+    assert_eq!(
+        names(&preview.derivative_files),
+        vec!["th_photo.jpg", "video_thumb.jpg"]
+    );
+    assert_eq!(preview.estimated_freed_space, 20);
+}
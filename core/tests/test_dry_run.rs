@@ -0,0 +1,100 @@
+//! 模拟清理集成测试
+//!
+//! 测试 `CleaningPreview::simulate` 完整执行清理流程但不修改文件系统，
+//! 以及删除前的重新校验和逐文件结果。
+
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use wechat_cleaner::cleaner::{CleaningPreview, Outcome};
+use wechat_cleaner::config::settings::{CleanerSettings, ScannerSettings, WechatSettings};
+use wechat_cleaner::file_utils::HasPath;
+use wechat_cleaner::progress::NoProgress;
+use wechat_cleaner::scanner::FileScanner;
+use wechat_cleaner::storage::StorageFormat;
+
+fn write(root: &Path, name: &str, content: &[u8]) {
+    let path = root.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+/// 构造缓存目录并扫描：一组副本和一个单独目录中的残留文件
+fn scan(temp_dir: &TempDir) -> CleaningPreview {
+    let cache = temp_dir.path().join("msg/file");
+    write(&cache, "2024-01/photo.jpg", b"photo");
+    write(&cache, "2024-01/photo(1).jpg", b"photo");
+    write(&cache, "2024-02/video.mp4.part", b"partial");
+
+    let wechat = WechatSettings {
+        cache_path: Some(cache),
+        cache_patterns: r"\(\d+\)\.[a-zA-Z0-9]+$".to_string(),
+        ..Default::default()
+    };
+    let scanner_settings = ScannerSettings {
+        save_path: temp_dir.path().join("results"),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    let scan_result = FileScanner::new(&wechat).unwrap().scan(&scanner_settings).unwrap();
+    CleaningPreview::with_settings(&scan_result, &settings()).unwrap()
+}
+
+fn settings() -> CleanerSettings {
+    CleanerSettings {
+        prune_empty_dirs: true,
+        clean_leftovers: true,
+        leftover_min_age_hours: 0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_simulate_reports_full_result_without_touching_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let preview = scan(&temp_dir);
+
+    let result = preview.simulate(&settings(), &NoProgress).unwrap();
+
+    assert!(result.simulated);
+    assert_eq!(result.files_deleted, 1);
+    assert_eq!(result.leftover_files_deleted, 1);
+    // 残留文件所在目录在模拟中变为空目录
+    assert_eq!(result.empty_dirs_removed, 1);
+    assert!(result.outcomes.iter().all(|o| o.outcome == Outcome::Deleted));
+
+    let cache = temp_dir.path().join("msg/file");
+    assert!(cache.join("2024-01/photo(1).jpg").exists());
+    assert!(cache.join("2024-02/video.mp4.part").exists());
+}
+
+#[test]
+fn test_simulate_matches_real_clean() {
+    let temp_dir = TempDir::new().unwrap();
+    let preview = scan(&temp_dir);
+
+    let simulated = preview.simulate(&settings(), &NoProgress).unwrap();
+    let real = preview.clean(&settings()).unwrap();
+
+    assert!(!real.simulated);
+    assert_eq!(simulated.files_deleted, real.files_deleted);
+    assert_eq!(simulated.freed_space, real.freed_space);
+    assert_eq!(simulated.empty_dirs_removed, real.empty_dirs_removed);
+    assert!(!temp_dir.path().join("msg/file/2024-02").exists());
+}
+
+#[test]
+fn test_changed_file_is_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    let preview = scan(&temp_dir);
+    let leftover = preview.leftover_files[0].path().clone();
+    fs::write(&leftover, b"download finished").unwrap();
+
+    let result = preview.simulate(&settings(), &NoProgress).unwrap();
+
+    assert_eq!(result.leftover_files_deleted, 0);
+    assert!(!result.is_partial());
+    let skipped: Vec<_> = result.skipped().collect();
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].path, leftover);
+}
@@ -0,0 +1,102 @@
+//! 文件系统边界集成测试
+//!
+//! 默认只扫描缓存根目录所在的文件系统；扫描结果记录每个根目录的设备号，
+//! 清理时只删除允许设备上的文件，且保留的文件与删除的文件位于同一设备。
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use wechat_cleaner::cancel::CancellationToken;
+use wechat_cleaner::cleaner::CleaningPreview;
+use wechat_cleaner::config::settings::{CleanerSettings, ScannerSettings};
+use wechat_cleaner::file_utils::HasPath;
+use wechat_cleaner::filesystem::MemoryFs;
+use wechat_cleaner::progress::NoProgress;
+use wechat_cleaner::storage::StorageFormat;
+use wechat_cleaner::{ScanResult, Scanner};
+
+fn cache_with_mount() -> Arc<MemoryFs> {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"same".to_vec(), 1)
+        .add_file("/cache/a(1).pdf", b"same".to_vec(), 2)
+        .add_file("/cache/backup/a.pdf", b"same".to_vec(), 3)
+        .add_file("/cache/backup/a(1).pdf", b"same".to_vec(), 4)
+        .mount("/cache/backup", 2);
+    fs
+}
+
+fn scan(fs: &Arc<MemoryFs>, one_file_system: bool) -> ScanResult {
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    Scanner::builder()
+        .root("/cache")
+        .fs(fs.clone())
+        .one_file_system(one_file_system)
+        .build()
+        .unwrap()
+        .scan(&settings)
+        .unwrap()
+}
+
+#[test]
+fn test_mounted_file_system_is_skipped_by_default() {
+    let fs = cache_with_mount();
+
+    let result = scan(&fs, true);
+    assert_eq!(result.total_files_count, 2);
+    assert_eq!(result.root_devices[Path::new("/cache")], 1);
+    assert_eq!(result.devices, BTreeSet::from([1]));
+
+    let result = scan(&fs, false);
+    assert_eq!(result.total_files_count, 4);
+    assert_eq!(result.devices, BTreeSet::from([1, 2]));
+}
+
+#[test]
+fn test_keepers_and_deletions_share_a_device() {
+    let fs = cache_with_mount();
+    let result = scan(&fs, false);
+
+    // 每个设备各保留一个文件，不会为了另一个设备上的副本删除唯一的文件
+    let preview = CleaningPreview::from(&result).unwrap();
+    assert_eq!(preview.estimated_files_count, 2);
+    for group in preview.file_groups.values() {
+        let device = group.file_to_keep.id().unwrap().device;
+        assert!(group.files_to_delete.iter().all(|file| file.id().unwrap().device == device));
+    }
+}
+
+#[test]
+fn test_files_off_allowed_devices_are_not_cleaned() {
+    let fs = cache_with_mount();
+    let mut result = scan(&fs, false);
+    result.devices = BTreeSet::from([1]);
+
+    let preview = CleaningPreview::from(&result).unwrap();
+    assert_eq!(preview.estimated_files_count, 1);
+    let mut off_device: Vec<&PathBuf> = preview.off_device_files.iter().map(|file| file.path()).collect();
+    off_device.sort();
+    assert_eq!(off_device, [Path::new("/cache/backup/a(1).pdf"), Path::new("/cache/backup/a.pdf")]);
+}
+
+#[test]
+fn test_clean_skips_files_that_moved_to_another_device() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"same".to_vec(), 1).add_file("/cache/sub/a.pdf", b"same".to_vec(), 2);
+    fs.add_file("/cache/sub/a(1).pdf", b"same".to_vec(), 3);
+    let result = scan(&fs, true);
+    let preview = CleaningPreview::from(&result).unwrap();
+    assert_eq!(preview.estimated_files_count, 1);
+
+    // 扫描后有文件系统挂载到了 /cache/sub
+    fs.mount("/cache/sub", 2);
+    let cleaned = preview
+        .clean_with_fs(fs.as_ref(), &CleanerSettings::default(), &NoProgress, &CancellationToken::new())
+        .unwrap();
+    assert_eq!(cleaned.files_deleted, 0);
+    assert_eq!(cleaned.skipped().count(), 1);
+    assert!(fs.exists("/cache/sub/a.pdf") && fs.exists("/cache/sub/a(1).pdf"));
+}
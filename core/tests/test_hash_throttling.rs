@@ -0,0 +1,141 @@
+//! 哈希读取方式集成测试
+//!
+//! 检查扫描设置中的线程数、读取带宽和机械硬盘模式对计算哈希的影响。
+
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use wechat_cleaner::config::settings::ScannerSettings;
+use wechat_cleaner::throttle::Throttle;
+use wechat_cleaner::{ContentHasher, Md5Hasher, MemoryFs, ScanResult, Scanner};
+
+/// 记录读取顺序和最大并发数的哈希算法
+#[derive(Clone, Default)]
+struct RecordingHasher {
+    order: Arc<Mutex<Vec<Vec<u8>>>>,
+    active: Arc<AtomicUsize>,
+    max_active: Arc<AtomicUsize>,
+}
+
+impl ContentHasher for RecordingHasher {
+    fn name(&self) -> &str {
+        "md5"
+    }
+
+    fn hash_reader(&self, reader: &mut dyn Read) -> io::Result<String> {
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_active.fetch_max(active, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(10));
+
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        let digest = Md5Hasher.hash_reader(&mut content.as_slice());
+        self.order.lock().unwrap().push(content);
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        digest
+    }
+}
+
+fn create_fs() -> Arc<MemoryFs> {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/d/4.dat", b"dd".to_vec(), 1)
+        .add_file("/cache/b/2.dat", b"bb".to_vec(), 2)
+        .add_file("/cache/c/3.dat", b"cc".to_vec(), 3)
+        .add_file("/cache/a/1.dat", b"aa".to_vec(), 4)
+        .add_file("/cache/a/5.dat", b"aa".to_vec(), 5);
+    fs
+}
+
+fn scan(fs: &Arc<MemoryFs>, hasher: &RecordingHasher, settings: ScannerSettings) -> ScanResult {
+    Scanner::builder()
+        .root("/cache")
+        .fs(fs.clone())
+        .hasher(hasher.clone())
+        .build()
+        .unwrap()
+        .scan(&settings)
+        .unwrap()
+}
+
+#[test]
+fn test_hdd_mode_hashes_sequentially_in_order() {
+    let hasher = RecordingHasher::default();
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        hdd_mode: true,
+        ..Default::default()
+    };
+    let result = scan(&create_fs(), &hasher, settings);
+
+    assert_eq!(result.verified_groups.len(), 1);
+    assert_eq!(hasher.max_active.load(Ordering::SeqCst), 1);
+    // 内存文件系统没有磁盘位置信息，按路径顺序读取
+    let order = hasher.order.lock().unwrap().clone();
+    let expected: Vec<Vec<u8>> = ["aa", "aa", "bb", "cc", "dd"].iter().map(|s| s.as_bytes().to_vec()).collect();
+    assert_eq!(order, expected);
+}
+
+#[test]
+fn test_thread_limit_bounds_concurrency() {
+    let hasher = RecordingHasher::default();
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        threads: Some(1),
+        ..Default::default()
+    };
+    let result = scan(&create_fs(), &hasher, settings);
+
+    assert_eq!(result.verified_groups.len(), 1);
+    assert_eq!(hasher.order.lock().unwrap().len(), 5);
+    assert_eq!(hasher.max_active.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_read_bandwidth_limit() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.bin", vec![1; 256 * 1024], 1)
+        .add_file("/cache/b.bin", vec![1; 256 * 1024], 2);
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        max_read_mbps: Some(1),
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    let result = scan(&fs, &RecordingHasher::default(), settings);
+
+    // 1 MB/s 读取 512 KB 至少需要约 0.5 秒
+    assert_eq!(result.verified_groups.len(), 1);
+    assert!(start.elapsed() >= Duration::from_millis(450));
+}
+
+#[test]
+fn test_zero_bandwidth_means_unlimited() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.bin", vec![1; 256 * 1024], 1)
+        .add_file("/cache/b.bin", vec![1; 256 * 1024], 2);
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        max_read_mbps: Some(0),
+        ..Default::default()
+    };
+    assert_eq!(settings.read_limit_mbps(), None);
+
+    let start = Instant::now();
+    let result = scan(&fs, &RecordingHasher::default(), settings);
+
+    assert_eq!(result.verified_groups.len(), 1);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_throttle_delays_consumer() {
+    let throttle = Throttle::new(1000);
+    let start = Instant::now();
+    throttle.consume(100);
+    throttle.consume(100);
+    assert!(start.elapsed() >= Duration::from_millis(180));
+    assert_eq!(throttle.bytes_per_sec(), 1000);
+}
@@ -0,0 +1,220 @@
+//! 硬链接与符号链接集成测试
+//!
+//! 指向同一 inode 的路径合并为一个逻辑文件，不会被当作重复文件；
+//! 预计释放空间只统计删除后真正释放的数据块；符号链接按配置跳过、跟随或列出。
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+use wechat_cleaner::cancel::CancellationToken;
+use wechat_cleaner::cleaner::CleaningPreview;
+use wechat_cleaner::config::settings::{CleanerSettings, ScannerSettings};
+use wechat_cleaner::file_utils::{FileInfo, HasPath};
+use wechat_cleaner::filesystem::{MemoryFs, SymlinkPolicy, WalkOptions};
+use wechat_cleaner::progress::NoProgress;
+use wechat_cleaner::storage::{self, StorageFormat};
+use wechat_cleaner::{Fs, ScanResult, Scanner};
+
+fn scan(fs: &Arc<MemoryFs>) -> ScanResult {
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    Scanner::builder().root("/cache").fs(fs.clone()).build().unwrap().scan(&settings).unwrap()
+}
+
+#[test]
+fn test_hard_links_are_collapsed_into_one_file() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"same".to_vec(), 1)
+        .add_file("/cache/b.pdf", b"same".to_vec(), 2)
+        .add_hard_link("/cache/b.pdf", "/cache/c.pdf")
+        .add_file("/cache/d.doc", b"alone".to_vec(), 3)
+        .add_hard_link("/cache/d.doc", "/cache/e.doc");
+
+    let meta = fs.metadata(Path::new("/cache/c.pdf")).unwrap();
+    assert_eq!(meta.links, 2);
+    assert_eq!(meta.id, fs.metadata(Path::new("/cache/b.pdf")).unwrap().id);
+
+    let result = scan(&fs);
+    assert_eq!(result.total_files_count, 3);
+    assert_eq!(result.hard_links, 2);
+    // d.doc 与 e.doc 是同一个文件，不是重复文件
+    assert_eq!(result.duplicate_files.len(), 1);
+    let group = result.duplicate_files.values().next().unwrap();
+    let b = group.iter().find(|file| file.path() == Path::new("/cache/b.pdf")).unwrap();
+    assert_eq!(b.hard_links(), [PathBuf::from("/cache/c.pdf")]);
+    assert_eq!(b.freed_size(), 4);
+
+    // 删除 b.pdf 时一并删除 c.pdf，才能真正释放空间；两个路径都计入预计删除数并在预览中列出
+    let preview = CleaningPreview::from(&result).unwrap();
+    assert_eq!(preview.estimated_files_count, 2);
+    assert_eq!(preview.estimated_freed_space, 4);
+    #[cfg(feature = "display")]
+    {
+        use wechat_cleaner::display::Display;
+        let details = preview.display_details();
+        assert!(details.contains("/cache/b.pdf"));
+        assert!(details.contains("/cache/c.pdf"));
+    }
+    let cleaned = preview
+        .clean_with_fs(fs.as_ref(), &CleanerSettings::default(), &NoProgress, &CancellationToken::new())
+        .unwrap();
+    assert_eq!(cleaned.files_deleted, 2);
+    assert_eq!(cleaned.freed_space, 4);
+    let mut paths: Vec<_> = cleaned.outcomes.iter().map(|o| o.path.clone()).collect();
+    paths.sort();
+    assert_eq!(paths, [PathBuf::from("/cache/b.pdf"), PathBuf::from("/cache/c.pdf")]);
+    assert!(fs.exists("/cache/a.pdf"));
+    assert!(!fs.exists("/cache/b.pdf"));
+    assert!(!fs.exists("/cache/c.pdf"));
+}
+
+#[test]
+fn test_links_outside_scan_free_nothing() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"same".to_vec(), 1)
+        .add_file("/cache/b.pdf", b"same".to_vec(), 2)
+        .add_hard_link("/cache/b.pdf", "/backup/b.pdf");
+
+    let result = scan(&fs);
+    assert_eq!(result.hard_links, 0);
+    let preview = CleaningPreview::from(&result).unwrap();
+    assert_eq!(preview.estimated_files_count, 1);
+    assert_eq!(preview.estimated_freed_space, 0);
+
+    // 空间预算模式不会选择删除后不释放空间的文件
+    assert!(CleaningPreview::with_budget(&result, 1).is_none());
+
+    let cleaned = preview
+        .clean_with_fs(fs.as_ref(), &CleanerSettings::default(), &NoProgress, &CancellationToken::new())
+        .unwrap();
+    assert_eq!(cleaned.files_deleted, 1);
+    assert_eq!(cleaned.freed_space, 0);
+    assert!(fs.exists("/backup/b.pdf"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_policies() {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new().unwrap();
+    let cache = temp_dir.path().join("cache");
+    let outside = temp_dir.path().join("outside");
+    fs::create_dir_all(&cache).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(cache.join("a.txt"), "hello").unwrap();
+    fs::write(outside.join("o.txt"), "world").unwrap();
+    symlink(cache.join("a.txt"), cache.join("link_a")).unwrap();
+    symlink(outside.join("o.txt"), cache.join("link_o")).unwrap();
+
+    let collect = |symlinks| {
+        let collected = FileInfo::collect_with(
+            &wechat_cleaner::RealFs,
+            &cache,
+            &WalkOptions { symlinks, ..Default::default() },
+            &NoProgress,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        let mut links = collected.symlinks;
+        links.sort();
+        (collected.files, links)
+    };
+
+    let (files, links) = collect(SymlinkPolicy::Skip);
+    assert_eq!(files.len(), 1);
+    assert!(links.is_empty());
+
+    let (files, links) = collect(SymlinkPolicy::Report);
+    assert_eq!(files.len(), 1);
+    assert_eq!(links, [cache.join("link_a"), cache.join("link_o")]);
+
+    // 指向扫描范围内文件的链接并入目标文件，指向范围外的链接只列出
+    let (files, links) = collect(SymlinkPolicy::Follow);
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path(), &cache.join("a.txt"));
+    assert_eq!(files[0].hard_links(), [cache.join("link_a")]);
+    assert_eq!(links, [cache.join("link_o")]);
+}
+
+#[cfg(unix)]
+fn collect_following(root: &Path) -> (Vec<FileInfo>, Vec<PathBuf>) {
+    let collected = FileInfo::collect_with(
+        &wechat_cleaner::RealFs,
+        root,
+        &WalkOptions { symlinks: SymlinkPolicy::Follow, ..Default::default() },
+        &NoProgress,
+        &CancellationToken::new(),
+    )
+    .unwrap();
+    let mut links = collected.symlinks;
+    links.sort();
+    (collected.files, links)
+}
+
+#[cfg(unix)]
+#[test]
+fn test_follow_directory_symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new().unwrap();
+    let cache = temp_dir.path().join("cache");
+    let outside = temp_dir.path().join("outside");
+    fs::create_dir_all(cache.join("real")).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(cache.join("real/a.jpg"), "same").unwrap();
+    fs::write(outside.join("a.jpg"), "same").unwrap();
+    symlink(&outside, cache.join("linked_out")).unwrap();
+    symlink(cache.join("real"), cache.join("linked_in")).unwrap();
+
+    // 经由目录链接到达的文件按真实路径判断是否在扫描范围内
+    let (files, links) = collect_following(&cache);
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path(), &cache.join("real/a.jpg"));
+    assert_eq!(files[0].hard_links(), [cache.join("linked_in/a.jpg")]);
+    assert_eq!(files[0].freed_size(), 4);
+    assert_eq!(links, [cache.join("linked_out/a.jpg")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_followed_symlinks_do_not_count_as_hard_links() {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new().unwrap();
+    let cache = temp_dir.path().join("cache");
+    let backup = temp_dir.path().join("backup");
+    fs::create_dir_all(&cache).unwrap();
+    fs::create_dir_all(&backup).unwrap();
+    fs::write(cache.join("a.txt"), "hello").unwrap();
+    fs::hard_link(cache.join("a.txt"), backup.join("a.txt")).unwrap();
+    symlink(cache.join("a.txt"), cache.join("link_a")).unwrap();
+
+    // 范围外还有硬链接，范围内的符号链接不能抵消它
+    let (files, _) = collect_following(&cache);
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].hard_links(), [cache.join("link_a")]);
+    assert_eq!(files[0].freed_size(), 0);
+}
+
+#[test]
+fn test_outdated_binary_result_requires_rescan() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("old.bin");
+    let header = br#"{"format_version": 5}"#;
+
+    let mut file = fs::File::create(&path).unwrap();
+    file.write_all(b"WCSR").unwrap();
+    file.write_all(&(header.len() as u32).to_le_bytes()).unwrap();
+    file.write_all(header).unwrap();
+    file.write_all(&0u64.to_le_bytes()).unwrap();
+    drop(file);
+
+    let error = storage::open_binary(&path).err().unwrap();
+    assert!(error.to_string().contains("请重新扫描"), "{error}");
+}
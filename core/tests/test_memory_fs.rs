@@ -0,0 +1,159 @@
+//! 内存文件系统集成测试
+//!
+//! 用 `MemoryFs` 构造合成的缓存目录树，测试扫描、预览和清理逻辑，
+//! 包括受控的修改时间和注入的 I/O 错误。
+
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::Arc;
+use wechat_cleaner::cancel::CancellationToken;
+use wechat_cleaner::cleaner::{CleaningPreview, Outcome};
+use wechat_cleaner::config::settings::{CleanerSettings, ScannerSettings, WechatSettings};
+use wechat_cleaner::file_utils::{prune_empty_dirs_with, FileInfo, FileProcessor, HasPath};
+use wechat_cleaner::filesystem::{Fs, MemoryFs};
+use wechat_cleaner::progress::NoProgress;
+use wechat_cleaner::scanner::{FileScanner, ScanResult};
+use wechat_cleaner::storage::StorageFormat;
+
+const ROOT: &str = "/wechat/wxid_a/msg/file";
+
+fn scan(fs: &Arc<MemoryFs>) -> ScanResult {
+    let wechat = WechatSettings {
+        cache_path: Some(ROOT.into()),
+        cache_patterns: r"\(\d+\)\.[a-zA-Z0-9]+$".to_string(),
+        ..Default::default()
+    };
+    let scanner_settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    FileScanner::with_fs(&wechat, fs.clone())
+        .unwrap()
+        .scan_with_progress(&scanner_settings, &NoProgress)
+        .unwrap()
+}
+
+/// 两个内容相同但文件名无关的文件、一组模式副本、一个隐藏目录中的文件
+fn create_tree() -> Arc<MemoryFs> {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file(format!("{ROOT}/2024-01/a.pdf"), b"same content".to_vec(), 1704067200)
+        .add_file(format!("{ROOT}/2024-01/b.pdf"), b"same content".to_vec(), 1704067100)
+        .add_file(format!("{ROOT}/2024-01/c.pdf"), b"different!!!".to_vec(), 1704067300)
+        .add_file(format!("{ROOT}/2024-02/photo.jpg"), b"photo".to_vec(), 1706745600)
+        .add_file(format!("{ROOT}/2024-02/photo(1).jpg"), b"photo".to_vec(), 1706745700)
+        .add_file(format!("{ROOT}/.cache/a.pdf"), b"same content".to_vec(), 1704067200);
+    fs
+}
+
+#[test]
+fn test_scan_synthetic_tree() {
+    let fs = create_tree();
+    let result = scan(&fs);
+
+    // 隐藏目录中的文件不参与扫描
+    assert_eq!(result.total_files_count, 5);
+    assert_eq!(result.duplicate_files.len(), 2);
+    assert_eq!(result.verified_groups.len(), 1);
+    assert_eq!(result.duplicate_files["photo"].len(), 2);
+    assert_eq!(result.usage[0].account.as_deref(), Some("wxid_a"));
+}
+
+#[test]
+fn test_preview_keeps_oldest_file() {
+    let fs = create_tree();
+    let preview = CleaningPreview::from(&scan(&fs)).unwrap();
+
+    let keepers: Vec<&Path> = preview.file_groups.keys().map(|p| p.as_path()).collect();
+    assert!(keepers.contains(&Path::new("/wechat/wxid_a/msg/file/2024-01/b.pdf")));
+    assert!(keepers.contains(&Path::new("/wechat/wxid_a/msg/file/2024-02/photo.jpg")));
+    assert_eq!(preview.estimated_files_count, 2);
+}
+
+#[test]
+fn test_unreadable_file_is_not_hashed() {
+    let fs = create_tree();
+    fs.inject_error(format!("{ROOT}/2024-01/b.pdf"), ErrorKind::PermissionDenied);
+
+    let result = scan(&fs);
+
+    // b.pdf 无法读取元数据而被跳过，a.pdf 没有可比较的文件
+    assert_eq!(result.total_files_count, 4);
+    assert!(result.verified_groups.is_empty());
+}
+
+#[test]
+fn test_clean_with_injected_error_is_partial() {
+    let fs = create_tree();
+    let preview = CleaningPreview::from(&scan(&fs)).unwrap();
+    fs.inject_error(format!("{ROOT}/2024-02/photo(1).jpg"), ErrorKind::PermissionDenied);
+
+    let settings = CleanerSettings {
+        prune_empty_dirs: true,
+        ..Default::default()
+    };
+    let result = preview.clean_with_fs(fs.as_ref(), &settings, &NoProgress, &CancellationToken::new()).unwrap();
+
+    assert!(result.is_partial());
+    assert_eq!(result.files_deleted, 1);
+    assert!(!fs.exists(format!("{ROOT}/2024-01/a.pdf")));
+    assert!(fs.exists(format!("{ROOT}/2024-02/photo(1).jpg")));
+    let failed: Vec<_> = result
+        .outcomes
+        .iter()
+        .filter(|o| matches!(o.outcome, Outcome::Failed(_)))
+        .collect();
+    assert_eq!(failed.len(), 1);
+}
+
+#[test]
+fn test_group_is_skipped_when_keeper_is_gone() {
+    let fs = create_tree();
+    let preview = CleaningPreview::from(&scan(&fs)).unwrap();
+    // 扫描之后保留的文件被移走，副本成了唯一的一份
+    fs.remove_file(Path::new(&format!("{ROOT}/2024-02/photo.jpg"))).unwrap();
+    // 另一组保留的文件被改写
+    fs.add_file(format!("{ROOT}/2024-01/b.pdf"), b"edited content".to_vec(), 1704067100);
+
+    let result = preview.clean_with_fs(fs.as_ref(), &CleanerSettings::default(), &NoProgress, &CancellationToken::new()).unwrap();
+
+    assert_eq!(result.files_deleted, 0);
+    assert!(!result.is_partial());
+    assert!(fs.exists(format!("{ROOT}/2024-02/photo(1).jpg")));
+    assert!(fs.exists(format!("{ROOT}/2024-01/a.pdf")));
+    let skipped: Vec<_> = result.skipped().collect();
+    assert_eq!(skipped.len(), 2);
+    assert!(skipped.iter().all(|o| matches!(&o.outcome, Outcome::Skipped(reason) if reason.starts_with("保留的文件"))));
+}
+
+#[test]
+fn test_memory_fs_prunes_dirs_and_reads_metadata() {
+    let fs = MemoryFs::new();
+    fs.add_file("/root/a/b/file.tmp", Vec::new(), 42);
+
+    let file = FileInfo::from_fs(&fs, Path::new("/root/a/b/file.tmp")).unwrap();
+    assert_eq!(file.modified, 42);
+    assert!(file.is_leftover());
+
+    fs.remove_file(file.path()).unwrap();
+    let removed = prune_empty_dirs_with(&fs, vec!["/root/a/b".into()], &["/root".into()]);
+    assert_eq!(removed.len(), 2);
+    assert!(fs.is_dir(Path::new("/root")));
+    assert!(!fs.exists("/root/a"));
+}
+
+#[test]
+fn test_file_processor_deletes_through_fs() {
+    let fs = create_tree();
+    let files = vec![
+        FileInfo::from_fs(fs.as_ref(), Path::new(&format!("{ROOT}/2024-01/a.pdf"))).unwrap(),
+        FileInfo::from_fs(fs.as_ref(), Path::new(&format!("{ROOT}/2024-01/c.pdf"))).unwrap(),
+    ];
+
+    assert!(files[0].delete(fs.as_ref()).unwrap());
+    assert!(!fs.exists(format!("{ROOT}/2024-01/a.pdf")));
+    assert!(fs.exists(format!("{ROOT}/2024-01/c.pdf")));
+
+    // 已删除的文件再次删除时报告错误
+    assert!(files.delete(fs.as_ref()).is_err());
+}
@@ -0,0 +1,179 @@
+//! 文件名模式规则集成测试
+//!
+//! 多条命名规则按顺序匹配副本文件名并提取原始名称，
+//! 扫描结果按规则列出识别出的重复组，规则可以按名称禁用。
+
+use std::sync::Arc;
+use wechat_cleaner::config::settings::{Merge, ScannerSettings, Settings, WechatSettings, default_pattern_rules};
+use wechat_cleaner::errors::Error;
+use std::path::PathBuf;
+use wechat_cleaner::FileInfo;
+use wechat_cleaner::file_utils::{PatternMatch, PatternRule, PatternSet};
+use wechat_cleaner::filesystem::MemoryFs;
+use wechat_cleaner::scanner::{FileScanner, PatternRuleStat, ScanResult};
+use wechat_cleaner::storage::StorageFormat;
+
+fn create_fs() -> Arc<MemoryFs> {
+    // 内容长度各不相同，重复组只可能来自模式规则
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"1".to_vec(), 1)
+        .add_file("/cache/a(1).pdf", b"22".to_vec(), 2)
+        .add_file("/cache/b.doc", b"333".to_vec(), 1)
+        .add_file("/cache/b - 副本.doc", b"4444".to_vec(), 2)
+        .add_file("/cache/c.txt", b"55555".to_vec(), 1)
+        .add_file("/cache/c copy 2.txt", b"666666".to_vec(), 2)
+        .add_file("/cache/d.jpg", b"7777777".to_vec(), 1)
+        .add_file("/cache/d_副本.jpg", b"88888888".to_vec(), 2)
+        .add_file("/cache/e.mp4", b"999999999".to_vec(), 1)
+        .add_file("/cache/e_20240101_120000.mp4", b"0000000000".to_vec(), 2);
+    fs
+}
+
+fn scan(fs: &Arc<MemoryFs>, wechat: WechatSettings) -> ScanResult {
+    let wechat = WechatSettings { cache_path: Some("/cache".into()), ..wechat };
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    FileScanner::with_fs(&wechat, fs.clone()).unwrap().scan(&settings).unwrap()
+}
+
+fn stat<'a>(result: &'a ScanResult, name: &str) -> Option<&'a PatternRuleStat> {
+    result.pattern_rules.iter().find(|stat| stat.name == name)
+}
+
+#[test]
+fn test_only_numbered_copies_are_enabled_by_default() {
+    let result = scan(&create_fs(), WechatSettings::default());
+
+    // 模式组不经内容验证也会被清理，其余内置规则需要手动启用
+    let keys: Vec<&String> = result.duplicate_files.keys().collect();
+    assert_eq!(keys, ["a"]);
+    let names: Vec<&str> = result.pattern_rules.iter().map(|stat| stat.name.as_str()).collect();
+    assert_eq!(names, ["序号副本"]);
+}
+
+#[test]
+fn test_builtin_rules_report_their_groups() {
+    let wechat = WechatSettings { disabled_patterns: vec!["时间戳后缀".into()], ..Default::default() };
+    let result = scan(&create_fs(), wechat);
+
+    let mut keys: Vec<&String> = result.duplicate_files.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["a", "b", "c", "d"]);
+
+    let names: Vec<&str> = result.pattern_rules.iter().map(|stat| stat.name.as_str()).collect();
+    assert_eq!(names, ["下划线副本", "横线副本", "copy 副本", "序号副本"]);
+    for (name, group) in [("序号副本", "a"), ("横线副本", "b"), ("copy 副本", "c"), ("下划线副本", "d")] {
+        let stat = stat(&result, name).unwrap();
+        assert_eq!(stat.groups, [group], "{name}");
+        assert_eq!(stat.files_count, 1, "{name}");
+    }
+}
+
+#[test]
+fn test_rules_can_be_disabled_and_enabled_by_name() {
+    let fs = create_fs();
+
+    let wechat = WechatSettings { disabled_patterns: vec!["序号副本".into(), "copy 副本".into(), "时间戳后缀".into()], ..Default::default() };
+    let result = scan(&fs, wechat);
+    assert!(!result.duplicate_files.contains_key("a"));
+    assert!(!result.duplicate_files.contains_key("c"));
+    assert!(stat(&result, "序号副本").is_none());
+
+    assert!(result.duplicate_files.contains_key("b"));
+
+    // 时间戳规则默认禁用，清空禁用列表后生效
+    let result = scan(&fs, WechatSettings { disabled_patterns: Vec::new(), ..Default::default() });
+    assert_eq!(stat(&result, "时间戳后缀").unwrap().groups, ["e"]);
+}
+
+#[test]
+fn test_match_name_uses_capture_group_and_falls_back_to_stem() {
+    let patterns = PatternSet::new(&default_pattern_rules()).unwrap();
+    assert_eq!(patterns.match_name("报告 (2).pdf"), PatternMatch { rule: Some("序号副本"), name: "报告".into() });
+    assert_eq!(patterns.match_name("报告 - 副本 (3).pdf"), PatternMatch { rule: Some("横线副本"), name: "报告".into() });
+    assert_eq!(patterns.match_name("报告.pdf"), PatternMatch { rule: None, name: "报告".into() });
+
+    // 旧版模式：取第一个匹配位置之前的部分
+    let legacy = PatternSet::new(&[PatternRule::legacy("legacy", r"_copy\.[a-z]+$")]).unwrap();
+    assert_eq!(legacy.match_name("a_copy.pdf").name, "a");
+    assert_eq!(legacy.match_name("_copy.pdf").rule, None);
+}
+
+#[test]
+fn test_invalid_rules_are_rejected() {
+    let no_group = PatternSet::new(&[PatternRule::new("无捕获组", r"\(\d+\)$", 1)]);
+    assert!(matches!(no_group, Err(Error::Config(_))));
+    assert!(matches!(PatternSet::new(&[PatternRule::new("坏规则", "(", 1)]), Err(Error::Regex(_))));
+}
+
+#[test]
+fn test_rules_from_config_file() {
+    let settings: Settings = toml::from_str(
+        r#"
+        [wechat]
+        disabled_patterns = []
+
+        [[wechat.pattern_rules]]
+        name = "bak"
+        regex = '^(?P<base>.+)\.bak$'
+        group = 1
+
+        [scanner]
+        [cleaner]
+        "#,
+    )
+    .unwrap();
+
+    let rules = settings.wechat.active_pattern_rules();
+    assert_eq!(rules, [PatternRule::new("bak", r"^(?P<base>.+)\.bak$", 1)]);
+    let patterns = PatternSet::new(&rules).unwrap();
+    assert_eq!(patterns.match_name("notes.txt.bak").name, "notes.txt");
+}
+
+#[test]
+fn test_legacy_config_does_not_enable_builtin_rules() {
+    let file: Settings = toml::from_str(
+        r#"
+        [wechat]
+        cache_patterns = '\(\d+\)\.[a-zA-Z0-9]+$'
+
+        [scanner]
+        [cleaner]
+        "#,
+    )
+    .unwrap();
+    let mut settings = Settings::default();
+    settings.merge(file);
+
+    // 升级前只有旧版模式生效，升级后保持不变
+    let rules = settings.wechat.active_pattern_rules();
+    assert_eq!(rules, [PatternRule::legacy("cache_patterns", r"\(\d+\)\.[a-zA-Z0-9]+$")]);
+}
+
+#[test]
+fn test_preview_shows_names_rules_and_groups() {
+    let mut rules = vec![PatternRule::new("bak", r"^(.+)\.[a-z]+\.bak$", 1)];
+    rules.extend(default_pattern_rules());
+    let patterns = PatternSet::new(&rules).unwrap();
+    let files: Vec<FileInfo> = ["/x/a.pdf", "/x/a(1).pdf", "/y/a.pdf.bak", "/x/b.doc"]
+        .into_iter()
+        .map(|path| FileInfo::new(path, 0, 0))
+        .collect();
+
+    let preview = patterns.preview(&files);
+    let paths: Vec<&str> = preview.files.iter().map(|file| file.path.to_str().unwrap()).collect();
+    assert_eq!(paths, ["/x/a(1).pdf", "/x/a.pdf", "/x/b.doc", "/y/a.pdf.bak"]);
+    let bak = &preview.files[3];
+    assert_eq!((bak.rule.as_deref(), bak.name.as_deref(), bak.group_size), (Some("bak"), Some("a"), 3));
+    assert_eq!(preview.files[2].group_size, 1);
+
+    // 只列出包含多个文件的组，规则按首次出现的顺序
+    assert_eq!(preview.groups.len(), 1);
+    let group = &preview.groups[0];
+    assert_eq!(group.name, "a");
+    assert_eq!(group.rules, ["序号副本", "bak"]);
+    assert_eq!(group.files, [PathBuf::from("/x/a(1).pdf"), "/x/a.pdf".into(), "/y/a.pdf.bak".into()]);
+}
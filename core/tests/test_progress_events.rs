@@ -0,0 +1,201 @@
+//! 进度事件集成测试
+//!
+//! 用记录事件的 `ProgressSink` 检查扫描和清理过程中发出的事件。
+
+use std::io::{self, ErrorKind, Read};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use wechat_cleaner::cleaner::CleaningPreview;
+use wechat_cleaner::config::settings::{CleanerSettings, ScannerSettings};
+use wechat_cleaner::progress::{Phase, ProgressEvent, ProgressSink};
+use wechat_cleaner::storage::StorageFormat;
+use wechat_cleaner::{CancellationToken, ContentHasher, MemoryFs, ScanResult, Scanner};
+
+/// 事件的简化记录
+#[derive(Debug, PartialEq)]
+enum Recorded {
+    Started(Phase, Option<u64>),
+    Finished(Phase),
+    Discovered(usize),
+    Metadata(PathBuf),
+    Grouped(usize),
+    Hashed(PathBuf, u64),
+    HashFailed(PathBuf, u64),
+    Group(String, usize),
+    Deleted(PathBuf),
+    Skipped(PathBuf),
+    Error(Option<PathBuf>),
+    Message,
+}
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<Recorded>>);
+
+impl Recorder {
+    fn take(&self) -> Vec<Recorded> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+impl ProgressSink for Recorder {
+    fn on_event(&self, event: &ProgressEvent) {
+        let recorded = match *event {
+            ProgressEvent::PhaseStarted { phase, total } => Recorded::Started(phase, total),
+            ProgressEvent::PhaseFinished { phase } => Recorded::Finished(phase),
+            ProgressEvent::FilesDiscovered { count } => Recorded::Discovered(count),
+            ProgressEvent::MetadataRead { path, .. } => Recorded::Metadata(path.to_path_buf()),
+            ProgressEvent::FilesGrouped { count } => Recorded::Grouped(count),
+            ProgressEvent::BytesHashed { path, bytes } => Recorded::Hashed(path.to_path_buf(), bytes),
+            ProgressEvent::HashFailed { path, bytes, .. } => Recorded::HashFailed(path.to_path_buf(), bytes),
+            ProgressEvent::GroupFound { key, files } => Recorded::Group(key.to_string(), files),
+            ProgressEvent::FileDeleted { path, .. } => Recorded::Deleted(path.to_path_buf()),
+            ProgressEvent::FileSkipped { path, .. } => Recorded::Skipped(path.to_path_buf()),
+            ProgressEvent::Error { path, .. } => Recorded::Error(path.map(|p| p.to_path_buf())),
+            ProgressEvent::Message(_) => Recorded::Message,
+        };
+        self.0.lock().unwrap().push(recorded);
+    }
+}
+
+fn create_fs() -> Arc<MemoryFs> {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"same".to_vec(), 1)
+        .add_file("/cache/b.pdf", b"same".to_vec(), 2)
+        .add_file("/cache/photo.jpg", b"photo".to_vec(), 3)
+        .add_file("/cache/photo(1).jpg", b"photo".to_vec(), 4);
+    fs
+}
+
+fn scan(fs: &Arc<MemoryFs>, recorder: &Recorder) -> ScanResult {
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    Scanner::builder()
+        .root("/cache")
+        .fs(fs.clone())
+        .build()
+        .unwrap()
+        .scan_with_progress(&settings, recorder)
+        .unwrap()
+}
+
+#[test]
+fn test_scan_emits_phase_and_file_events() {
+    let fs = create_fs();
+    let recorder = Recorder::default();
+    scan(&fs, &recorder);
+    let events = recorder.take();
+
+    assert_eq!(events[..4], [
+        Recorded::Started(Phase::Walk, None),
+        Recorded::Finished(Phase::Walk),
+        Recorded::Discovered(4),
+        Recorded::Started(Phase::Metadata, Some(4)),
+    ]);
+    let metadata = events.iter().filter(|e| matches!(e, Recorded::Metadata(_))).count();
+    assert_eq!(metadata, 4);
+
+    // 分组阶段的计数达到总量：2 个模式副本 + 2 个按大小分组的候选文件
+    assert!(events.contains(&Recorded::Started(Phase::Group, Some(4))));
+    let grouped: usize = events
+        .iter()
+        .filter_map(|e| match e {
+            Recorded::Grouped(count) => Some(*count),
+            _ => None,
+        })
+        .sum();
+    assert_eq!(grouped, 4);
+
+    // 只有模式不匹配且大小相同的两个文件需要计算哈希
+    assert!(events.contains(&Recorded::Started(Phase::Hash, Some(8))));
+    assert!(events.contains(&Recorded::Finished(Phase::Hash)));
+    let mut hashed: Vec<_> = events
+        .iter()
+        .filter_map(|e| match e {
+            Recorded::Hashed(path, bytes) => Some((path.clone(), *bytes)),
+            _ => None,
+        })
+        .collect();
+    hashed.sort();
+    assert_eq!(hashed, vec![("/cache/a.pdf".into(), 4), ("/cache/b.pdf".into(), 4)]);
+
+    let groups = events.iter().filter(|e| matches!(e, Recorded::Group(_, 2))).count();
+    assert_eq!(groups, 2);
+    assert!(events.contains(&Recorded::Group("photo".to_string(), 2)));
+}
+
+#[test]
+fn test_scan_reports_unreadable_file() {
+    let fs = create_fs();
+    fs.inject_error("/cache/b.pdf", ErrorKind::PermissionDenied);
+    let recorder = Recorder::default();
+    scan(&fs, &recorder);
+
+    // 元数据同样无法读取，因此在收集阶段就报告错误
+    assert!(recorder.take().contains(&Recorded::Error(Some("/cache/b.pdf".into()))));
+}
+
+/// 总是读取失败的哈希算法
+struct FailingHasher;
+
+impl ContentHasher for FailingHasher {
+    fn name(&self) -> &str {
+        "failing"
+    }
+
+    fn hash_reader(&self, _reader: &mut dyn Read) -> io::Result<String> {
+        Err(io::Error::other("读取失败"))
+    }
+}
+
+#[test]
+fn test_hash_failures_carry_file_size() {
+    let fs = create_fs();
+    let recorder = Recorder::default();
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    Scanner::builder()
+        .root("/cache")
+        .fs(fs.clone())
+        .hasher(FailingHasher)
+        .build()
+        .unwrap()
+        .scan_with_progress(&settings, &recorder)
+        .unwrap();
+    let events = recorder.take();
+
+    // 失败的文件按大小计入哈希阶段，进度仍能达到总量
+    assert!(events.contains(&Recorded::Started(Phase::Hash, Some(8))));
+    let mut failed: Vec<&Recorded> = events.iter().filter(|e| matches!(e, Recorded::HashFailed(..))).collect();
+    failed.sort_by_key(|e| format!("{e:?}"));
+    assert_eq!(failed, [
+        &Recorded::HashFailed("/cache/a.pdf".into(), 4),
+        &Recorded::HashFailed("/cache/b.pdf".into(), 4),
+    ]);
+    assert!(!events.iter().any(|e| matches!(e, Recorded::Hashed(..) | Recorded::Error(_))));
+}
+
+#[test]
+fn test_clean_emits_per_file_events() {
+    let fs = create_fs();
+    let result = scan(&fs, &Recorder::default());
+    let preview = CleaningPreview::from(&result).unwrap();
+    // 扫描后一个副本被修改，清理时跳过
+    fs.add_file("/cache/photo(1).jpg", b"changed".to_vec(), 9);
+
+    let recorder = Recorder::default();
+    preview
+        .clean_with_fs(fs.as_ref(), &CleanerSettings::default(), &recorder, &CancellationToken::new())
+        .unwrap();
+    let events = recorder.take();
+
+    assert_eq!(events.first(), Some(&Recorded::Started(Phase::Clean, Some(2))));
+    assert_eq!(events.last(), Some(&Recorded::Finished(Phase::Clean)));
+    assert!(events.contains(&Recorded::Deleted("/cache/b.pdf".into())));
+    assert!(events.contains(&Recorded::Skipped("/cache/photo(1).jpg".into())));
+}
@@ -0,0 +1,74 @@
+//! 文件读取策略集成测试
+//!
+//! 不同读取方式（缓冲区、页对齐大缓冲区、内存映射）计算出的摘要必须一致。
+
+use std::fs;
+use std::io::Read;
+use tempfile::TempDir;
+use wechat_cleaner::file_utils::Hashed;
+use wechat_cleaner::hasher::{ContentHasher, Md5Hasher};
+use wechat_cleaner::reader::{self, AlignedReader, ReadStrategy, LARGE_BUFFER, SMALL_BUFFER};
+use wechat_cleaner::throttle::{Throttle, ThrottledHasher};
+use wechat_cleaner::{FileInfo, MemoryFs, RealFs};
+
+fn content(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+#[test]
+fn test_strategy_depends_on_size() {
+    assert_eq!(ReadStrategy::for_size(0), ReadStrategy::Buffered(SMALL_BUFFER));
+    assert_eq!(ReadStrategy::for_size(4 * 1024 * 1024), ReadStrategy::Buffered(LARGE_BUFFER));
+    // 大文件同样使用页对齐缓冲区，扫描时不使用内存映射
+    assert_eq!(ReadStrategy::for_size(4 * 1024 * 1024 * 1024), ReadStrategy::Buffered(LARGE_BUFFER));
+}
+
+#[test]
+fn test_all_strategies_produce_same_digest() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("video.mp4");
+    let data = content(3 * 1024 * 1024 + 17);
+    fs::write(&path, &data).unwrap();
+    let expected = Md5Hasher.hash_bytes(&data).unwrap();
+
+    // SAFETY: 临时文件只由本测试写入，读取期间不会被截断或改写
+    for strategy in [ReadStrategy::Buffered(SMALL_BUFFER), ReadStrategy::Buffered(LARGE_BUFFER), ReadStrategy::Mapped] {
+        assert_eq!(unsafe { reader::hash_file_with(&path, strategy, &Md5Hasher) }.unwrap(), expected, "{strategy:?}");
+    }
+    assert_eq!(reader::hash_file(&path, data.len() as u64, &Md5Hasher).unwrap(), expected);
+
+    // 限速包装的算法处理内存映射内容时退回到逐块读取，结果不变
+    let throttle = Throttle::new(u64::MAX);
+    let throttled = ThrottledHasher::new(&Md5Hasher, &throttle);
+    // SAFETY: 同上
+    assert_eq!(unsafe { reader::hash_file_with(&path, ReadStrategy::Mapped, &throttled) }.unwrap(), expected);
+
+    // 真实文件系统和内存文件系统的结果一致
+    let file = FileInfo::from_path(&path).unwrap();
+    assert_eq!(file.hash_with(&RealFs).unwrap(), expected);
+    let memory = MemoryFs::new();
+    memory.add_file(&path, data, 1);
+    assert_eq!(file.hash_with(&memory).unwrap(), expected);
+}
+
+#[test]
+fn test_aligned_reader_returns_all_bytes() {
+    let data = content(10_000);
+    let mut output = Vec::new();
+    AlignedReader::with_capacity(4096, data.as_slice())
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, data);
+}
+
+#[test]
+fn test_file_truncated_after_scan_is_read_safely() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("video.mp4");
+    let data = content(64 * 1024);
+    fs::write(&path, &data).unwrap();
+
+    // 扫描时记录的大小远大于文件当前的内容，按记录的大小选择的读取方式只读到实际内容
+    let digest = reader::hash_file(&path, 2 * 1024 * 1024 * 1024, &Md5Hasher).unwrap();
+    assert_eq!(digest, Md5Hasher.hash_bytes(&data).unwrap());
+}
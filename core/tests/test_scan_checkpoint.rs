@@ -0,0 +1,151 @@
+//! 扫描检查点集成测试
+//!
+//! 中断扫描后保留检查点，恢复时复用已计算的摘要；
+//! 根目录或设置指纹不一致的检查点会被忽略。
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::TempDir;
+use wechat_cleaner::config::settings::ScannerSettings;
+use wechat_cleaner::progress::{NoProgress, ProgressEvent, ProgressSink};
+use wechat_cleaner::storage::StorageFormat;
+use wechat_cleaner::{CancellationToken, ContentHasher, Md5Hasher, MemoryFs, ScanCheckpoint, ScanResult, Scanner};
+
+/// 统计调用次数的哈希算法
+#[derive(Clone, Default)]
+struct CountingHasher(Arc<AtomicUsize>);
+
+impl CountingHasher {
+    fn calls(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl ContentHasher for CountingHasher {
+    fn name(&self) -> &str {
+        "md5"
+    }
+
+    fn hash_reader(&self, reader: &mut dyn Read) -> io::Result<String> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Md5Hasher.hash_reader(reader)
+    }
+}
+
+/// 第一个文件完成哈希后请求取消
+struct CancelAfterHash(CancellationToken);
+
+impl ProgressSink for CancelAfterHash {
+    fn on_event(&self, event: &ProgressEvent) {
+        if matches!(event, ProgressEvent::BytesHashed { .. }) {
+            self.0.cancel();
+        }
+    }
+}
+
+fn create_fs() -> Arc<MemoryFs> {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"same".to_vec(), 1)
+        .add_file("/cache/b.pdf", b"same".to_vec(), 2)
+        .add_file("/cache/c.doc", b"report".to_vec(), 3)
+        .add_file("/cache/d.doc", b"report".to_vec(), 4);
+    fs
+}
+
+fn scan(
+    fs: &Arc<MemoryFs>,
+    checkpoint: &Path,
+    resume: bool,
+    hasher: &CountingHasher,
+    token: &CancellationToken,
+    progress: &dyn ProgressSink,
+) -> ScanResult {
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    Scanner::builder()
+        .root("/cache")
+        .fs(fs.clone())
+        .hasher(hasher.clone())
+        .cancellation(token.clone())
+        .checkpoint(checkpoint)
+        .resume(resume)
+        .build()
+        .unwrap()
+        .scan_with_progress(&settings, progress)
+        .unwrap()
+}
+
+/// 中断一次扫描，返回检查点路径和扫描器的设置指纹
+fn interrupted_scan(fs: &Arc<MemoryFs>, temp_dir: &TempDir) -> (PathBuf, String) {
+    let path = temp_dir.path().join("checkpoint.json");
+    let token = CancellationToken::new();
+    let result = scan(fs, &path, false, &CountingHasher::default(), &token, &CancelAfterHash(token.clone()));
+    assert!(result.interrupted);
+    (path, result.settings_fingerprint)
+}
+
+#[test]
+fn test_interrupted_scan_leaves_checkpoint() {
+    let fs = create_fs();
+    let temp_dir = TempDir::new().unwrap();
+    let (path, fingerprint) = interrupted_scan(&fs, &temp_dir);
+
+    let checkpoint = ScanCheckpoint::load(&path, &fingerprint, &["/cache".into()]).unwrap();
+    assert_eq!(checkpoint.files().unwrap().len(), 4);
+    assert!(checkpoint.digests_count() >= 1);
+
+    // 根目录或设置指纹不一致时不复用
+    assert!(ScanCheckpoint::load(&path, "other", &["/cache".into()]).is_none());
+    assert!(ScanCheckpoint::load(&path, &fingerprint, &["/other".into()]).is_none());
+}
+
+#[test]
+fn test_resume_reuses_digests_and_removes_checkpoint() {
+    let fs = create_fs();
+    let temp_dir = TempDir::new().unwrap();
+    let (path, fingerprint) = interrupted_scan(&fs, &temp_dir);
+    let saved = ScanCheckpoint::load(&path, &fingerprint, &["/cache".into()]).unwrap().digests_count();
+
+    let hasher = CountingHasher::default();
+    let result = scan(&fs, &path, true, &hasher, &CancellationToken::new(), &NoProgress);
+
+    assert!(!result.interrupted);
+    assert_eq!(hasher.calls(), 4 - saved);
+    assert_eq!(result.verified_groups.len(), 2);
+    assert_eq!(result.duplicate_count, 4);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_resume_ignores_checkpoint_with_other_settings() {
+    let fs = create_fs();
+    let temp_dir = TempDir::new().unwrap();
+    let (path, _) = interrupted_scan(&fs, &temp_dir);
+
+    let hasher = CountingHasher::default();
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    let result = Scanner::builder()
+        .root("/cache")
+        .pattern(r"_copy\.[a-z]+$")
+        .fs(fs.clone())
+        .hasher(hasher.clone())
+        .checkpoint(&path)
+        .resume(true)
+        .build()
+        .unwrap()
+        .scan(&settings)
+        .unwrap();
+
+    assert!(!result.interrupted);
+    assert_eq!(hasher.calls(), 4);
+    assert_eq!(result.verified_groups.len(), 2);
+}
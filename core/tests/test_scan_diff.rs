@@ -0,0 +1,123 @@
+//! 扫描对比集成测试
+//!
+//! 测试两次扫描之间新增/消失的文件与重复组、占用变化的统计，
+//! 以及按账号和文件类型的归类。
+
+use std::collections::HashMap;
+use wechat_cleaner::diff::ScanDiff;
+use wechat_cleaner::display::Display;
+use wechat_cleaner::file_utils::{FileCategory, FileInfo};
+use wechat_cleaner::scanner::{ScanResult, UsageStat};
+
+/// 通过 JSON 构造 FileInfo（构造函数是私有的）
+fn file_info(path: &str, size: u64) -> FileInfo {
+    FileInfo::new(path, size, 1704067200)
+}
+
+fn scan(id: &str, groups: &[(&str, &[(&str, u64)])], others: &[(&str, u64)]) -> ScanResult {
+    let mut duplicate_files = HashMap::new();
+    let mut all_files = Vec::new();
+    for (key, files) in groups {
+        let files: Vec<FileInfo> = files.iter().map(|(p, s)| file_info(p, *s)).collect();
+        all_files.extend(files.iter().cloned());
+        duplicate_files.insert(key.to_string(), files);
+    }
+    all_files.extend(others.iter().map(|(p, s)| file_info(p, *s)));
+
+    ScanResult {
+        id: id.to_string(),
+        total_files_count: all_files.len(),
+        duplicate_count: duplicate_files.values().map(Vec::len).sum(),
+        duplicate_files,
+        usage: UsageStat::collect(&all_files),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_usage_groups_by_account_and_category() {
+    let files = vec![
+        file_info("/cache/wxid_a/msg/file/2024-01/a.jpg", 10),
+        file_info("/cache/wxid_a/msg/file/2024-01/b.PNG", 20),
+        file_info("/cache/wxid_b/msg/file/2024-01/c.pdf", 30),
+        file_info("/cache/other/d.bin", 40),
+    ];
+    let usage = UsageStat::collect(&files);
+
+    assert_eq!(usage.len(), 3);
+    assert_eq!(usage[0].account, None);
+    assert_eq!(usage[0].category, FileCategory::Other);
+    assert_eq!(usage[1].account.as_deref(), Some("wxid_a"));
+    assert_eq!(usage[1].category, FileCategory::Image);
+    assert_eq!((usage[1].files_count, usage[1].total_size), (2, 30));
+    assert_eq!(usage[2].category, FileCategory::Document);
+}
+
+#[test]
+fn test_diff_reports_new_and_removed_groups() {
+    let before = scan(
+        "20240101-120000",
+        &[("old", &[("/c/wxid_a/old.jpg", 10), ("/c/wxid_a/old(1).jpg", 10)])],
+        &[("/c/wxid_a/notes.pdf", 100)],
+    );
+    let after = scan(
+        "20240201-120000",
+        &[("new", &[("/c/wxid_a/new.mp4", 500), ("/c/wxid_a/new(1).mp4", 500)])],
+        &[("/c/wxid_a/notes.pdf", 100)],
+    );
+
+    let diff = ScanDiff::between(&before, &after);
+
+    assert_eq!(diff.from, "20240101-120000");
+    assert_eq!(diff.total_files_delta, 0);
+    assert_eq!(diff.total_size_delta, Some(980));
+    assert_eq!(diff.duplicate_size_delta, 980);
+    assert_eq!(diff.added_files.len(), 2);
+    assert_eq!(diff.removed_files.len(), 2);
+    assert_eq!(diff.new_groups[0].key, "new");
+    assert_eq!(diff.new_groups[0].total_size, 1000);
+    assert_eq!(diff.removed_groups[0].key, "old");
+
+    // 文档没有变化，不出现在占用变化中
+    let usage_deltas = diff.usage_deltas.unwrap();
+    let categories: Vec<FileCategory> = usage_deltas.iter().map(|d| d.category).collect();
+    assert_eq!(categories, vec![FileCategory::Image, FileCategory::Video]);
+    assert_eq!(usage_deltas[0].files_delta, -2);
+    assert_eq!(usage_deltas[1].bytes_delta, 1000);
+}
+
+#[test]
+fn test_diff_against_scan_without_usage_reports_unavailable() {
+    // 从旧格式迁移来的扫描结果有文件但没有占用统计
+    let before = ScanResult {
+        usage: Vec::new(),
+        ..scan("a", &[], &[("/c/x.jpg", 2048)])
+    };
+    let after = scan("b", &[], &[("/c/x.jpg", 2048), ("/c/y.jpg", 1024)]);
+
+    let diff = ScanDiff::between(&before, &after);
+
+    assert!(!diff.has_usage());
+    assert_eq!(diff.total_size_delta, None);
+    assert!(diff.usage_deltas.is_none());
+    assert_eq!(diff.total_files_delta, 1);
+
+    let json = serde_json::to_value(&diff).unwrap();
+    assert!(json["total_size_delta"].is_null());
+}
+
+#[test]
+fn test_diff_renders_as_display_and_json() {
+    let before = scan("a", &[], &[("/c/x.jpg", 2048)]);
+    let after = scan("b", &[], &[]);
+    let diff = ScanDiff::between(&before, &after);
+
+    let summary = diff.display_summary();
+    assert!(summary.contains("-2.00 KB"));
+    assert!(summary.contains("-1"));
+
+    let json = serde_json::to_value(&diff).unwrap();
+    assert_eq!(json["total_size_delta"], -2048);
+    assert_eq!(json["usage_deltas"][0]["category"], "image");
+    assert!(json["usage_deltas"][0]["account"].is_null());
+}
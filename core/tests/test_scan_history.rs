@@ -0,0 +1,147 @@
+//! 扫描历史集成测试
+//!
+//! 测试扫描结果的编号保存、查找、删除、清理标记，
+//! 以及旧版单一扫描结果文件的导入。
+
+use std::fs;
+use tempfile::TempDir;
+use wechat_cleaner::config::settings::ScannerSettings;
+use wechat_cleaner::errors::Error;
+use wechat_cleaner::history::{self, ScanHistory};
+use wechat_cleaner::scanner::ScanResult;
+use wechat_cleaner::storage::StorageFormat;
+
+fn settings(temp_dir: &TempDir) -> ScannerSettings {
+    ScannerSettings {
+        save_path: temp_dir.path().to_path_buf(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    }
+}
+
+fn scan_result(settings: &ScannerSettings, created_at: u64, total_files_count: usize) -> ScanResult {
+    let id = history::scan_id(created_at);
+    ScanResult {
+        path: settings.scan_path(&id),
+        id,
+        created_at,
+        total_files_count,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_scan_id_is_sortable_timestamp() {
+    // 2024-01-01T12:00:00Z
+    assert_eq!(history::scan_id(1704110400), "20240101-120000");
+}
+
+#[test]
+fn test_record_list_and_latest() {
+    let temp_dir = TempDir::new().unwrap();
+    let settings = settings(&temp_dir);
+    let mut history = ScanHistory::open(&settings).unwrap();
+    assert!(history.latest().is_none());
+
+    history.record(&mut scan_result(&settings, 1704196800, 20)).unwrap();
+    history.record(&mut scan_result(&settings, 1704110400, 10)).unwrap();
+
+    // 重新打开后从索引读取，按时间排序
+    let history = ScanHistory::open(&settings).unwrap();
+    let ids: Vec<&str> = history.entries().iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, vec!["20240101-120000", "20240102-120000"]);
+    assert_eq!(history.latest().unwrap().total_files_count, 20);
+    assert_eq!(history.resolve(None).unwrap().id, "20240102-120000");
+    assert_eq!(history.find("20240101").unwrap().total_files_count, 10);
+    assert!(matches!(history.find("2024"), Err(Error::InvalidOperation(_))));
+    assert!(matches!(history.find("1999"), Err(Error::ScanNotFound(_))));
+    assert_eq!(history.load("20240101").unwrap().total_files_count, 10);
+}
+
+#[test]
+fn test_record_same_second_gets_unique_id() {
+    let temp_dir = TempDir::new().unwrap();
+    let settings = settings(&temp_dir);
+    let mut history = ScanHistory::open(&settings).unwrap();
+
+    history.record(&mut scan_result(&settings, 1704110400, 1)).unwrap();
+    let mut second = scan_result(&settings, 1704110400, 2);
+    history.record(&mut second).unwrap();
+
+    assert_eq!(second.id, "20240101-120000-2");
+    assert!(second.path.ends_with("20240101-120000-2.json"));
+    assert_eq!(history.entries().len(), 2);
+}
+
+#[test]
+fn test_remove_and_mark_cleaned() {
+    let temp_dir = TempDir::new().unwrap();
+    let settings = settings(&temp_dir);
+    let mut history = ScanHistory::open(&settings).unwrap();
+    let mut first = scan_result(&settings, 1704110400, 1);
+    history.record(&mut first).unwrap();
+    history.record(&mut scan_result(&settings, 1704196800, 2)).unwrap();
+
+    history.mark_cleaned("20240102").unwrap();
+    let removed = history.remove("20240101").unwrap();
+    assert_eq!(removed.id, first.id);
+    assert!(!first.path.exists());
+
+    let history = ScanHistory::open(&settings).unwrap();
+    assert_eq!(history.entries().len(), 1);
+    assert!(history.latest().unwrap().cleaned);
+}
+
+#[test]
+fn test_legacy_result_is_imported() {
+    let temp_dir = TempDir::new().unwrap();
+    let settings = settings(&temp_dir);
+    let legacy_path = temp_dir.path().join("wechat-cleaner/scan-result.json");
+    ScanResult {
+        path: legacy_path.clone(),
+        total_files_count: 7,
+        ..Default::default()
+    }
+    .save()
+    .unwrap();
+
+    let history = ScanHistory::open(&settings).unwrap();
+    let entry = history.latest().unwrap();
+    assert_eq!(entry.total_files_count, 7);
+    assert!(entry.created_at > 0);
+    assert!(!legacy_path.exists());
+    assert!(history.path_of(entry).exists());
+}
+
+#[test]
+fn test_unreadable_legacy_result_is_set_aside() {
+    let temp_dir = TempDir::new().unwrap();
+    let settings = settings(&temp_dir);
+    let legacy_dir = temp_dir.path().join("wechat-cleaner");
+    fs::create_dir_all(&legacy_dir).unwrap();
+    fs::write(legacy_dir.join("scan-result.json"), b"{\"format_version\": 3, \"path\": ").unwrap();
+    fs::write(legacy_dir.join("scan-result.bin"), b"WCSR\xff\xff\xff\xffgarbage").unwrap();
+
+    let history = ScanHistory::open(&settings).unwrap();
+    assert!(history.entries().is_empty());
+    assert!(!legacy_dir.join("scan-result.json").exists());
+    assert!(legacy_dir.join("scan-result.json.unreadable").exists());
+    assert!(legacy_dir.join("scan-result.bin.unreadable").exists());
+
+    // 改名后不再尝试导入
+    assert!(ScanHistory::open(&settings).is_ok());
+}
+
+#[test]
+fn test_missing_index_is_rebuilt() {
+    let temp_dir = TempDir::new().unwrap();
+    let settings = settings(&temp_dir);
+    let mut history = ScanHistory::open(&settings).unwrap();
+    history.record(&mut scan_result(&settings, 1704110400, 3)).unwrap();
+
+    fs::remove_file(settings.history_dir().join("index.json")).unwrap();
+
+    let history = ScanHistory::open(&settings).unwrap();
+    assert_eq!(history.entries().len(), 1);
+    assert_eq!(history.latest().unwrap().id, "20240101-120000");
+}
@@ -0,0 +1,91 @@
+//! 扫描报告导出集成测试
+//!
+//! 测试 JSON、CSV、HTML 三种报告格式的结构与转义。
+
+use std::collections::{HashMap, HashSet};
+use wechat_cleaner::file_utils::FileInfo;
+use wechat_cleaner::report::{ReportFormat, ScanReport};
+use wechat_cleaner::scanner::ScanResult;
+
+/// 通过 JSON 构造 FileInfo（构造函数是私有的）
+fn file_info(path: &str, size: u64) -> FileInfo {
+    FileInfo::new(path, size, 1704067200)
+}
+
+fn create_scan_result() -> ScanResult {
+    let mut duplicate_files = HashMap::new();
+    duplicate_files.insert(
+        "report, final".to_string(),
+        vec![
+            file_info("/cache/2024-01/report, final(1).pdf", 20),
+            file_info("/cache/2024-01/report, final.pdf", 20),
+        ],
+    );
+    duplicate_files.insert(
+        "0cc175b9c0f1b6a831c399e269772661".to_string(),
+        vec![file_info("/cache/a.jpg", 5), file_info("/cache/<b>.jpg", 5)],
+    );
+
+    ScanResult {
+        total_files_count: 5,
+        duplicate_count: 4,
+        duplicate_files,
+        verified_groups: HashSet::from(["0cc175b9c0f1b6a831c399e269772661".to_string()]),
+        leftover_files: vec![file_info("/cache/video.mp4.part", 0)],
+        ..Default::default()
+    }
+}
+
+fn render(format: ReportFormat) -> String {
+    let mut buffer = Vec::new();
+    ScanReport::from(&create_scan_result())
+        .write_to(&mut buffer, format)
+        .unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+#[test]
+fn test_json_report_has_stable_group_ids() {
+    let report: serde_json::Value = serde_json::from_str(&render(ReportFormat::Json)).unwrap();
+
+    assert_eq!(report["schema_version"], 1);
+    assert_eq!(report["duplicate_size"], 50);
+    assert_eq!(report["groups"][0]["id"], 1);
+    assert_eq!(report["groups"][0]["kind"], "content");
+    assert_eq!(report["groups"][1]["key"], "report, final");
+    assert_eq!(report["groups"][1]["kind"], "pattern");
+    assert_eq!(report["leftover_files"][0]["size"], 0);
+}
+
+#[test]
+fn test_csv_report_has_one_row_per_file() {
+    let csv = render(ReportFormat::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(lines[0], "group_id,category,kind,key,path,size,modified");
+    assert_eq!(lines.len(), 6);
+    assert!(lines.contains(
+        &r#"2,duplicate,pattern,"report, final","/cache/2024-01/report, final.pdf",20,1704067200"#
+    ));
+    assert!(lines.contains(&",leftover,,,/cache/video.mp4.part,0,1704067200"));
+}
+
+#[test]
+fn test_html_report_is_escaped_and_collapsible() {
+    let html = render(ReportFormat::Html);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert_eq!(html.matches("<details>").count(), 3);
+    assert!(html.contains("/cache/&lt;b&gt;.jpg"));
+    assert!(!html.contains("<b>.jpg"));
+}
+
+#[test]
+fn test_report_format_from_path() {
+    use std::path::Path;
+
+    assert_eq!(ReportFormat::from_path(Path::new("out/report.HTML")), Some(ReportFormat::Html));
+    assert_eq!(ReportFormat::from_path(Path::new("report.csv")), Some(ReportFormat::Csv));
+    assert_eq!(ReportFormat::from_path(Path::new("report.txt")), None);
+    assert!("xml".parse::<ReportFormat>().is_err());
+}
@@ -0,0 +1,79 @@
+//! 扫描结果文件格式集成测试
+//!
+//! 测试版本头部的读写、旧版本文件的迁移以及未知新版本的错误提示。
+
+use std::fs;
+use std::time::Duration;
+use tempfile::TempDir;
+use wechat_cleaner::errors::Error;
+use wechat_cleaner::migrator::CURRENT_FORMAT_VERSION;
+use wechat_cleaner::scanner::ScanResult;
+
+/// 版本 1 的扫描结果：没有版本头部，`scan_time` 为 `{ secs, nanos }`
+const LEGACY_V1: &str = r#"{
+  "path": "/tmp/wechat-cleaner/scan-result.json",
+  "total_files_count": 3,
+  "duplicate_count": 2,
+  "duplicate_files": {
+    "photo": [
+      { "path": "/cache/photo.jpg", "size": 10, "modified": 1640995200 },
+      { "path": "/cache/photo(1).jpg", "size": 10, "modified": 1640995300 }
+    ]
+  },
+  "scan_time": { "secs": 2, "nanos": 340000000 }
+}"#;
+
+#[test]
+fn test_save_and_load_roundtrip() {
+    let temp_dir = TempDir::new().unwrap();
+    let result = ScanResult {
+        path: temp_dir.path().join("wechat-cleaner/scan-result.json"),
+        settings_fingerprint: "abc".to_string(),
+        scan_time: Duration::from_millis(1500),
+        ..Default::default()
+    };
+    result.save().unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&result.path).unwrap()).unwrap();
+    assert_eq!(json["format_version"], CURRENT_FORMAT_VERSION);
+    assert_eq!(json["tool_version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(json["scan_time_ms"], 1500);
+
+    let loaded = ScanResult::load(&result.path).unwrap();
+    assert_eq!(loaded.settings_fingerprint, "abc");
+    assert_eq!(loaded.scan_time, Duration::from_millis(1500));
+}
+
+#[test]
+fn test_load_migrates_legacy_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("scan-result.json");
+    fs::write(&path, LEGACY_V1).unwrap();
+
+    let loaded = ScanResult::load(&path).unwrap();
+    assert_eq!(loaded.format_version, CURRENT_FORMAT_VERSION);
+    assert_eq!(loaded.tool_version, "unknown");
+    assert_eq!(loaded.id, "scan-result");
+    assert_eq!(loaded.created_at, 0);
+    assert_eq!(loaded.scan_time, Duration::from_millis(2340));
+    assert_eq!(loaded.duplicate_files["photo"].len(), 2);
+    assert!(loaded.leftover_files.is_empty());
+    assert!(loaded.verified_groups.is_empty());
+}
+
+#[test]
+fn test_load_rejects_future_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("scan-result.json");
+    let future = format!(r#"{{"format_version": {}}}"#, CURRENT_FORMAT_VERSION + 1);
+    fs::write(&path, future).unwrap();
+
+    match ScanResult::load(&path) {
+        Err(Error::UnsupportedFormatVersion { found, supported }) => {
+            assert_eq!(found, CURRENT_FORMAT_VERSION + 1);
+            assert_eq!(supported, CURRENT_FORMAT_VERSION);
+        }
+        other => panic!("expected UnsupportedFormatVersion, got {:?}", other),
+    }
+}
@@ -0,0 +1,105 @@
+//! 扫描器构建器集成测试
+//!
+//! 不经过配置文件，直接通过公开的构建器 API 和构造函数使用核心库。
+
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::TempDir;
+use wechat_cleaner::config::settings::ScannerSettings;
+use wechat_cleaner::errors::Error;
+use wechat_cleaner::file_utils::{HasPath, HasSize};
+use wechat_cleaner::storage::StorageFormat;
+use wechat_cleaner::{ContentHasher, FileInfo, MemoryFs, Scanner};
+
+/// 只按内容长度计算“哈希”，并统计调用次数
+struct LengthHasher(Arc<AtomicUsize>);
+
+impl ContentHasher for LengthHasher {
+    fn name(&self) -> &str {
+        "length"
+    }
+
+    fn hash_reader(&self, reader: &mut dyn Read) -> io::Result<String> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        Ok(content.len().to_string())
+    }
+}
+
+fn scanner_settings() -> ScannerSettings {
+    ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    }
+}
+
+fn create_fs() -> Arc<MemoryFs> {
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file("/cache/a.pdf", b"aaaa".to_vec(), 1)
+        .add_file("/cache/b.pdf", b"bbbb".to_vec(), 2)
+        .add_file("/cache/c.pdf", b"cc".to_vec(), 3);
+    fs
+}
+
+#[test]
+fn test_builder_uses_custom_hasher() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let scanner = Scanner::builder()
+        .root("/cache")
+        .hasher(LengthHasher(calls.clone()))
+        .fs(create_fs())
+        .build()
+        .unwrap();
+
+    let result = scanner.scan(&scanner_settings()).unwrap();
+
+    // 长度相同的两个文件被自定义算法视为重复，只有它们需要计算哈希
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(result.duplicate_files["4"].len(), 2);
+    assert!(result.verified_groups.contains("4"));
+}
+
+#[test]
+fn test_default_hasher_and_fingerprint() {
+    let fs = create_fs();
+    let md5 = Scanner::builder().root("/cache").fs(fs.clone()).build().unwrap();
+    let length = Scanner::builder()
+        .root("/cache")
+        .hasher(LengthHasher(Arc::default()))
+        .fs(fs)
+        .build()
+        .unwrap();
+
+    // 默认的 MD5 能区分内容不同但长度相同的文件
+    assert!(md5.scan(&scanner_settings()).unwrap().duplicate_files.is_empty());
+    assert_ne!(md5.settings_fingerprint(), length.settings_fingerprint());
+}
+
+#[test]
+fn test_builder_validates_settings() {
+    assert!(matches!(Scanner::builder().build(), Err(Error::CacheNotFound)));
+    assert!(matches!(
+        Scanner::builder().root("/cache").pattern("(").build(),
+        Err(Error::Regex(_))
+    ));
+}
+
+#[test]
+fn test_file_info_constructors() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("a.jpg");
+    std::fs::write(&path, b"photo").unwrap();
+
+    let info = FileInfo::from_path(&path).unwrap();
+    assert_eq!(info.size(), 5);
+    assert_eq!(info.path(), &path);
+    assert!(FileInfo::from_path(temp_dir.path()).is_err());
+
+    let info = FileInfo::new("/cache/a.jpg", 42, 7);
+    assert_eq!(info.path(), Path::new("/cache/a.jpg"));
+    assert_eq!((info.size(), info.modified), (42, 7));
+}
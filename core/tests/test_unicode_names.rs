@@ -0,0 +1,181 @@
+//! 文件名规范化集成测试
+//!
+//! 按文件名分组前把名称规范化为 NFC，组合形式与分解形式的同名文件视为相同；
+//! 读取和删除使用原始路径；无法解码的文件名单独列出，不按名称与其他文件合并，只按内容检测。
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use unicode_normalization::UnicodeNormalization;
+use wechat_cleaner::cancel::CancellationToken;
+use wechat_cleaner::cleaner::CleaningPreview;
+use wechat_cleaner::config::settings::{CleanerSettings, ScannerSettings};
+use wechat_cleaner::file_utils::{HasPath, normalize_name};
+use wechat_cleaner::filesystem::MemoryFs;
+use wechat_cleaner::progress::NoProgress;
+use wechat_cleaner::storage::StorageFormat;
+use wechat_cleaner::{ScanResult, Scanner};
+
+fn scan(fs: &Arc<MemoryFs>) -> ScanResult {
+    let settings = ScannerSettings {
+        save_path: "/results".into(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    Scanner::builder().root("/cache").fs(fs.clone()).build().unwrap().scan(&settings).unwrap()
+}
+
+#[test]
+fn test_nfc_and_nfd_names_are_grouped_together() {
+    let nfc = "旅行-lǚxíng";
+    let nfd: String = nfc.nfd().collect();
+    assert_ne!(nfc, nfd);
+    assert_eq!(normalize_name(nfd.as_ref()).unwrap(), nfc);
+
+    let nfd_copy = PathBuf::from(format!("/cache/{}(1).pdf", nfd));
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file(format!("/cache/{}.pdf", nfc), b"same".to_vec(), 1)
+        .add_file(&nfd_copy, b"same".to_vec(), 2);
+
+    let result = scan(&fs);
+    assert_eq!(result.duplicate_files.len(), 1);
+    assert!(result.undecodable_files.is_empty());
+
+    // 删除时使用原始的分解形式路径
+    let preview = CleaningPreview::from(&result).unwrap();
+    let cleaned = preview
+        .clean_with_fs(fs.as_ref(), &CleanerSettings::default(), &NoProgress, &CancellationToken::new())
+        .unwrap();
+    assert_eq!(cleaned.files_deleted, 1);
+    assert!(!fs.exists(&nfd_copy));
+    assert!(fs.exists(format!("/cache/{}.pdf", nfc)));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_undecodable_names_are_reported_not_merged() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let first = PathBuf::from(OsStr::from_bytes(b"/cache/\xff\xfe.pdf"));
+    let second = PathBuf::from(OsStr::from_bytes(b"/cache/\xff\xfd.pdf"));
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file(&first, b"one".to_vec(), 1).add_file(&second, b"two".to_vec(), 2);
+
+    // 替换字符后两个名称相同，但不会被当作重复文件
+    let result = scan(&fs);
+    assert!(result.duplicate_files.is_empty());
+    let mut undecodable: Vec<&PathBuf> = result.undecodable_files.iter().map(|file| file.path()).collect();
+    undecodable.sort();
+    assert_eq!(undecodable, [&second, &first]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_undecodable_names_are_compared_by_content() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let undecodable = PathBuf::from(OsStr::from_bytes(b"/cache/\xff.pdf"));
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file(&undecodable, b"same".to_vec(), 1).add_file("/cache/other.pdf", b"same".to_vec(), 2);
+
+    let result = scan(&fs);
+    assert_eq!(result.undecodable_files.len(), 1);
+    assert_eq!(result.verified_groups.len(), 1);
+    let mut paths: Vec<PathBuf> = result.duplicate_files.values().flatten().map(|file| file.path().clone()).collect();
+    paths.sort();
+    assert_eq!(paths, [PathBuf::from("/cache/other.pdf"), undecodable]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_undecodable_roots_have_distinct_fingerprints() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let fingerprint = |root: &[u8]| {
+        Scanner::builder()
+            .root(OsStr::from_bytes(root))
+            .fs(Arc::new(MemoryFs::new()))
+            .build()
+            .unwrap()
+            .settings_fingerprint()
+    };
+    // 替换字符后两个路径相同
+    assert_ne!(fingerprint(b"/cache/\xff"), fingerprint(b"/cache/\xfe"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_raw_paths_survive_json_and_binary_storage() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = PathBuf::from(OsStr::from_bytes(b"/cache/\xe5\x9b"));
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file(dir.join("a.pdf"), b"same".to_vec(), 1)
+        .add_file(dir.join("a(1).pdf"), b"same".to_vec(), 2)
+        .add_file(OsStr::from_bytes(b"/cache/\xff.doc"), b"alone".to_vec(), 3);
+    let mut result = scan(&fs);
+    assert_eq!(result.duplicate_count, 2);
+
+    let temp_dir = TempDir::new().unwrap();
+    for name in ["result.json", "result.bin"] {
+        result.path = temp_dir.path().join(name);
+        result.save().unwrap();
+        let loaded = ScanResult::load(&result.path).unwrap();
+
+        let mut paths: Vec<PathBuf> = loaded.duplicate_files.values().flatten().map(|file| file.path().clone()).collect();
+        paths.sort();
+        assert_eq!(paths, [dir.join("a(1).pdf"), dir.join("a.pdf")], "{name}");
+        assert_eq!(loaded.undecodable_files[0].path().as_os_str().as_bytes(), b"/cache/\xff.doc", "{name}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cleaning_log_keeps_raw_paths() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    use wechat_cleaner::history::ScanHistory;
+
+    let root = PathBuf::from(OsStr::from_bytes(b"/cache/\xe5\x9b"));
+    let leftover = root.join(OsStr::from_bytes(b"\xff.tmp"));
+    let fs = Arc::new(MemoryFs::new());
+    fs.add_file(root.join("a.pdf"), b"same".to_vec(), 1)
+        .add_file(root.join("a(1).pdf"), b"same".to_vec(), 2)
+        .add_file(&leftover, Vec::new(), 3)
+        .set_capacity(1 << 20);
+
+    let temp_dir = TempDir::new().unwrap();
+    let settings = ScannerSettings {
+        save_path: temp_dir.path().to_path_buf(),
+        storage: StorageFormat::Json,
+        ..Default::default()
+    };
+    let mut result = Scanner::builder().root(&root).fs(fs.clone()).build().unwrap().scan(&settings).unwrap();
+    assert_eq!(result.root_devices.keys().collect::<Vec<_>>(), [&root]);
+
+    // 根目录、设备映射的键等路径在保存和读取后保持原样
+    let mut history = ScanHistory::open(&settings).unwrap();
+    history.record(&mut result).unwrap();
+    let loaded = history.load(&result.id).unwrap();
+    assert_eq!(loaded.roots.iter().collect::<Vec<_>>(), [&root]);
+    assert_eq!(loaded.root_devices.keys().collect::<Vec<_>>(), [&root]);
+
+    let cleaner_settings = CleanerSettings { clean_leftovers: true, leftover_min_age_hours: 0, ..Default::default() };
+    let preview = CleaningPreview::with_settings(&loaded, &cleaner_settings).unwrap();
+    assert_eq!(preview.leftover_files[0].path(), &leftover);
+    assert!(serde_json::to_string(&preview).is_ok());
+
+    let cleaned = preview.clean_with_fs(fs.as_ref(), &cleaner_settings, &NoProgress, &CancellationToken::new()).unwrap();
+    assert_eq!(cleaned.leftover_files_deleted, 1);
+    assert!(!fs.exists(&leftover));
+
+    let log = history.save_cleaning(&result.id, &cleaned).unwrap();
+    let log: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(log).unwrap()).unwrap();
+    let raw: Vec<u8> = leftover.as_os_str().as_bytes().to_vec();
+    assert!(log["outcomes"].as_array().unwrap().iter().any(|outcome| outcome["path"]["raw"] == serde_json::json!(raw)));
+    assert_eq!(log["free_space"][0]["root"]["raw"], serde_json::json!(root.as_os_str().as_bytes()));
+}
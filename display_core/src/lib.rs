@@ -34,6 +34,20 @@ impl DisplayValue for usize {
     }
 }
 
+// 有符号数值表示变化量：i64 为空间变化，isize 为数量变化
+impl DisplayValue for i64 {
+    fn format_display(&self) -> String {
+        let sign = if *self < 0 { "-" } else { "+" };
+        format!("{}{}", sign, format_size(self.unsigned_abs()))
+    }
+}
+
+impl DisplayValue for isize {
+    fn format_display(&self) -> String {
+        format!("{:+}", self)
+    }
+}
+
 impl DisplayValue for String {
     fn format_display(&self) -> String {
         self.clone()
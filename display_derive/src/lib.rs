@@ -5,7 +5,7 @@ use syn::{parse_macro_input, DeriveInput, Data, Fields, Field, Attribute, Meta};
 /// Derive macro for implementing Display trait
 /// 
 /// Usage:
-/// ```
+/// ```ignore
 /// #[derive(Display)]
 /// pub struct MyStruct {
 ///     #[display(summary, name="自定义名称")]
@@ -122,39 +122,36 @@ fn parse_display_attribute(attrs: &[Attribute]) -> Option<DisplayAttribute> {
         if attr.path().is_ident("display") {
             found_display = true;
             
-            match &attr.meta {
-                Meta::List(meta_list) => {
-                    let tokens = &meta_list.tokens;
-                    let content = tokens.to_string();
+            if let Meta::List(meta_list) = &attr.meta {
+                let tokens = &meta_list.tokens;
+                let content = tokens.to_string();
+                
+                // 解析逗号分隔的参数
+                for part in content.split(',') {
+                    let part = part.trim();
                     
-                    // 解析逗号分隔的参数
-                    for part in content.split(',') {
-                        let part = part.trim();
+                    if part == "summary" {
+                        display_attr.summary = true;
+                    } else if part == "details" {
+                        display_attr.details = true;
+                        display_attr.details_only = !display_attr.summary;
+                    } else if part.starts_with("name=") || part.starts_with("name =") {
+                        // 解析 name="value" 或 name = "value" 格式
+                        let name_value = if part.starts_with("name =") {
+                            part.strip_prefix("name =")
+                        } else {
+                            part.strip_prefix("name=")
+                        };
                         
-                        if part == "summary" {
-                            display_attr.summary = true;
-                        } else if part == "details" {
-                            display_attr.details = true;
-                            display_attr.details_only = !display_attr.summary;
-                        } else if part.starts_with("name=") || part.starts_with("name =") {
-                            // 解析 name="value" 或 name = "value" 格式
-                            let name_value = if part.starts_with("name =") {
-                                part.strip_prefix("name =")
-                            } else {
-                                part.strip_prefix("name=")
-                            };
-                            
-                            if let Some(name_value) = name_value {
-                                let name_value = name_value.trim();
-                                if name_value.starts_with('"') && name_value.ends_with('"') {
-                                    let extracted_name = &name_value[1..name_value.len()-1];
-                                    display_attr.name = Some(extracted_name.to_string());
-                                }
+                        if let Some(name_value) = name_value {
+                            let name_value = name_value.trim();
+                            if name_value.starts_with('"') && name_value.ends_with('"') {
+                                let extracted_name = &name_value[1..name_value.len()-1];
+                                display_attr.name = Some(extracted_name.to_string());
                             }
                         }
                     }
                 }
-                _ => {}
             }
         }
     }